@@ -1,8 +1,30 @@
-use clap::Parser;
+mod bench;
+use bench::BenchCommand;
+mod book;
+use book::BookCommand;
+mod datagen;
+use datagen::DatagenCommand;
+mod diagram;
+use diagram::DiagramCommand;
+mod mate;
+use mate::MateCommand;
+mod play;
+use play::PlayCommand;
+mod random_fen;
+use random_fen::RandomFenCommand;
+mod tb;
+use tb::TbCommand;
+mod testsuite;
+use testsuite::TestsuiteCommand;
+mod uci;
+use uci::UCICommand;
+
+use clap::{Parser, Subcommand};
 use knightmare::{
-    error::Error,
+    error::{Error, ErrorExt},
     fen::{FromFENString, FEN_START},
     game::GameState,
+    str,
 };
 
 /// The builder tool for AcaciaLinux
@@ -11,6 +33,24 @@ pub struct Cli {
     /// The log level to operate on (0 = info, 1 = debug, * = trace)
     #[arg(long = "loglevel", short = 'v', default_value_t = 0, global = true)]
     pub loglevel: u8,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The subcommands of the CLI
+#[derive(Subcommand)]
+pub enum Command {
+    Bench(BenchCommand),
+    Book(BookCommand),
+    Datagen(DatagenCommand),
+    Diagram(DiagramCommand),
+    Mate(MateCommand),
+    Play(PlayCommand),
+    RandomFen(RandomFenCommand),
+    Tb(TbCommand),
+    Testsuite(TestsuiteCommand),
+    Uci(UCICommand),
 }
 
 impl Cli {
@@ -25,9 +65,24 @@ impl Cli {
         }
         pretty_env_logger::init();
 
+        if let Some(command) = &self.command {
+            return match command {
+                Command::Bench(command) => command.run(),
+                Command::Book(command) => command.run(),
+                Command::Datagen(command) => command.run(),
+                Command::Diagram(command) => command.run(),
+                Command::Mate(command) => command.run(),
+                Command::Play(command) => command.run(),
+                Command::RandomFen(command) => command.run(),
+                Command::Tb(command) => command.run(),
+                Command::Testsuite(command) => command.run(),
+                Command::Uci(command) => command.run(),
+            };
+        }
+
         println!("Knightmare\n");
 
-        let game = GameState::from_fen(FEN_START).expect("LOPP");
+        let game = GameState::from_fen(FEN_START).ctx(str!("Parsing the starting position"))?;
 
         for (coordinate, piece) in game.board.get_occupied_fields_rm() {
             println!(
@@ -0,0 +1,57 @@
+use clap::Parser;
+use knightmare::{
+    bench::{self, BenchResult, BENCH_POSITIONS, DEFAULT_DEPTH, TREE_DEPTH},
+    error::Error,
+};
+
+/// Measure the speed of the engine on a fixed set of positions
+#[derive(Parser)]
+pub struct BenchCommand {
+    /// The depth to search every position to
+    #[arg(default_value_t = DEFAULT_DEPTH)]
+    pub depth: u8,
+
+    /// The number of threads to search with
+    #[arg(default_value_t = 1)]
+    pub threads: usize,
+
+    /// The transposition table size in megabytes
+    #[arg(default_value_t = 16)]
+    pub hash: usize,
+}
+
+impl BenchCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let search = bench::search(self.depth, self.threads, self.hash, &mut |index, result| {
+            println!(
+                "Position {:>2}/{}: {} nodes, best move {}",
+                index + 1,
+                BENCH_POSITIONS.len(),
+                result.nodes,
+                result
+                    .best_move
+                    .map_or_else(|| "(none)".to_string(), |mv| mv.to_string())
+            );
+        });
+        let movegen = bench::movegen(TREE_DEPTH);
+        let make_move = bench::make_move(TREE_DEPTH);
+
+        let line = |name: &str, result: &BenchResult| {
+            println!(
+                "{name:<16}: {} moves in {} ms, {} moves/s",
+                result.count,
+                result.time.as_millis(),
+                result.per_second()
+            );
+        };
+
+        println!("===========================");
+        println!("Total time (ms) : {}", search.time.as_millis());
+        println!("Nodes searched  : {}", search.count);
+        println!("Nodes/second    : {}", search.per_second());
+        line("Move generation", &movegen);
+        line("Make move", &make_move);
+
+        Ok(0)
+    }
+}
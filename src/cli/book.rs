@@ -0,0 +1,99 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use knightmare::{
+    error::{Error, ErrorExt},
+    fen::{FENMode, FEN_START},
+    game::GameState,
+    polyglot::{build_book, BookBuildConfig, PolyglotBook},
+    str,
+};
+
+/// Work with Polyglot opening books
+#[derive(Parser)]
+pub struct BookCommand {
+    #[command(subcommand)]
+    pub command: BookSubcommand,
+}
+
+/// The subcommands of `book`
+#[derive(Subcommand)]
+pub enum BookSubcommand {
+    /// Build a book from a PGN collection
+    Build {
+        /// The PGN file to read the games from
+        pgn: PathBuf,
+
+        /// The file to write the book to
+        #[arg(long, short)]
+        output: PathBuf,
+
+        /// Leave out moves that have been played in fewer games
+        #[arg(long, default_value_t = 3)]
+        min_games: u32,
+
+        /// Only use this many plies from the start of each game
+        #[arg(long, default_value_t = 20)]
+        max_ply: usize,
+    },
+    /// Show the book moves for a position
+    Probe {
+        /// The book file
+        book: PathBuf,
+
+        /// The position to look up, the starting position if not given
+        #[arg(long)]
+        fen: Option<String>,
+    },
+}
+
+impl BookCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        match &self.command {
+            BookSubcommand::Build {
+                pgn,
+                output,
+                min_games,
+                max_ply,
+            } => {
+                let file = File::open(pgn).ctx(str!("Opening PGN file {}", pgn.display()))?;
+                let config = BookBuildConfig {
+                    min_games: *min_games,
+                    max_ply: *max_ply,
+                };
+
+                let (book, stats) = build_book(BufReader::new(file), &config)?;
+                book.save(output)?;
+
+                println!(
+                    "Wrote {} entries from {} games to {} ({} games skipped)",
+                    book.entries().len(),
+                    stats.games,
+                    output.display(),
+                    stats.errors
+                );
+            }
+            BookSubcommand::Probe { book, fen } => {
+                let book = PolyglotBook::open(book)?;
+                let fen = fen.as_deref().unwrap_or(FEN_START);
+                let state = GameState::from_fen_with(fen, FENMode::Strict)
+                    .ctx(str!("Parsing FEN '{fen}'"))?;
+
+                let moves = book.moves(&state);
+                let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+
+                println!("Key: {:016x}", state.polyglot_key());
+                for (mv, weight) in moves {
+                    println!(
+                        "{:<8} {:>6} {:>6.2}%",
+                        mv.to_san(&state),
+                        weight,
+                        weight as f64 * 100.0 / total.max(1) as f64
+                    );
+                }
+            }
+        }
+
+        Ok(0)
+    }
+}
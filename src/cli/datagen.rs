@@ -0,0 +1,98 @@
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use clap::Parser;
+use knightmare::{
+    datagen::{generate, DataFormat, DatagenConfig},
+    error::{Error, ErrorExt},
+    random::Random,
+    search::SearchLimits,
+    str,
+};
+
+/// Generate training data by letting the engine play against itself
+#[derive(Parser)]
+pub struct DatagenCommand {
+    /// The file to write the training data to
+    #[arg(long, short)]
+    pub output: PathBuf,
+
+    /// The format to write the training data in
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    pub format: DataFormat,
+
+    /// The number of games to play
+    #[arg(long, default_value_t = 1000)]
+    pub games: u64,
+
+    /// The number of games to play in parallel
+    #[arg(long, default_value_t = 1)]
+    pub threads: usize,
+
+    /// The number of nodes to search per move
+    #[arg(long, default_value_t = 5000)]
+    pub nodes: u64,
+
+    /// The depth to search per move, replaces the node limit
+    #[arg(long)]
+    pub depth: Option<u8>,
+
+    /// The number of random moves to start each game with
+    #[arg(long, default_value_t = 8)]
+    pub random_plies: u8,
+
+    /// Discard openings the engine scores beyond this many centipawns
+    #[arg(long, default_value_t = 300)]
+    pub max_opening_score: i32,
+
+    /// The transposition table size per thread in megabytes
+    #[arg(long, default_value_t = 16)]
+    pub hash: usize,
+
+    /// The seed for the random openings, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+impl DatagenCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let limits = match self.depth {
+            Some(depth) => SearchLimits {
+                depth: Some(depth),
+                ..Default::default()
+            },
+            None => SearchLimits {
+                nodes: Some(self.nodes),
+                ..Default::default()
+            },
+        };
+
+        let config = DatagenConfig {
+            games: self.games,
+            threads: self.threads,
+            limits,
+            random_plies: self.random_plies,
+            max_opening_score: self.max_opening_score,
+            hash_mb: self.hash,
+            seed: self.seed.unwrap_or_else(|| Random::from_time().next_u64()),
+        };
+
+        let file = File::create(&self.output)
+            .ctx(str!("Creating output file {}", self.output.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let stats =
+            generate(&config, self.format, &mut writer).ctx(str!("Generating training data"))?;
+
+        println!(
+            "Played {} games (+{} ={} -{}), wrote {} positions to {}",
+            stats.games,
+            stats.white_wins,
+            stats.draws,
+            stats.black_wins,
+            stats.positions,
+            self.output.display()
+        );
+
+        Ok(0)
+    }
+}
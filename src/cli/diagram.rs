@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use knightmare::{
+    coordinate::Coordinate,
+    error::{Error, ErrorExt},
+    fen::{FENMode, FromFENString, FEN_START},
+    game::GameState,
+    moves::Move,
+    san::FromSANError,
+    str,
+    svg::SvgOptions,
+};
+
+/// Draw a position as an SVG image
+#[derive(Parser)]
+pub struct DiagramCommand {
+    /// The position to draw instead of the initial position
+    #[arg(long)]
+    pub fen: Option<String>,
+
+    /// The file to write the image to
+    #[arg(long, short)]
+    pub output: PathBuf,
+
+    /// Show the board from black's side
+    #[arg(long)]
+    pub flip: bool,
+
+    /// Leave out the rank and file labels
+    #[arg(long)]
+    pub no_coordinates: bool,
+
+    /// The size of a square in pixels
+    #[arg(long, default_value_t = 45)]
+    pub size: u32,
+
+    /// The move that led to the position, in coordinate notation (e2e4)
+    #[arg(long)]
+    pub last_move: Option<String>,
+
+    /// A move to draw an arrow for, in coordinate notation (g1f3)
+    #[arg(long = "arrow")]
+    pub arrows: Vec<String>,
+
+    /// A square to highlight (e4)
+    #[arg(long = "highlight")]
+    pub highlights: Vec<String>,
+}
+
+impl DiagramCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let fen = self.fen.as_deref().unwrap_or(FEN_START);
+        let state =
+            GameState::from_fen_with(fen, FENMode::Strict).ctx(str!("Parsing FEN '{fen}'"))?;
+
+        let parse_move = |text: &str| {
+            Move::from_uci(text)
+                .ok_or_else(|| FromSANError::InvalidFormat(text.to_string()))
+                .ctx(str!("Parsing move '{text}'"))
+        };
+
+        let last_move = self.last_move.as_deref().map(parse_move).transpose()?;
+        let arrows = self
+            .arrows
+            .iter()
+            .map(|text| parse_move(text))
+            .collect::<Result<Vec<Move>, Error>>()?;
+        let highlights = self
+            .highlights
+            .iter()
+            .map(|text| Coordinate::from_fen(text).ctx(str!("Parsing square '{text}'")))
+            .collect::<Result<Vec<Coordinate>, Error>>()?;
+
+        let options = SvgOptions {
+            square_size: self.size.max(1),
+            coordinates: !self.no_coordinates,
+            flipped: self.flip,
+            last_move,
+            arrows,
+            highlights,
+            check: None,
+        };
+
+        std::fs::write(&self.output, state.to_svg(&options))
+            .ctx(str!("Writing diagram to {}", self.output.display()))?;
+
+        Ok(0)
+    }
+}
@@ -0,0 +1,75 @@
+use clap::Parser;
+use knightmare::{
+    color::Color,
+    error::{Error, ErrorExt},
+    fen::FENMode,
+    game::GameState,
+    mate::{MateResult, MateSearch},
+    moves::Move,
+    str,
+};
+
+/// Search a position for a forced mate by the side to move
+#[derive(Parser)]
+pub struct MateCommand {
+    /// The position to search
+    #[arg(long)]
+    pub fen: String,
+
+    /// The largest number of moves to search for a mate in
+    #[arg(long, default_value_t = 3)]
+    pub moves: u32,
+}
+
+impl MateCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let state = GameState::from_fen_with(&self.fen, FENMode::Strict)
+            .ctx(str!("Parsing FEN '{}'", self.fen))?;
+
+        let mut search = MateSearch::new();
+        let result = search.search(&state, self.moves, &mut |moves, nodes| {
+            log::info!("No mate in {moves} ({nodes} nodes)")
+        });
+
+        // Not finding a mate is a valid answer, only an unfinished search is a failure
+        match result {
+            MateResult::Mate { moves, line } => {
+                println!("Mate in {moves}: {}", format_line(&state, &line));
+                Ok(0)
+            }
+            MateResult::NoMate => {
+                println!(
+                    "No forced mate in {} moves ({} positions searched)",
+                    self.moves,
+                    search.nodes()
+                );
+                Ok(0)
+            }
+            MateResult::Aborted => {
+                println!(
+                    "Search aborted after {} positions, a mate may still exist",
+                    search.nodes()
+                );
+                Ok(1)
+            }
+        }
+    }
+}
+
+/// Formats `line` in SAN with move numbers, e.g. `1. Qh5+ Kd8 2. Qf7#`
+fn format_line(state: &GameState, line: &[Move]) -> String {
+    let mut state = *state;
+    let mut parts = Vec::new();
+
+    for (i, mv) in line.iter().enumerate() {
+        if state.side_to_move == Color::White {
+            parts.push(format!("{}.", state.fullmove_counter));
+        } else if i == 0 {
+            parts.push(format!("{}...", state.fullmove_counter));
+        }
+        parts.push(mv.to_san(&state));
+        state = state.make_move(*mv);
+    }
+
+    parts.join(" ")
+}
@@ -0,0 +1,255 @@
+use std::io::{BufRead, Write};
+
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use knightmare::{
+    color::Color,
+    error::{Error, ErrorExt},
+    fen::{FENMode, ToFENString, FEN_START},
+    game::{GameState, Outcome},
+    moves::Move,
+    render::{PieceStyle, RenderOptions},
+    search::{SearchLimits, Searcher},
+    str,
+};
+
+/// The side the player plays
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PlayerColor {
+    White,
+    Black,
+}
+
+/// Play a game against the engine in the terminal
+#[derive(Parser)]
+pub struct PlayCommand {
+    /// The color to play with
+    #[arg(long, value_enum, default_value_t = PlayerColor::White)]
+    pub color: PlayerColor,
+
+    /// The position to start from instead of the initial position
+    #[arg(long)]
+    pub fen: Option<String>,
+
+    /// The depth the engine searches to
+    #[arg(long, default_value_t = 6)]
+    pub depth: u8,
+
+    /// The transposition table size in megabytes
+    #[arg(long, default_value_t = 64)]
+    pub hash: usize,
+
+    /// Draw the pieces as Unicode figurines instead of letters
+    #[arg(long)]
+    pub unicode: bool,
+}
+
+/// A game in progress
+struct Game {
+    /// The positions of the game, the current one last
+    states: Vec<GameState>,
+    /// The moves that led to each position after the first
+    moves: Vec<Move>,
+    player: Color,
+    flipped: bool,
+    style: PieceStyle,
+    searcher: Searcher,
+    limits: SearchLimits,
+}
+
+impl PlayCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let fen = self.fen.as_deref().unwrap_or(FEN_START);
+        let start =
+            GameState::from_fen_with(fen, FENMode::Strict).ctx(str!("Parsing FEN '{fen}'"))?;
+        let player = match self.color {
+            PlayerColor::White => Color::White,
+            PlayerColor::Black => Color::Black,
+        };
+
+        let mut game = Game {
+            states: vec![start],
+            moves: Vec::new(),
+            player,
+            flipped: player == Color::Black,
+            style: if self.unicode {
+                PieceStyle::Unicode
+            } else {
+                PieceStyle::Ascii
+            },
+            searcher: Searcher::new(self.hash),
+            limits: SearchLimits {
+                depth: Some(self.depth),
+                ..Default::default()
+            },
+        };
+
+        println!("You play {player}. Type 'help' for the commands.");
+        game.play()
+    }
+}
+
+impl Game {
+    /// Returns the current position
+    fn state(&self) -> GameState {
+        *self.states.last().expect("[DEV] Game without positions")
+    }
+
+    /// Returns the hashes of the positions before the current one
+    fn history(&self) -> Vec<u64> {
+        self.states[..self.states.len() - 1]
+            .iter()
+            .map(|s| s.hash())
+            .collect()
+    }
+
+    /// Returns the outcome of the game if it has ended, including repetitions
+    fn outcome(&self) -> Option<Outcome> {
+        let state = self.state();
+        let repetitions = self
+            .states
+            .iter()
+            .filter(|s| s.hash() == state.hash())
+            .count();
+
+        state
+            .outcome()
+            .or_else(|| (repetitions >= 3).then_some(Outcome::ThreefoldRepetition))
+    }
+
+    /// Lets the engine search the current position
+    fn search(&mut self) -> Option<Move> {
+        let state = self.state();
+        let history = self.history();
+
+        self.searcher
+            .search(&state, &history, &self.limits)
+            .best_move
+    }
+
+    /// Plays `mv` in the current position
+    fn push(&mut self, mv: Move) {
+        let next = self.state().make_move(mv);
+        self.states.push(next);
+        self.moves.push(mv);
+    }
+
+    /// Runs the game loop until the game ends or the player leaves
+    fn play(&mut self) -> Result<i32, Error> {
+        let stdin = std::io::stdin();
+        let mut input = stdin.lock();
+
+        loop {
+            if let Some(outcome) = self.outcome() {
+                self.print_board();
+                println!("{}", describe(outcome).bold());
+                return Ok(0);
+            }
+
+            let state = self.state();
+            if state.side_to_move != self.player {
+                let Some(mv) = self.search() else {
+                    return Ok(0);
+                };
+                println!("Knightmare plays {}", mv.to_san(&state).bold());
+                self.push(mv);
+                continue;
+            }
+
+            self.print_board();
+            print!("{} ", ">".bold());
+            std::io::stdout().flush().ctx(str!("Writing prompt"))?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line).ctx(str!("Reading move"))? == 0 {
+                return Ok(0);
+            }
+
+            match line.trim() {
+                "" => {}
+                "help" => print_help(),
+                "quit" | "exit" => return Ok(0),
+                "undo" => {
+                    if self.moves.is_empty() {
+                        println!("There is no move to take back");
+                    }
+                    // Take back the engine's reply as well
+                    while self.moves.pop().is_some() {
+                        self.states.pop();
+                        if self.state().side_to_move == self.player {
+                            break;
+                        }
+                    }
+                }
+                "flip" => self.flipped = !self.flipped,
+                "fen" => println!("{}", state.to_fen()),
+                "hint" => match self.search() {
+                    Some(mv) => println!("Hint: {}", mv.to_san(&state).bold()),
+                    None => println!("There is no legal move"),
+                },
+                "resign" => {
+                    println!(
+                        "{}",
+                        format!("{} wins by resignation", self.player.opposite()).bold()
+                    );
+                    return Ok(0);
+                }
+                text => match parse_move(&state, text) {
+                    Some(mv) => self.push(mv),
+                    None => println!("{}", format!("Illegal or unknown move '{text}'").red()),
+                },
+            }
+        }
+    }
+
+    /// Prints the board from the current perspective
+    fn print_board(&self) {
+        let state = self.state();
+        let options = RenderOptions {
+            style: self.style,
+            colors: true,
+            flipped: self.flipped,
+            highlight: self.moves.last().copied(),
+            ..Default::default()
+        };
+
+        println!("\n{}\n", state.render(&options));
+
+        if let (Some(mv), Some(previous)) = (self.moves.last(), self.states.iter().rev().nth(1)) {
+            println!("Last move: {}", mv.to_san(previous));
+        }
+        if state.is_check() {
+            println!("{}", "Check!".red().bold());
+        }
+    }
+}
+
+/// Parses a move in coordinate notation or SAN
+fn parse_move(state: &GameState, text: &str) -> Option<Move> {
+    Move::from_uci(text)
+        .filter(|mv| state.is_legal(*mv))
+        .or_else(|| state.parse_san(text).ok())
+}
+
+/// Describes how the game ended
+fn describe(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Checkmate { winner } => format!("Checkmate, {winner} wins"),
+        Outcome::Stalemate => "Stalemate, the game is drawn".to_string(),
+        Outcome::FiftyMoveRule => "Draw by the 50 move rule".to_string(),
+        Outcome::ThreefoldRepetition => "Draw by threefold repetition".to_string(),
+        Outcome::InsufficientMaterial => "Draw by insufficient material".to_string(),
+        Outcome::VariantWin { winner } => format!("{winner} wins by the rules of the variant"),
+        Outcome::VariantDraw => "Draw by the rules of the variant".to_string(),
+    }
+}
+
+fn print_help() {
+    println!("Enter moves in SAN (Nf3, exd5, O-O, e8=Q) or coordinates (g1f3, e7e8q)");
+    println!("  undo    take back your last move");
+    println!("  flip    turn the board around");
+    println!("  fen     print the current position");
+    println!("  hint    let the engine suggest a move");
+    println!("  resign  give up the game");
+    println!("  quit    leave without a result");
+}
@@ -0,0 +1,62 @@
+use clap::Parser;
+use knightmare::{
+    error::{Error, ErrorExt},
+    fen::ToFENString,
+    random::Random,
+    random_position::{Constraints, MaterialRange, PositionGenerator},
+    str,
+    syzygy::MaterialError,
+};
+
+/// Print random legal positions with a given material in FEN notation
+#[derive(Parser)]
+pub struct RandomFenCommand {
+    /// The material signature like `KQPvKR`, white's pieces first
+    #[arg(long)]
+    pub material: String,
+
+    /// The number of positions to print
+    #[arg(short, default_value_t = 1)]
+    pub n: usize,
+
+    /// The seed for the random positions, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Only print positions where the side to move is not in check
+    #[arg(long)]
+    pub not_in_check: bool,
+
+    /// Only print positions where the side to move cannot capture
+    #[arg(long)]
+    pub no_captures: bool,
+
+    /// Allow pawns on the first and last rank
+    #[arg(long)]
+    pub back_rank_pawns: bool,
+}
+
+impl RandomFenCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let material = MaterialRange::parse(&self.material)
+            .ctx(str!("Parsing the material of the positions"))?;
+
+        let constraints = Constraints {
+            not_in_check: self.not_in_check,
+            no_captures: self.no_captures,
+            back_rank_pawns: self.back_rank_pawns,
+        };
+        let seed = self.seed.unwrap_or_else(|| Random::from_time().next_u64());
+
+        let mut generator = PositionGenerator::new(material, constraints, seed);
+        for _ in 0..self.n {
+            let state = generator
+                .generate()
+                .ok_or_else(|| MaterialError::Unsatisfiable(self.material.clone()))
+                .ctx(str!("Generating a position with seed {seed}"))?;
+            println!("{}", state.to_fen());
+        }
+
+        Ok(0)
+    }
+}
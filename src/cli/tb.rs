@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use knightmare::{
+    endgame::{Endgames, EXTENSION, MAX_PIECES},
+    error::{Error, ErrorExt},
+    fen::FENMode,
+    game::GameState,
+    str,
+    syzygy::{Material, Tablebase, TablebaseError},
+};
+
+/// Work with endgame tablebases
+#[derive(Parser)]
+pub struct TbCommand {
+    #[command(subcommand)]
+    pub command: TbSubcommand,
+}
+
+/// The subcommands of `tb`
+#[derive(Subcommand)]
+pub enum TbSubcommand {
+    /// Look up a position in the Syzygy tablebases
+    Probe {
+        /// The position to look up
+        #[arg(long)]
+        fen: String,
+
+        /// The directories with the Syzygy files, separated like `PATH`
+        #[arg(long)]
+        path: String,
+    },
+    /// Solve a material signature like `KRvK` by retrograde analysis
+    Generate {
+        /// The material signature, the tables it depends on are generated as well
+        material: String,
+
+        /// The directory to write the table files to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+impl TbCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        match &self.command {
+            TbSubcommand::Probe { fen, path } => {
+                let tablebase = Tablebase::open(path)?;
+                let state = GameState::from_fen_with(fen, FENMode::Strict)
+                    .ctx(str!("Parsing FEN '{fen}'"))?;
+
+                let wdl = tablebase
+                    .probe_wdl(&state)
+                    .ok_or(TablebaseError::NotFound {
+                        tables: tablebase.table_count(),
+                        max_pieces: tablebase.max_pieces(),
+                    })
+                    .ctx(str!("Probing '{fen}'"))?;
+
+                println!("WDL: {wdl}");
+                match tablebase.probe_dtz(&state) {
+                    Some(dtz) => println!("DTZ: {dtz}"),
+                    None => println!("DTZ: unknown"),
+                }
+
+                if let Some(moves) = tablebase.rank_root_moves(&state, false) {
+                    println!();
+                    for root_move in moves {
+                        println!(
+                            "{:<8} DTZ {:>5}",
+                            root_move.mv.to_san(&state),
+                            root_move.dtz
+                        );
+                    }
+                }
+            }
+            TbSubcommand::Generate { material, output } => {
+                let material =
+                    Material::parse(material).ctx(str!("Parsing the material to generate"))?;
+
+                let mut endgames = Endgames::new();
+                endgames
+                    .generate(&material)
+                    .ok_or(TablebaseError::UnsupportedMaterial {
+                        material: material.name(),
+                        max_pieces: MAX_PIECES,
+                    })
+                    .ctx(str!("Generating {}", material.name()))?;
+
+                let mut tables: Vec<_> = endgames.tables().collect();
+                tables.sort_by_key(|t| (t.material().piece_count(), t.material().name()));
+
+                for table in tables {
+                    let name = table.material().name();
+                    let stats = table.stats();
+                    table.save(&output.join(format!("{name}.{EXTENSION}")))?;
+
+                    println!(
+                        "{name:<8} {:>9} wins {:>9} draws {:>9} losses, longest mate {} plies",
+                        stats.wins, stats.draws, stats.losses, stats.longest
+                    );
+                }
+            }
+        }
+
+        Ok(0)
+    }
+}
@@ -0,0 +1,130 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use colored::Colorize;
+use knightmare::{
+    epd::EPD,
+    error::{Error, ErrorExt},
+    search::{SearchLimits, Searcher},
+    str,
+};
+
+/// Run the engine over a suite of EPD test positions
+#[derive(Parser)]
+pub struct TestsuiteCommand {
+    /// The EPD file with the positions and their `bm` / `am` operations
+    pub file: PathBuf,
+
+    /// The time to search each position for in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    pub movetime: u64,
+
+    /// The maximum depth to search each position to
+    #[arg(long)]
+    pub depth: Option<u8>,
+
+    /// The transposition table size in megabytes
+    #[arg(long, default_value_t = 64)]
+    pub hash: usize,
+}
+
+impl TestsuiteCommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        let content = std::fs::read_to_string(&self.file)
+            .ctx(str!("Reading test suite {}", self.file.display()))?;
+
+        let limits = SearchLimits {
+            depth: self.depth,
+            movetime: Some(Duration::from_millis(self.movetime)),
+            ..Default::default()
+        };
+        let mut searcher = Searcher::new(self.hash);
+
+        let lines: Vec<(usize, &str)> = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .collect();
+
+        // Lines that cannot be tested count as failures
+        let mut passed = 0;
+        let mut skipped = 0;
+
+        for (index, (line_number, line)) in lines.iter().enumerate() {
+            let prefix = format!("{:>4}/{}", index + 1, lines.len());
+
+            let epd = match EPD::from_epd(line) {
+                Ok(epd) => epd,
+                Err(e) => {
+                    println!(
+                        "{prefix} {}",
+                        format!("line {}: {e}", line_number + 1).red()
+                    );
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let id = epd.id().unwrap_or("-").to_string();
+
+            let (best, avoid) = match (epd.best_moves(), epd.avoid_moves()) {
+                (Ok(best), Ok(avoid)) if !best.is_empty() || !avoid.is_empty() => (best, avoid),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{prefix} {id:<16} {}", e.to_string().red());
+                    skipped += 1;
+                    continue;
+                }
+                _ => {
+                    println!("{prefix} {id:<16} {}", "no bm or am operation".red());
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            searcher.clear();
+            let result = searcher.search(&epd.state, &[], &limits);
+            let Some(best_move) = result.best_move else {
+                println!("{prefix} {id:<16} {}", "no legal moves".red());
+                skipped += 1;
+                continue;
+            };
+
+            let pass =
+                (best.is_empty() || best.contains(&best_move)) && !avoid.contains(&best_move);
+            if pass {
+                passed += 1;
+            }
+
+            let mut expected = Vec::new();
+            if !best.is_empty() {
+                let moves: Vec<String> = best.iter().map(|m| m.to_san(&epd.state)).collect();
+                expected.push(format!("bm {}", moves.join(" ")));
+            }
+            if !avoid.is_empty() {
+                let moves: Vec<String> = avoid.iter().map(|m| m.to_san(&epd.state)).collect();
+                expected.push(format!("am {}", moves.join(" ")));
+            }
+
+            println!(
+                "{prefix} {id:<16} {} {:<8} ({}) score {} depth {} nodes {}",
+                if pass { "PASS".green() } else { "FAIL".red() },
+                best_move.to_san(&epd.state),
+                expected.join(", "),
+                result.score,
+                result.depth,
+                result.nodes
+            );
+        }
+
+        let total = lines.len();
+        println!(
+            "\nSolved {passed} of {total} positions ({:.1}%), {skipped} could not be tested",
+            if total == 0 {
+                0.0
+            } else {
+                passed as f64 * 100.0 / total as f64
+            }
+        );
+
+        Ok(0)
+    }
+}
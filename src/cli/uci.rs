@@ -0,0 +1,14 @@
+use clap::Parser;
+use knightmare::{error::Error, uci::UCI};
+
+/// Speak the Universal Chess Interface protocol on stdin and stdout
+#[derive(Parser)]
+pub struct UCICommand {}
+
+impl UCICommand {
+    pub fn run(&self) -> Result<i32, Error> {
+        UCI::new().run(std::io::stdin().lock())?;
+
+        Ok(0)
+    }
+}
@@ -0,0 +1,415 @@
+//! Generation of training data through self-play
+//!
+//! Games start from a few random moves and are then played out by the engine
+//! at a fixed search limit. Every quiet position is recorded together with
+//! the search score and the final result of the game.
+//!
+//! # Text format
+//! One record per line: `<fen> | <score> | <result>` where the score is in
+//! centipawns from white's point of view and the result is `1.0`, `0.5` or `0.0`
+//! for a white win, draw or black win.
+//!
+//! # Binary format
+//! Records of [RECORD_SIZE] bytes, all numbers little endian:
+//!
+//! | Bytes   | Content                                                           |
+//! |---------|-------------------------------------------------------------------|
+//! | `0..8`  | Occupancy bitboard, bit `rank * 8 + file` is set for pieces        |
+//! | `8..24` | One nibble per piece in occupancy order, low nibble first:         |
+//! |         | the [Piece] discriminant, bit `3` set for black pieces             |
+//! | `24`    | En passant square (`rank * 8 + file`, `64` if none), bit `7` set if black is to move |
+//! | `25`    | Castling rights as in [CastlingRights::bits()]                     |
+//! | `26`    | Halfmove clock                                                    |
+//! | `27..29`| Fullmove counter                                                  |
+//! | `29..31`| Score from white's point of view                                  |
+//! | `31`    | Result: `2` white win, `1` draw, `0` black win                     |
+use std::{
+    fmt::Display,
+    io::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+};
+
+use crate::{
+    board::Board,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::Coordinate,
+    error::{Error, ErrorExt},
+    fen::{FromFENString, ToFENString, FEN_START},
+    game::{GameResult, GameState, Outcome},
+    piece::{ColoredPiece, Piece},
+    random::Random,
+    search::{SearchLimits, Searcher, MATE_BOUND},
+    str,
+};
+
+/// The size of a record in the binary format
+pub const RECORD_SIZE: usize = 32;
+
+/// The number of plies after which a game is declared a draw
+const MAX_GAME_PLIES: usize = 400;
+
+/// The number of random openings tried for a game before giving up
+const MAX_OPENING_ATTEMPTS: usize = 1000;
+
+/// The format training data gets written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DataFormat {
+    /// Human readable lines of FEN, score and result
+    Text,
+    /// Compact fixed size records
+    Binary,
+}
+
+/// The settings for generating training data
+#[derive(Clone, Debug)]
+pub struct DatagenConfig {
+    /// The number of games to play
+    pub games: u64,
+    /// The number of games played in parallel
+    pub threads: usize,
+    /// The search limits for every move
+    pub limits: SearchLimits,
+    /// The number of random moves played before the engine takes over
+    pub random_plies: u8,
+    /// Openings that the engine scores worse than this for either side are discarded
+    pub max_opening_score: i32,
+    /// The size of the transposition table of each thread in megabytes
+    pub hash_mb: usize,
+    /// The seed for the random openings
+    pub seed: u64,
+}
+
+/// A single training position
+#[derive(Clone, Copy, Debug)]
+pub struct DataRecord {
+    /// The position
+    pub state: GameState,
+    /// The search score in centipawns from white's point of view
+    pub score: i16,
+    /// The result of the game the position occurred in
+    pub result: GameResult,
+}
+
+/// Statistics about a finished data generation run
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DatagenStats {
+    /// The number of games played
+    pub games: u64,
+    /// The number of positions written
+    pub positions: u64,
+    /// The number of games won by white
+    pub white_wins: u64,
+    /// The number of drawn games
+    pub draws: u64,
+    /// The number of games won by black
+    pub black_wins: u64,
+}
+
+/// An error that occurred while generating training data
+#[derive(Debug)]
+pub enum DatagenError {
+    /// No random opening scored within the limit
+    NoOpening {
+        /// The number of openings that were tried
+        attempts: usize,
+        /// The limit of the opening score
+        max_opening_score: i32,
+    },
+}
+
+impl Display for DatagenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoOpening {
+                attempts,
+                max_opening_score,
+            } => write!(
+                f,
+                "None of {attempts} random openings scored within {max_opening_score} centipawns"
+            ),
+        }
+    }
+}
+
+impl DataRecord {
+    /// Serializes the record to a line of the text format, without line break
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} | {} | {:.1}",
+            self.state.to_fen(),
+            self.score,
+            self.result.white_score()
+        )
+    }
+
+    /// Serializes the record to the binary format
+    pub fn to_binary(&self) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0u8; RECORD_SIZE];
+        let mut occupancy = 0u64;
+
+        for (i, (coordinate, piece)) in self
+            .state
+            .board
+            .get_occupied_fields_rm()
+            .iter()
+            .take(32)
+            .enumerate()
+        {
+            occupancy |= 1 << (coordinate.rank as u64 * 8 + coordinate.file as u64);
+
+            let nibble = piece.piece as u8 | ((piece.color == Color::Black) as u8) << 3;
+            bytes[8 + i / 2] |= nibble << (4 * (i % 2));
+        }
+        bytes[0..8].copy_from_slice(&occupancy.to_le_bytes());
+
+        bytes[24] = self
+            .state
+            .en_passant
+            .map_or(64, |c| c.rank as u8 * 8 + c.file as u8)
+            | ((self.state.side_to_move == Color::Black) as u8) << 7;
+        bytes[25] = self.state.castling.bits();
+        bytes[26] = self.state.halfmove_clock.min(255) as u8;
+        bytes[27..29].copy_from_slice(&self.state.fullmove_counter.to_le_bytes());
+        bytes[29..31].copy_from_slice(&self.score.to_le_bytes());
+        bytes[31] = match self.result {
+            GameResult::WhiteWins => 2,
+            GameResult::Draw => 1,
+            GameResult::BlackWins => 0,
+        };
+
+        bytes
+    }
+
+    /// Deserializes a record from the binary format, [None] if the data is malformed
+    /// # Arguments
+    /// * `bytes` - The record
+    pub fn from_binary(bytes: &[u8; RECORD_SIZE]) -> Option<Self> {
+        let mut board = Board::default();
+        let occupancy = u64::from_le_bytes(bytes[0..8].try_into().ok()?);
+
+        let mut i = 0;
+        for index in 0..64u8 {
+            if occupancy & (1 << index) == 0 {
+                continue;
+            }
+
+            let nibble = (bytes.get(8 + i / 2)? >> (4 * (i % 2))) & 0xF;
+            board.squares[(index % 8) as usize][(index / 8) as usize] = Some(ColoredPiece {
+                piece: *Piece::ALL.get((nibble & 0x7) as usize)?,
+                color: if nibble & 0x8 != 0 {
+                    Color::Black
+                } else {
+                    Color::White
+                },
+            });
+            i += 1;
+        }
+
+        let en_passant = match bytes[24] & 0x7F {
+            64 => None,
+            index => Some(Coordinate::from_u8s(index % 8, index / 8)?),
+        };
+        let side_to_move = if bytes[24] & 0x80 != 0 {
+            Color::Black
+        } else {
+            Color::White
+        };
+
+        Some(Self {
+            state: GameState::new(
+                board,
+                side_to_move,
+                CastlingRights::from_bits(bytes[25]),
+                en_passant,
+                bytes[26] as u16,
+                u16::from_le_bytes([bytes[27], bytes[28]]),
+            ),
+            score: i16::from_le_bytes([bytes[29], bytes[30]]),
+            result: match bytes[31] {
+                2 => GameResult::WhiteWins,
+                1 => GameResult::Draw,
+                0 => GameResult::BlackWins,
+                _ => return None,
+            },
+        })
+    }
+}
+
+/// Plays `config.games` games and writes the recorded positions to `output`
+/// # Arguments
+/// * `config` - The settings for the run
+/// * `format` - The format to write the records in
+/// * `output` - The writer to write the records to
+pub fn generate<W: Write>(
+    config: &DatagenConfig,
+    format: DataFormat,
+    output: &mut W,
+) -> Result<DatagenStats, Error> {
+    let started = AtomicU64::new(0);
+    let mut stats = DatagenStats::default();
+    let (sender, receiver) = mpsc::channel::<Result<(GameResult, Vec<DataRecord>), DatagenError>>();
+
+    std::thread::scope(|scope| -> Result<(), Error> {
+        for thread in 0..config.threads.max(1) {
+            let sender = sender.clone();
+            let started = &started;
+
+            scope.spawn(move || {
+                let mut searcher = Searcher::new(config.hash_mb);
+                let mut random = Random::new(config.seed.wrapping_add(thread as u64));
+
+                while started.fetch_add(1, Ordering::Relaxed) < config.games {
+                    let game = play_game(config, &mut searcher, &mut random);
+                    let failed = game.is_err();
+
+                    // The receiver is gone if writing failed, so stop playing
+                    if sender.send(game).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for game in receiver {
+            let (result, records) = game.ctx(str!("Playing game #{}", stats.games + 1))?;
+            for record in &records {
+                match format {
+                    DataFormat::Text => writeln!(output, "{}", record.to_text()),
+                    DataFormat::Binary => output.write_all(&record.to_binary()),
+                }
+                .ctx(str!("Writing training record"))?;
+            }
+
+            stats.games += 1;
+            stats.positions += records.len() as u64;
+            match result {
+                GameResult::WhiteWins => stats.white_wins += 1,
+                GameResult::Draw => stats.draws += 1,
+                GameResult::BlackWins => stats.black_wins += 1,
+            }
+
+            if stats.games.is_multiple_of(100) {
+                log::info!(
+                    "Played {} of {} games, {} positions",
+                    stats.games,
+                    config.games,
+                    stats.positions
+                );
+            }
+        }
+
+        Ok(())
+    })?;
+
+    output.flush().ctx(str!("Flushing training data"))?;
+
+    Ok(stats)
+}
+
+/// Plays a single game from a random opening and returns its result and quiet positions
+fn play_game(
+    config: &DatagenConfig,
+    searcher: &mut Searcher,
+    random: &mut Random,
+) -> Result<(GameResult, Vec<DataRecord>), DatagenError> {
+    let (mut state, mut history) = random_opening(config, searcher, random)?;
+    let mut positions: Vec<(GameState, i16)> = Vec::new();
+
+    let result = loop {
+        if let Some(outcome) = state.outcome() {
+            break outcome.result();
+        }
+
+        let repetitions = history
+            .iter()
+            .rev()
+            .take(state.halfmove_clock as usize)
+            .filter(|hash| **hash == state.hash())
+            .count();
+        if repetitions >= 2 {
+            break Outcome::ThreefoldRepetition.result();
+        }
+
+        if history.len() >= MAX_GAME_PLIES {
+            break GameResult::Draw;
+        }
+
+        let search = searcher.search(&state, &history, &config.limits);
+        let Some(best_move) = search.best_move else {
+            break GameResult::Draw;
+        };
+
+        // Only keep quiet positions, their score is meaningful without a further search
+        let quiet = !state.is_check()
+            && !state.is_capture(best_move)
+            && best_move.promotion.is_none()
+            && search.score.abs() < MATE_BOUND;
+        if quiet {
+            let score = match state.side_to_move {
+                Color::White => search.score,
+                Color::Black => -search.score,
+            };
+            positions.push((state, score.clamp(i16::MIN as i32, i16::MAX as i32) as i16));
+        }
+
+        history.push(state.hash());
+        state = state.make_move(best_move);
+    };
+
+    let records = positions
+        .into_iter()
+        .map(|(state, score)| DataRecord {
+            state,
+            score,
+            result,
+        })
+        .collect();
+
+    Ok((result, records))
+}
+
+/// Plays random moves from the starting position until a playable,
+/// balanced position is reached, giving up after [MAX_OPENING_ATTEMPTS] tries
+fn random_opening(
+    config: &DatagenConfig,
+    searcher: &mut Searcher,
+    random: &mut Random,
+) -> Result<(GameState, Vec<u64>), DatagenError> {
+    let start = GameState::from_fen(FEN_START).expect("[DEV] Invalid starting position");
+
+    for _ in 0..MAX_OPENING_ATTEMPTS {
+        searcher.clear();
+
+        let mut state = start;
+        let mut history = Vec::new();
+
+        for _ in 0..config.random_plies {
+            let moves = state.legal_moves();
+            let Some(mv) = random.choose(&moves) else {
+                break;
+            };
+
+            history.push(state.hash());
+            state = state.make_move(*mv);
+        }
+
+        if state.outcome().is_some() {
+            continue;
+        }
+
+        let search = searcher.search(&state, &history, &config.limits);
+        if search.score.abs() <= config.max_opening_score {
+            return Ok((state, history));
+        }
+    }
+
+    Err(DatagenError::NoOpening {
+        attempts: MAX_OPENING_ATTEMPTS,
+        max_opening_score: config.max_opening_score,
+    })
+}
@@ -0,0 +1,593 @@
+//! Endgame tables generated by retrograde analysis
+//!
+//! For a material signature with up to [MAX_PIECES] pieces every placement
+//! of the pieces is enumerated and solved backwards from the checkmates,
+//! which gives the distance to mate of each position. Captures and promotions
+//! lead into the tables of the smaller signatures, which are solved first.
+//! The 50 move rule is not taken into account.
+//!
+//! A table file (`KRvK.dtm`) starts with a header (magic, format version and
+//! the signature) followed by one byte per position: `0` for a draw, `255` for
+//! an illegal position and the distance to mate in plies plus one otherwise.
+//! Odd distances are wins for the side to move, even distances are losses.
+use std::{collections::HashMap, fmt::Display, io, path::Path};
+
+use crate::{
+    castling::CastlingRights,
+    color::Color,
+    coordinate::Coordinate,
+    error::{Error, ErrorExt},
+    game::GameState,
+    piece::{ColoredPiece, Piece},
+    str,
+    syzygy::Material,
+    variant::Variant,
+};
+
+/// The largest number of pieces, kings included, tables can be generated for
+pub const MAX_PIECES: usize = 3;
+
+/// The file extension of table files
+pub const EXTENSION: &str = "dtm";
+
+/// The first bytes of every table file
+const MAGIC: &[u8; 4] = b"KDTM";
+
+/// The version of the file format
+const VERSION: u8 = 1;
+
+/// The stored value of positions that cannot occur in a game
+const ILLEGAL: u8 = u8::MAX;
+
+/// The distance to mate of a position from the side to move's point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DTM {
+    /// The side to move mates in this many plies
+    Win(u32),
+    /// The side to move is mated in this many plies, `0` if it is mated already
+    Loss(u32),
+    /// No side can force mate
+    Draw,
+}
+
+/// The number of positions per result in a table
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TableStats {
+    /// The positions the side to move wins
+    pub wins: u64,
+    /// The drawn positions
+    pub draws: u64,
+    /// The positions the side to move loses
+    pub losses: u64,
+    /// The longest distance to mate in plies
+    pub longest: u32,
+}
+
+/// A solved material signature
+#[derive(Clone, Debug)]
+pub struct EndgameTable {
+    material: Material,
+    values: Vec<u8>,
+}
+
+/// A set of solved material signatures
+#[derive(Clone, Debug, Default)]
+pub struct Endgames {
+    tables: HashMap<String, EndgameTable>,
+}
+
+impl DTM {
+    /// Returns the value of the position before the move that led to this one
+    pub fn parent(self) -> Self {
+        match self {
+            Self::Win(plies) => Self::Loss(plies + 1),
+            Self::Loss(plies) => Self::Win(plies + 1),
+            Self::Draw => Self::Draw,
+        }
+    }
+
+    /// Orders the values by how good they are for the side to move
+    fn preference(self) -> i64 {
+        match self {
+            Self::Win(plies) => i64::MAX - plies as i64,
+            Self::Draw => 0,
+            Self::Loss(plies) => i64::MIN + plies as i64 + 1,
+        }
+    }
+
+    /// Decodes a stored value
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 | ILLEGAL => Self::Draw,
+            b if b % 2 == 0 => Self::Win(b as u32 - 1),
+            b => Self::Loss(b as u32 - 1),
+        }
+    }
+
+    /// Encodes the value for storing
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Win(plies) | Self::Loss(plies) => {
+                u8::try_from(plies + 1).expect("[DEV] Distance to mate too long to store")
+            }
+            Self::Draw => 0,
+        }
+    }
+}
+
+impl Display for DTM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Win(plies) => write!(f, "mate in {}", plies.div_ceil(2)),
+            Self::Loss(0) => write!(f, "mated"),
+            Self::Loss(plies) => write!(f, "mated in {}", plies / 2),
+            Self::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+/// Returns the pieces of `material` in the order they are indexed in:
+/// white before black, kings first
+fn pieces(material: &Material) -> Vec<ColoredPiece> {
+    let order = [
+        Piece::King,
+        Piece::Queen,
+        Piece::Rook,
+        Piece::Bishop,
+        Piece::Knight,
+        Piece::Pawn,
+    ];
+
+    let mut pieces = Vec::new();
+    for color in [Color::White, Color::Black] {
+        for piece in order {
+            for _ in 0..material.count(color, piece) {
+                pieces.push(ColoredPiece { piece, color });
+            }
+        }
+    }
+
+    pieces
+}
+
+/// The number of positions of a table with `pieces` pieces
+fn table_size(pieces: usize) -> usize {
+    2 << (6 * pieces)
+}
+
+/// Computes the index of a position
+/// # Arguments
+/// * `layout` - The pieces of the table in index order
+/// * `placement` - The squares (`0` for `A1` up to `63` for `H8`) and pieces of the position
+/// * `side_to_move` - The color that has to make the next move
+fn index_of(
+    layout: &[ColoredPiece],
+    placement: impl Iterator<Item = (usize, ColoredPiece)>,
+    side_to_move: Color,
+) -> usize {
+    let mut squares = [None; MAX_PIECES];
+
+    for (square, piece) in placement {
+        let slot = layout
+            .iter()
+            .enumerate()
+            .position(|(i, p)| *p == piece && squares[i].is_none())
+            .expect("[DEV] Position does not match the table");
+        squares[slot] = Some(square);
+    }
+
+    squares[..layout.len()]
+        .iter()
+        .fold(side_to_move as usize, |index, square| {
+            index * 64 + square.expect("[DEV] Position does not match the table")
+        })
+}
+
+/// Sets up the position with `index`, [None] if it cannot occur in a game
+/// # Arguments
+/// * `layout` - The pieces of the table in index order
+/// * `index` - The index of the position
+fn position_at(layout: &[ColoredPiece], index: usize) -> Option<GameState> {
+    let side_to_move = match index >> (6 * layout.len()) {
+        0 => Color::White,
+        _ => Color::Black,
+    };
+
+    let mut board = crate::board::Board::default();
+    for (i, piece) in layout.iter().enumerate() {
+        let square = (index >> (6 * (layout.len() - 1 - i))) & 63;
+        let rank = square / 8;
+
+        if board.squares[square % 8][rank].is_some()
+            || (piece.piece == Piece::Pawn && (rank == 0 || rank == 7))
+        {
+            return None;
+        }
+        board.squares[square % 8][rank] = Some(*piece);
+    }
+
+    // The side that just moved cannot be in check
+    let king = board.find_king(side_to_move.opposite())?;
+    if board.is_attacked(king, side_to_move) {
+        return None;
+    }
+
+    Some(GameState::new(
+        board,
+        side_to_move,
+        CastlingRights::default(),
+        None,
+        0,
+        1,
+    ))
+}
+
+/// The square number of `coordinate`, `0` for `A1` up to `63` for `H8`
+fn square(coordinate: Coordinate) -> usize {
+    coordinate.rank as usize * 8 + coordinate.file as usize
+}
+
+/// Returns the signatures `material` can turn into by a capture and/or promotion
+fn successors(material: &Material) -> Vec<Material> {
+    let mut captures = vec![*material];
+    for color in [Color::White, Color::Black] {
+        for piece in Piece::ALL {
+            if piece != Piece::King && material.count(color, piece) > 0 {
+                captures.push(material.without(color, piece));
+            }
+        }
+    }
+
+    let mut successors = captures.clone();
+    for capture in &captures {
+        for color in [Color::White, Color::Black] {
+            if capture.count(color, Piece::Pawn) > 0 {
+                for promotion in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                    successors.push(capture.without(color, Piece::Pawn).with(color, promotion));
+                }
+            }
+        }
+    }
+
+    successors.retain(|m| m != material);
+    successors
+}
+
+/// Solves `material`, the tables of all its successors must be in `endgames`
+fn solve(material: &Material, endgames: &Endgames) -> EndgameTable {
+    let layout = pieces(material);
+    let size = table_size(layout.len());
+
+    let mut values: Vec<Option<DTM>> = vec![None; size];
+    let mut legal = vec![false; size];
+    // The best result of the moves that leave the table
+    let mut exits: Vec<Option<DTM>> = vec![None; size];
+    // The moves within the table, those of position `i` are at `offsets[i]..offsets[i + 1]`
+    let mut offsets = Vec::with_capacity(size + 1);
+    let mut moves = Vec::new();
+
+    offsets.push(0);
+    for index in 0..size {
+        if let Some(state) = position_at(&layout, index) {
+            legal[index] = true;
+
+            let legal_moves = state.legal_moves();
+            if legal_moves.is_empty() {
+                values[index] = Some(if state.is_check() {
+                    DTM::Loss(0)
+                } else {
+                    DTM::Draw
+                });
+            }
+
+            for mv in legal_moves {
+                let next = state.make_move(mv);
+
+                if state.is_capture(mv) || mv.promotion.is_some() {
+                    let value = endgames
+                        .probe(&next)
+                        .expect("[DEV] Successor table missing")
+                        .parent();
+                    if exits[index].is_none_or(|e| value.preference() > e.preference()) {
+                        exits[index] = Some(value);
+                    }
+                } else {
+                    let placement = next
+                        .board
+                        .get_occupied_fields_fm()
+                        .into_iter()
+                        .map(|(c, p)| (square(c), p));
+                    moves.push(index_of(&layout, placement, next.side_to_move) as u32);
+                }
+            }
+        }
+        offsets.push(moves.len());
+    }
+
+    let longest_exit = exits
+        .iter()
+        .filter_map(|e| match e {
+            Some(DTM::Win(plies) | DTM::Loss(plies)) => Some(*plies),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    // Wins are found on odd plies, losses on even plies
+    let mut plies = 1;
+    loop {
+        let mut changed = false;
+
+        for index in 0..size {
+            if !legal[index] || values[index].is_some() {
+                continue;
+            }
+            let targets = &moves[offsets[index]..offsets[index + 1]];
+
+            let value = if plies % 2 == 1 {
+                let wins = exits[index] == Some(DTM::Win(plies))
+                    || targets
+                        .iter()
+                        .any(|t| values[*t as usize] == Some(DTM::Loss(plies - 1)));
+                wins.then_some(DTM::Win(plies))
+            } else {
+                // Lost once every move loses, the longest defence gives the distance
+                let mut longest = match exits[index] {
+                    None => Some(0),
+                    Some(DTM::Loss(p)) => Some(p),
+                    Some(_) => None,
+                };
+                for target in targets {
+                    longest = match values[*target as usize] {
+                        Some(DTM::Win(p)) => longest.map(|l| l.max(p + 1)),
+                        _ => None,
+                    };
+                }
+                (longest == Some(plies)).then_some(DTM::Loss(plies))
+            };
+
+            if value.is_some() {
+                values[index] = value;
+                changed = true;
+            }
+        }
+
+        if !changed && plies > longest_exit {
+            break;
+        }
+        plies += 1;
+    }
+
+    let values = values
+        .iter()
+        .zip(legal)
+        .map(|(value, legal)| {
+            if legal {
+                value.unwrap_or(DTM::Draw).to_byte()
+            } else {
+                ILLEGAL
+            }
+        })
+        .collect();
+
+    EndgameTable {
+        material: *material,
+        values,
+    }
+}
+
+impl EndgameTable {
+    /// Returns the material signature of this table
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Returns the number of positions per result
+    pub fn stats(&self) -> TableStats {
+        let mut stats = TableStats::default();
+
+        for byte in self.values.iter().filter(|b| **b != ILLEGAL) {
+            match DTM::from_byte(*byte) {
+                DTM::Win(plies) => {
+                    stats.wins += 1;
+                    stats.longest = stats.longest.max(plies);
+                }
+                DTM::Loss(plies) => {
+                    stats.losses += 1;
+                    stats.longest = stats.longest.max(plies);
+                }
+                DTM::Draw => stats.draws += 1,
+            }
+        }
+
+        stats
+    }
+
+    /// Looks up `state`, [None] if it does not have the material of this table
+    /// # Arguments
+    /// * `state` - The position to look up
+    pub fn probe(&self, state: &GameState) -> Option<DTM> {
+        let placement = state.board.get_occupied_fields_fm();
+        let layout = pieces(&self.material);
+
+        let index = if Material::of(state) == self.material {
+            let placement = placement.into_iter().map(|(c, p)| (square(c), p));
+            index_of(&layout, placement, state.side_to_move)
+        } else if Material::of(state) == self.material.flipped() {
+            // Swap the colors and mirror the board
+            let placement = placement.into_iter().map(|(c, p)| {
+                let piece = ColoredPiece {
+                    piece: p.piece,
+                    color: p.color.opposite(),
+                };
+                (square(c) ^ 56, piece)
+            });
+            index_of(&layout, placement, state.side_to_move.opposite())
+        } else {
+            return None;
+        };
+
+        Some(DTM::from_byte(self.values[index]))
+    }
+
+    /// Parses a table from the contents of a table file, [None] if it is invalid
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let header = bytes.get(..6)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return None;
+        }
+
+        let name_end = 6 + header[5] as usize;
+        let name = std::str::from_utf8(bytes.get(6..name_end)?).ok()?;
+        let material = Material::parse(name).ok()?;
+
+        let values = &bytes[name_end..];
+        (material.piece_count() <= MAX_PIECES && values.len() == table_size(material.piece_count()))
+            .then(|| Self {
+                material,
+                values: values.to_vec(),
+            })
+    }
+
+    /// Serializes the table to the contents of a table file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.material.name();
+
+        let mut bytes = Vec::with_capacity(6 + name.len() + self.values.len());
+        bytes.extend(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(name.len() as u8);
+        bytes.extend(name.as_bytes());
+        bytes.extend(&self.values);
+
+        bytes
+    }
+
+    /// Loads a table from the file at `path`
+    /// # Arguments
+    /// * `path` - The path to the table file
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).ctx(str!("Reading endgame table {}", path.display()))?;
+
+        match Self::from_bytes(&bytes) {
+            Some(table) => Ok(table),
+            None => io::Error::new(io::ErrorKind::InvalidData, "Not a valid endgame table")
+                .ctx(str!("Reading endgame table {}", path.display())),
+        }
+    }
+
+    /// Writes the table to the file at `path`
+    /// # Arguments
+    /// * `path` - The path to the table file
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.to_bytes()).ctx(str!("Writing endgame table {}", path.display()))
+    }
+}
+
+impl Endgames {
+    /// Creates an empty set of tables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the table files from the directories in `path`,
+    /// separated like the `PATH` environment variable
+    /// # Arguments
+    /// * `path` - The directories to search for table files
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let mut endgames = Self::new();
+
+        for directory in std::env::split_paths(path) {
+            if directory.as_os_str().is_empty() {
+                continue;
+            }
+            endgames.add_directory(&directory)?;
+        }
+
+        Ok(endgames)
+    }
+
+    /// Loads all table files in `directory`, invalid files are skipped
+    /// # Arguments
+    /// * `directory` - The directory to search for table files
+    pub fn add_directory(&mut self, directory: &Path) -> Result<(), Error> {
+        let entries = std::fs::read_dir(directory)
+            .ctx(str!("Reading endgame directory {}", directory.display()))?;
+
+        for entry in entries {
+            let path = entry
+                .ctx(str!("Reading endgame directory {}", directory.display()))?
+                .path();
+            if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+
+            match EndgameTable::open(&path) {
+                Ok(table) => self.insert(table),
+                Err(e) => log::warn!("Skipping endgame table: {e}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds `table`, replacing a table with the same material
+    pub fn insert(&mut self, table: EndgameTable) {
+        self.tables.insert(table.material.name(), table);
+    }
+
+    /// Returns the table of `material`, it may be stored with the colors swapped
+    pub fn get(&self, material: &Material) -> Option<&EndgameTable> {
+        self.tables
+            .get(&material.name())
+            .or_else(|| self.tables.get(&material.flipped().name()))
+    }
+
+    /// Returns all tables
+    pub fn tables(&self) -> impl Iterator<Item = &EndgameTable> {
+        self.tables.values()
+    }
+
+    /// Solves `material` and all signatures it can turn into that are not known yet
+    /// # Returns
+    /// The table, [None] if `material` has more than [MAX_PIECES] pieces or only kings
+    pub fn generate(&mut self, material: &Material) -> Option<&EndgameTable> {
+        if material.piece_count() > MAX_PIECES || material.piece_count() <= 2 {
+            return None;
+        }
+
+        if self.get(material).is_none() {
+            for successor in successors(material) {
+                if successor.piece_count() > 2 {
+                    self.generate(&successor);
+                }
+            }
+
+            log::info!("Solving {}", material.name());
+            let table = solve(material, self);
+            self.insert(table);
+        }
+
+        self.get(material)
+    }
+
+    /// Looks up the distance to mate of `state`
+    /// # Returns
+    /// [None] if there is no table for the position, it has castling rights or is not standard chess
+    pub fn probe(&self, state: &GameState) -> Option<DTM> {
+        if state.castling.bits() != 0 || state.variant != Variant::Standard {
+            return None;
+        }
+
+        // Count the pieces without collecting them, this runs in the search
+        let count = state.board.squares.iter().flatten().flatten().count();
+        if count == 2 {
+            return Some(DTM::Draw);
+        }
+        if count > MAX_PIECES || self.tables.is_empty() {
+            return None;
+        }
+
+        self.get(&Material::of(state))?.probe(state)
+    }
+}
@@ -0,0 +1,259 @@
+//! Stuff to work with Extended Position Description (EPD) strings
+//!
+//! An EPD record consists of the first four fields of a FEN string,
+//! followed by operations like `bm Nf3; id "test 1";`.
+use crate::{
+    fen::{FENMode, FromFENError, ToFENString},
+    game::GameState,
+    moves::Move,
+    san::FromSANError,
+};
+
+/// The opcodes of the move counters, which are stored in the position
+const COUNTER_OPCODES: [&str; 2] = ["hmvc", "fmvn"];
+
+/// A position together with its EPD operations
+#[derive(Clone, Debug)]
+pub struct EPD {
+    /// The position, the move counters are taken from the `hmvc` and `fmvn` operations
+    pub state: GameState,
+    /// The operations as `(opcode, operands)` in the order they appeared,
+    /// without `hmvc` and `fmvn` which are part of the position
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl EPD {
+    /// Parses an EPD record
+    /// # Arguments
+    /// * `epd` - The EPD line
+    pub fn from_epd(epd: &str) -> Result<Self, FromFENError> {
+        let epd = epd.trim();
+
+        // The position is made up of the first four fields
+        let mut fields = Vec::new();
+        let mut rest = epd;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(FromFENError::InvalidFormat);
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        let mut operations = parse_operations(rest)?;
+
+        let counter = |opcode: &str, default: &str| {
+            operations
+                .iter()
+                .find(|(o, _)| o == opcode)
+                .and_then(|(_, operands)| operands.first().cloned())
+                .unwrap_or_else(|| default.to_string())
+        };
+        let fen = format!(
+            "{} {} {}",
+            fields.join(" "),
+            counter("hmvc", "0"),
+            counter("fmvn", "1")
+        );
+
+        let state = GameState::from_fen_with(&fen, FENMode::Strict)?;
+        operations.retain(|(opcode, _)| !COUNTER_OPCODES.contains(&opcode.as_str()));
+
+        Ok(Self { state, operations })
+    }
+
+    /// Serializes the record to an EPD line without the move counters
+    pub fn to_epd(&self) -> String {
+        let fen = self.state.to_fen();
+        let mut epd = fen.split(' ').take(4).collect::<Vec<&str>>().join(" ");
+
+        for (opcode, operands) in &self.operations {
+            push_operation(&mut epd, opcode, operands);
+        }
+
+        epd
+    }
+
+    /// Serializes the record to an EPD line with the move counters
+    /// of the position as `hmvc` and `fmvn` operations
+    pub fn to_epd_with_counters(&self) -> String {
+        let mut epd = self.to_epd();
+
+        push_operation(&mut epd, "hmvc", &[self.state.halfmove_clock.to_string()]);
+        push_operation(&mut epd, "fmvn", &[self.state.fullmove_counter.to_string()]);
+
+        epd
+    }
+
+    /// Returns the operands of the operation `opcode`
+    /// # Arguments
+    /// * `opcode` - The opcode, e.g. `bm`
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(o, _)| o == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// Sets the operands of the operation `opcode`, replacing existing ones
+    /// # Arguments
+    /// * `opcode` - The opcode, e.g. `bm`
+    /// * `operands` - The new operands
+    pub fn set_operation(&mut self, opcode: &str, operands: Vec<String>) {
+        match self.operations.iter_mut().find(|(o, _)| o == opcode) {
+            Some((_, o)) => *o = operands,
+            None => self.operations.push((opcode.to_string(), operands)),
+        }
+    }
+
+    /// Returns the identifier of the position (`id`)
+    pub fn id(&self) -> Option<&str> {
+        self.first_operand("id")
+    }
+
+    /// Returns one of the comments `c0` to `c9`
+    /// # Arguments
+    /// * `index` - The number of the comment
+    pub fn comment(&self, index: u8) -> Option<&str> {
+        self.first_operand(&format!("c{index}"))
+    }
+
+    /// Returns the best moves (`bm`), empty if there are none
+    pub fn best_moves(&self) -> Result<Vec<Move>, FromSANError> {
+        self.moves("bm")
+    }
+
+    /// Returns the moves to avoid (`am`), empty if there are none
+    pub fn avoid_moves(&self) -> Result<Vec<Move>, FromSANError> {
+        self.moves("am")
+    }
+
+    /// Returns the predicted variation (`pv`), empty if there is none
+    pub fn predicted_variation(&self) -> Result<Vec<Move>, FromSANError> {
+        let mut state = self.state;
+        let mut moves = Vec::new();
+
+        for san in self.operation("pv").unwrap_or_default() {
+            let mv = state.parse_san(san)?;
+            state = state.make_move(mv);
+            moves.push(mv);
+        }
+
+        Ok(moves)
+    }
+
+    /// Returns the depth the position has been analyzed to (`acd`)
+    pub fn analysis_depth(&self) -> Option<u32> {
+        self.first_operand("acd")?.parse().ok()
+    }
+
+    /// Returns the evaluation in centipawns (`ce`) from the side to move's point of view
+    pub fn centipawn_evaluation(&self) -> Option<i32> {
+        self.first_operand("ce")?.parse().ok()
+    }
+
+    /// Returns the first operand of `opcode`
+    fn first_operand(&self, opcode: &str) -> Option<&str> {
+        self.operation(opcode)?.first().map(|s| s.as_str())
+    }
+
+    /// Parses all operands of `opcode` as moves in this position
+    fn moves(&self, opcode: &str) -> Result<Vec<Move>, FromSANError> {
+        self.operation(opcode)
+            .unwrap_or_default()
+            .iter()
+            .map(|san| self.state.parse_san(san))
+            .collect()
+    }
+}
+
+/// Appends an operation to `epd`, string operands are always quoted.
+/// Quotes and backslashes within quoted operands are escaped with a backslash
+/// # Arguments
+/// * `epd` - The EPD line written so far
+/// * `opcode` - The opcode, e.g. `bm`
+/// * `operands` - The operands of the operation
+fn push_operation(epd: &mut String, opcode: &str, operands: &[String]) {
+    let strings = is_string_opcode(opcode);
+
+    epd.push(' ');
+    epd.push_str(opcode);
+    for operand in operands {
+        epd.push(' ');
+        if strings || operand.is_empty() || operand.contains([' ', ';', '"']) {
+            let escaped = operand.replace('\\', "\\\\").replace('"', "\\\"");
+            epd.push_str(&format!("\"{escaped}\""));
+        } else {
+            epd.push_str(operand);
+        }
+    }
+    epd.push(';');
+}
+
+/// Returns whether the operands of `opcode` are strings, like the
+/// identifier `id`, the comments `c0`-`c9` and the variation names `v0`-`v9`
+fn is_string_opcode(opcode: &str) -> bool {
+    match opcode.as_bytes() {
+        [b'c' | b'v', b'0'..=b'9'] => true,
+        _ => matches!(opcode, "id" | "eco" | "nic" | "tcri" | "tcsi"),
+    }
+}
+
+/// Parses the operations part of an EPD record
+fn parse_operations(operations: &str) -> Result<Vec<(String, Vec<String>)>, FromFENError> {
+    let mut result = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut chars = operations.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if current.is_empty() {
+                    return Err(FromFENError::InvalidOperation(";".to_string()));
+                }
+                let opcode = current.remove(0);
+                result.push((opcode, std::mem::take(&mut current)));
+            }
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => string.push(c),
+                            None => return Err(FromFENError::InvalidOperation(string)),
+                        },
+                        Some(c) => string.push(c),
+                        None => return Err(FromFENError::InvalidOperation(string)),
+                    }
+                }
+                if current.is_empty() {
+                    return Err(FromFENError::InvalidOperation(string));
+                }
+                current.push(string);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() || *c == ';' || *c == '"' {
+                        break;
+                    }
+                    token.push(*c);
+                    chars.next();
+                }
+                current.push(token);
+            }
+        }
+    }
+
+    // Tolerate a missing ';' after the last operation
+    if !current.is_empty() {
+        let opcode = current.remove(0);
+        result.push((opcode, current));
+    }
+
+    Ok(result)
+}
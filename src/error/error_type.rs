@@ -1,19 +1,66 @@
 use std::{fmt::Display, io};
 
+use crate::{
+    datagen::DatagenError,
+    fen::FromFENError,
+    pgn::PGNError,
+    san::FromSANError,
+    syzygy::{MaterialError, TablebaseError},
+    uci::{OptionError, ProtocolError},
+};
+
 use super::ErrorIn;
 
-/// All the possible types of errors that can occur within Velocity
+/// All the possible types of errors that can occur within Velocity.
+///
+/// The discriminant of a variant is the exit code of the process when it
+/// fails with that error, `0` and `1` are left for success and generic failures
 #[derive(Debug)]
 #[repr(u16)]
 pub enum ErrorType {
     /// An IO error
-    IO(io::Error),
+    IO(io::Error) = 2,
+    /// An invalid position in FEN notation
+    FEN(FromFENError) = 3,
+    /// A move that could not be parsed or is not legal
+    SAN(FromSANError) = 4,
+    /// An error in a PGN file
+    PGN(PGNError) = 5,
+    /// A command from the GUI that does not follow the UCI protocol
+    Protocol(ProtocolError) = 6,
+    /// An unknown engine option or an invalid value for one
+    EngineOption(OptionError) = 7,
+    /// A position or material signature the tablebases do not cover
+    Tablebase(TablebaseError) = 8,
+    /// An invalid material signature
+    Material(MaterialError) = 9,
+    /// Training data could not be generated with the given settings
+    Datagen(DatagenError) = 10,
+}
+
+impl ErrorType {
+    /// Returns the exit code of the process when it fails with this error,
+    /// which is the discriminant of the variant
+    pub fn exit_code(&self) -> i32 {
+        // SAFETY: `#[repr(u16)]` lays the enum out as a `repr(C)` union of
+        // `repr(C)` structs that all start with the `u16` discriminant
+        let discriminant = unsafe { *(self as *const Self).cast::<u16>() };
+        discriminant as i32
+    }
 }
 
 impl Display for ErrorType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::IO(e) => e.fmt(f),
+            Self::FEN(e) => e.fmt(f),
+            Self::SAN(e) => e.fmt(f),
+            Self::PGN(e) => e.fmt(f),
+            Self::Protocol(e) => e.fmt(f),
+            Self::EngineOption(e) => e.fmt(f),
+            Self::Tablebase(e) => e.fmt(f),
+            Self::Material(e) => e.fmt(f),
+            Self::Datagen(e) => e.fmt(f),
         }
     }
 }
@@ -24,3 +71,59 @@ impl From<io::Error> for ErrorType {
     }
 }
 impl ErrorIn for io::Error {}
+
+impl From<FromFENError> for ErrorType {
+    fn from(value: FromFENError) -> Self {
+        Self::FEN(value)
+    }
+}
+impl ErrorIn for FromFENError {}
+
+impl From<FromSANError> for ErrorType {
+    fn from(value: FromSANError) -> Self {
+        Self::SAN(value)
+    }
+}
+impl ErrorIn for FromSANError {}
+
+impl From<PGNError> for ErrorType {
+    fn from(value: PGNError) -> Self {
+        Self::PGN(value)
+    }
+}
+impl ErrorIn for PGNError {}
+
+impl From<ProtocolError> for ErrorType {
+    fn from(value: ProtocolError) -> Self {
+        Self::Protocol(value)
+    }
+}
+impl ErrorIn for ProtocolError {}
+
+impl From<OptionError> for ErrorType {
+    fn from(value: OptionError) -> Self {
+        Self::EngineOption(value)
+    }
+}
+impl ErrorIn for OptionError {}
+
+impl From<TablebaseError> for ErrorType {
+    fn from(value: TablebaseError) -> Self {
+        Self::Tablebase(value)
+    }
+}
+impl ErrorIn for TablebaseError {}
+
+impl From<MaterialError> for ErrorType {
+    fn from(value: MaterialError) -> Self {
+        Self::Material(value)
+    }
+}
+impl ErrorIn for MaterialError {}
+
+impl From<DatagenError> for ErrorType {
+    fn from(value: DatagenError) -> Self {
+        Self::Datagen(value)
+    }
+}
+impl ErrorIn for DatagenError {}
@@ -0,0 +1,156 @@
+//! Static evaluation of positions
+//!
+//! The evaluation is based on material and piece-square tables,
+//! blending between middlegame and endgame tables for the king.
+use crate::{color::Color, game::GameState, piece::Piece};
+
+/// The value of a pawn, the unit of all scores
+pub const PAWN_VALUE: i32 = 100;
+
+/// The material values of all pieces, indexed by [Piece]
+pub const PIECE_VALUES: [i32; 6] = [PAWN_VALUE, 500, 320, 330, 900, 0];
+
+/// The phase weight of each piece, indexed by [Piece]
+const PHASE_WEIGHTS: [i32; 6] = [0, 2, 1, 1, 4, 0];
+
+/// The phase of the starting position
+const MAX_PHASE: i32 = 24;
+
+// The piece-square tables are written from white's point of view, `A8` first
+
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// Evaluates `state` from the point of view of the side to move.
+///
+/// Positive scores are good for the side to move
+/// # Arguments
+/// * `state` - The position to evaluate
+pub fn evaluate(state: &GameState) -> i32 {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    let mut phase = 0;
+
+    for (coordinate, piece) in state.board.get_occupied_fields_fm() {
+        // Mirror the square vertically for black so the tables can be shared
+        let rank = match piece.color {
+            Color::White => 7 - coordinate.rank as usize,
+            Color::Black => coordinate.rank as usize,
+        };
+        let index = rank * 8 + coordinate.file as usize;
+
+        let (mg, eg) = match piece.piece {
+            Piece::Pawn => (PAWN_TABLE[index], PAWN_TABLE[index]),
+            Piece::Knight => (KNIGHT_TABLE[index], KNIGHT_TABLE[index]),
+            Piece::Bishop => (BISHOP_TABLE[index], BISHOP_TABLE[index]),
+            Piece::Rook => (ROOK_TABLE[index], ROOK_TABLE[index]),
+            Piece::Queen => (QUEEN_TABLE[index], QUEEN_TABLE[index]),
+            Piece::King => (KING_MIDDLEGAME_TABLE[index], KING_ENDGAME_TABLE[index]),
+        };
+        let value = PIECE_VALUES[piece.piece as usize];
+
+        let sign = if piece.color == state.side_to_move {
+            1
+        } else {
+            -1
+        };
+        middlegame += sign * (value + mg);
+        endgame += sign * (value + eg);
+        phase += PHASE_WEIGHTS[piece.piece as usize];
+    }
+
+    // Pieces in hand in Crazyhouse count as material without a square
+    let mut pockets = 0;
+    for piece in Piece::ALL {
+        if piece == Piece::King {
+            continue;
+        }
+        let value = PIECE_VALUES[piece as usize];
+        pockets += value * state.pockets.count(state.side_to_move, piece) as i32;
+        pockets -= value * state.pockets.count(state.side_to_move.opposite(), piece) as i32;
+    }
+
+    let phase = phase.min(MAX_PHASE);
+    pockets + (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE
+}
@@ -1,19 +1,202 @@
 //! Stuff to work with FEN notation strings
+use std::fmt::Display;
+
+use crate::{color::Color, coordinate::Coordinate};
 
 /// The starting position of a chess game in FEN notation
 pub const FEN_START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
+/// The six fields of a FEN string, in the order they are written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FENField {
+    /// The pieces on the board, rank 8 first
+    Placement,
+    /// The color that has to make the next move
+    SideToMove,
+    /// The castling abilities of both players
+    Castling,
+    /// The square a pawn can be captured on en passant
+    EnPassant,
+    /// The number of halfmoves since the last capture or pawn move
+    HalfmoveClock,
+    /// The number of the full move
+    FullmoveCounter,
+}
+
+impl FENField {
+    /// All fields in the order they are written
+    pub const ALL: [FENField; 6] = [
+        FENField::Placement,
+        FENField::SideToMove,
+        FENField::Castling,
+        FENField::EnPassant,
+        FENField::HalfmoveClock,
+        FENField::FullmoveCounter,
+    ];
+}
+
+impl Display for FENField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Self::Placement => "piece placement",
+            Self::SideToMove => "side to move",
+            Self::Castling => "castling",
+            Self::EnPassant => "en passant",
+            Self::HalfmoveClock => "halfmove clock",
+            Self::FullmoveCounter => "fullmove counter",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// How thoroughly a FEN string is checked when parsing a game state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FENMode {
+    /// Accepts partial FENs: missing fields take their default (`w - - 0 1`)
+    /// and squares missing at the end of a rank or board are empty
+    Lenient,
+    /// Requires all six fields to be complete and well-formed, but accepts any position
+    #[default]
+    Normal,
+    /// Additionally requires a position that can occur in a game of standard chess or Chess960
+    Strict,
+}
+
 /// Possible errors that can arise when parsing FEN notation strings
 #[derive(Debug)]
 pub enum FromFENError {
     /// The whole string is in an invalid format and cannot be parsed
     InvalidFormat,
+    /// A field is missing from the string
+    MissingField(FENField),
+    /// There is more than the six fields in the string
+    ExtraField(String),
     /// The parser encountered an invalid placement symbol
     InvalidPlacementSymbol(char),
     /// An invalid count / layout for files has been parsed
     InvalidFileLayout(u8),
     /// An invalid count / layout for ranks has been parsed
     InvalidRankLayout(u8),
+    /// The side to move is neither `w` nor `b`
+    InvalidSideToMove(String),
+    /// The parser encountered an invalid castling symbol
+    InvalidCastlingSymbol(char),
+    /// A square could not be parsed
+    InvalidSquare(String),
+    /// A move counter could not be parsed
+    InvalidCounter(String),
+    /// An EPD operation could not be parsed
+    InvalidOperation(String),
+    /// A player does not have exactly one king
+    KingCount {
+        /// The player with the wrong number of kings
+        color: Color,
+        /// The number of kings found
+        count: usize,
+    },
+    /// A pawn stands on the first or last rank
+    PawnOnBackRank(Coordinate),
+    /// The player who just moved is still in check
+    OpponentInCheck(Color),
+    /// A castling right without the king and rook on their squares
+    InconsistentCastling(char),
+    /// An en passant square without a pawn that was just pushed past it
+    InconsistentEnPassant(Coordinate),
+    /// A move counter that does not fit the position
+    ImplausibleCounter {
+        /// The counter in question
+        field: FENField,
+        /// Its value
+        value: u16,
+    },
+    /// An error located at a character of the string
+    At {
+        /// The field the error was found in
+        field: FENField,
+        /// The offset of the offending character from the start of the string
+        offset: usize,
+        /// The error itself
+        error: Box<FromFENError>,
+    },
+}
+
+impl FromFENError {
+    /// Locates the error at `offset` in `field`, an error that is located
+    /// already is moved by `offset` instead
+    /// # Arguments
+    /// * `field` - The field the error was found in
+    /// * `offset` - The offset of the offending character
+    pub fn at(self, field: FENField, offset: usize) -> Self {
+        match self {
+            Self::At {
+                field,
+                offset: inner,
+                error,
+            } => Self::At {
+                field,
+                offset: offset + inner,
+                error,
+            },
+            error => Self::At {
+                field,
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Returns the field and character offset of the error, if it is located
+    pub fn location(&self) -> Option<(FENField, usize)> {
+        match self {
+            Self::At { field, offset, .. } => Some((*field, *offset)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for FromFENError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "Invalid FEN format"),
+            Self::MissingField(field) => write!(f, "Missing the {field} field"),
+            Self::ExtraField(s) => write!(f, "Unexpected extra field '{s}'"),
+            Self::InvalidPlacementSymbol(c) => write!(f, "Invalid placement symbol '{c}'"),
+            Self::InvalidFileLayout(file) => write!(f, "Invalid file layout ({file} files)"),
+            Self::InvalidRankLayout(ranks) => write!(f, "Invalid rank layout ({ranks} ranks)"),
+            Self::InvalidSideToMove(s) => write!(f, "Invalid side to move '{s}'"),
+            Self::InvalidCastlingSymbol(c) => write!(f, "Invalid castling symbol '{c}'"),
+            Self::InvalidSquare(s) => write!(f, "Invalid square '{s}'"),
+            Self::InvalidCounter(s) => write!(f, "Invalid move counter '{s}'"),
+            Self::InvalidOperation(s) => write!(f, "Invalid EPD operation '{s}'"),
+            Self::KingCount { color, count } => {
+                write!(f, "{color} has {count} kings instead of one")
+            }
+            Self::PawnOnBackRank(square) => {
+                write!(f, "Pawn on the back rank on {}", square.to_fen())
+            }
+            Self::OpponentInCheck(color) => {
+                write!(f, "{color} is in check but not to move")
+            }
+            Self::InconsistentCastling(c) => {
+                write!(
+                    f,
+                    "Castling right '{c}' without king and rook on their squares"
+                )
+            }
+            Self::InconsistentEnPassant(square) => write!(
+                f,
+                "En passant square {} without a pawn pushed past it",
+                square.to_fen()
+            ),
+            Self::ImplausibleCounter { field, value } => write!(f, "Implausible {field} {value}"),
+            Self::At {
+                field,
+                offset,
+                error,
+            } => write!(f, "{error} in the {field} field at offset {offset}"),
+        }
+    }
 }
 
 /// Parse FEN strings
@@ -3,4 +3,18 @@ pub mod error;
 mod model;
 pub use model::*;
 
+pub mod bench;
+pub mod datagen;
+pub mod endgame;
+pub mod epd;
+pub mod eval;
 pub mod fen;
+pub mod mate;
+pub mod pgn;
+pub mod polyglot;
+pub mod random;
+pub mod random_position;
+pub mod san;
+pub mod search;
+pub mod syzygy;
+pub mod uci;
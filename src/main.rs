@@ -10,7 +10,8 @@ fn main() {
     match run() {
         Ok(v) => exit(v),
         Err(e) => {
-            println!("{}", e.to_string().red())
+            println!("{}", e.to_string().red());
+            exit(e.error.exit_code())
         }
     }
 }
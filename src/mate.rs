@@ -0,0 +1,232 @@
+//! Searching for forced mates
+//!
+//! The mate finder only looks for mates by the side to move. It runs a
+//! depth-limited AND/OR search: the attacker needs one move that mates or
+//! leaves the defender only with moves after which the attacker can mate
+//! again. Checks are tried first, and the limit is raised one move at a
+//! time, so the first mate found is the shortest one.
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::{game::GameState, moves::Move};
+
+/// The result of a mate search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MateResult {
+    /// The side to move mates in `moves` moves
+    Mate {
+        /// The number of attacker moves until mate
+        moves: u32,
+        /// The mating line with the longest defence, ending in mate
+        line: Vec<Move>,
+    },
+    /// There is no forced mate within the searched number of moves
+    NoMate,
+    /// The search was stopped before it was finished
+    Aborted,
+}
+
+/// A search for forced mates
+pub struct MateSearch {
+    nodes: u64,
+    stop: Option<Arc<AtomicBool>>,
+    stopped: bool,
+    /// Attacker positions and the largest number of moves they were refuted for
+    refuted: HashMap<u64, u32>,
+    /// Attacker positions and the smallest number of moves they were proven for
+    proven: HashMap<u64, (u32, Move)>,
+}
+
+impl MateSearch {
+    /// Creates a new mate search
+    pub fn new() -> Self {
+        Self {
+            nodes: 0,
+            stop: None,
+            stopped: false,
+            refuted: HashMap::new(),
+            proven: HashMap::new(),
+        }
+    }
+
+    /// Creates a new mate search that aborts once `stop` is set
+    /// # Arguments
+    /// * `stop` - The flag that stops the search
+    pub fn with_stop(stop: Arc<AtomicBool>) -> Self {
+        Self {
+            stop: Some(stop),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the number of positions searched
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Searches for a mate in at most `max_moves` moves
+    /// # Arguments
+    /// * `state` - The position, the side to move is the attacker
+    /// * `max_moves` - The largest number of attacker moves to search
+    /// * `progress` - Called with each number of moves that has been refuted
+    pub fn search(
+        &mut self,
+        state: &GameState,
+        max_moves: u32,
+        progress: &mut dyn FnMut(u32, u64),
+    ) -> MateResult {
+        self.stopped = false;
+
+        for moves in 1..=max_moves {
+            // A move is only returned once the mate is proven, even if the search was stopped
+            if self.attack(state, moves).is_some() {
+                return MateResult::Mate {
+                    moves,
+                    line: self.line(state, moves),
+                };
+            }
+            if self.stopped {
+                return MateResult::Aborted;
+            }
+            progress(moves, self.nodes);
+        }
+
+        MateResult::NoMate
+    }
+
+    /// Checks whether the search has to stop
+    fn should_stop(&mut self) -> bool {
+        if self
+            .stop
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed))
+        {
+            self.stopped = true;
+        }
+
+        self.stopped
+    }
+
+    /// Finds a move that mates in at most `moves` moves (OR node)
+    fn attack(&mut self, state: &GameState, moves: u32) -> Option<Move> {
+        self.nodes += 1;
+        if self.should_stop() {
+            return None;
+        }
+
+        let hash = state.hash();
+        if let Some((proven, mv)) = self.proven.get(&hash) {
+            if *proven <= moves {
+                return Some(*mv);
+            }
+        }
+        if self.refuted.get(&hash).is_some_and(|r| *r >= moves) {
+            return None;
+        }
+
+        for mv in attacker_moves(state) {
+            let next = state.make_move(mv);
+
+            if self.defend(&next, moves) {
+                self.proven.insert(hash, (moves, mv));
+                return Some(mv);
+            }
+            if self.stopped {
+                return None;
+            }
+        }
+
+        self.refuted.insert(hash, moves);
+        None
+    }
+
+    /// Checks whether every defence loses to a mate within `moves` moves,
+    /// including the move that led here (AND node)
+    fn defend(&mut self, state: &GameState, moves: u32) -> bool {
+        self.nodes += 1;
+
+        let defences = state.legal_moves();
+        if defences.is_empty() {
+            return state.is_check();
+        }
+        if moves <= 1 {
+            return false;
+        }
+
+        defences
+            .iter()
+            .all(|mv| !self.stopped && self.attack(&state.make_move(*mv), moves - 1).is_some())
+    }
+
+    /// Returns the smallest number of moves the attacker needs to mate, up to `max_moves`
+    fn distance(&mut self, state: &GameState, max_moves: u32) -> Option<u32> {
+        (1..=max_moves).find(|moves| self.attack(state, *moves).is_some())
+    }
+
+    /// Builds the mating line from a position proven to be mate in `moves`,
+    /// the defender always picks a reply that delays the mate the longest.
+    ///
+    /// The mate is already proven, so the line is completed even if the
+    /// search is stopped in the meantime
+    fn line(&mut self, state: &GameState, moves: u32) -> Vec<Move> {
+        let stop = self.stop.take();
+        self.stopped = false;
+
+        let mut line = Vec::new();
+        let mut state = *state;
+        let mut moves = moves;
+
+        while let Some(mv) = self.attack(&state, moves) {
+            line.push(mv);
+            state = state.make_move(mv);
+
+            let mut longest: Option<(u32, Move)> = None;
+            for defence in state.legal_moves() {
+                let distance = self
+                    .distance(&state.make_move(defence), moves - 1)
+                    .expect("[DEV] Refuted a proven mate");
+                if longest.is_none_or(|(l, _)| distance > l) {
+                    longest = Some((distance, defence));
+                }
+            }
+
+            let Some((distance, defence)) = longest else {
+                break;
+            };
+            line.push(defence);
+            state = state.make_move(defence);
+            moves = distance;
+        }
+
+        self.stop = stop;
+        line
+    }
+}
+
+impl Default for MateSearch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the legal moves of the attacker, checks first, then captures
+fn attacker_moves(state: &GameState) -> Vec<Move> {
+    let mut moves = state.legal_moves();
+
+    moves.sort_by_cached_key(|mv| {
+        if state.make_move(*mv).is_check() {
+            0
+        } else if state.is_capture(*mv) || mv.promotion.is_some() {
+            1
+        } else {
+            2
+        }
+    });
+
+    moves
+}
@@ -1,5 +1,17 @@
 pub mod board;
+pub mod builder;
+pub mod castling;
+pub mod chess960;
 pub mod color;
 pub mod coordinate;
 pub mod game;
+pub mod movegen;
+pub mod moves;
 pub mod piece;
+pub mod render;
+pub mod square;
+pub mod svg;
+pub mod transform;
+pub mod validation;
+pub mod variant;
+pub mod zobrist;
@@ -1,10 +1,18 @@
 //! The chess board
-use crate::fen::{FromFENChar, FromFENError, FromFENString, ToFENChar, ToFENString};
+use crate::fen::{
+    FENField, FENMode, FromFENChar, FromFENError, FromFENString, ToFENChar, ToFENString,
+};
 
-use super::{coordinate::Coordinate, piece::ColoredPiece};
+use super::{
+    color::Color,
+    coordinate::Coordinate,
+    piece::{
+        ColoredPiece, Piece, BISHOP_DIRECTIONS, KING_OFFSETS, KNIGHT_OFFSETS, ROOK_DIRECTIONS,
+    },
+};
 
 /// A representation of the chess board
-#[derive(Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Board {
     /// The squares that (can) hold a piece
     /// indexed by `[file][rank]` starting at `A1`
@@ -19,6 +27,42 @@ impl Board {
         self.squares[coord.file as usize][coord.rank as usize]
     }
 
+    /// Places `piece` on `coord`, returning the piece that stood there before
+    /// # Arguments
+    /// * `coord` - The coordinate to place the piece on
+    /// * `piece` - The piece to place
+    pub fn set(&mut self, coord: Coordinate, piece: ColoredPiece) -> Option<ColoredPiece> {
+        self.squares[coord.file as usize][coord.rank as usize].replace(piece)
+    }
+
+    /// Removes the piece on `coord` and returns it
+    /// # Arguments
+    /// * `coord` - The coordinate to empty
+    pub fn remove(&mut self, coord: Coordinate) -> Option<ColoredPiece> {
+        self.squares[coord.file as usize][coord.rank as usize].take()
+    }
+
+    /// Moves the piece on `from` to `to` without checking the rules
+    /// # Arguments
+    /// * `from` - The coordinate to take the piece from
+    /// * `to` - The coordinate to put the piece on
+    /// # Returns
+    /// The piece that stood on `to`, or `from` as the error if it is empty
+    /// and nothing was moved
+    pub fn move_piece(
+        &mut self,
+        from: Coordinate,
+        to: Coordinate,
+    ) -> Result<Option<ColoredPiece>, Coordinate> {
+        let piece = self.remove(from).ok_or(from)?;
+        Ok(self.set(to, piece))
+    }
+
+    /// Removes all pieces from the board
+    pub fn clear(&mut self) {
+        self.squares = [[None; 8]; 8];
+    }
+
     /// Returns a list of occupied fields in file-major form
     pub fn get_occupied_fields_fm(&self) -> Vec<(Coordinate, ColoredPiece)> {
         let mut pieces = Vec::new();
@@ -56,63 +100,159 @@ impl Board {
 
         pieces
     }
+
+    /// Finds the king of `color`
+    /// # Arguments
+    /// * `color` - The color of the king to search for
+    pub fn find_king(&self, color: Color) -> Option<Coordinate> {
+        let king = ColoredPiece {
+            piece: Piece::King,
+            color,
+        };
+
+        for file in 0..8 {
+            for rank in 0..8 {
+                if self.squares[file][rank] == Some(king) {
+                    return Coordinate::from_u8s(file as u8, rank as u8);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Checks whether any piece of `by` attacks `target`
+    /// # Arguments
+    /// * `target` - The square to check
+    /// * `by` - The attacking color
+    pub fn is_attacked(&self, target: Coordinate, by: Color) -> bool {
+        let is = |coordinate: Option<Coordinate>, pieces: &[Piece]| {
+            coordinate
+                .and_then(|c| self.get(c))
+                .is_some_and(|p| p.color == by && pieces.contains(&p.piece))
+        };
+
+        // Pawns attack diagonally forward, so look diagonally backward
+        let pawn_rank = match by {
+            Color::White => -1,
+            Color::Black => 1,
+        };
+        if is(target.offset(-1, pawn_rank), &[Piece::Pawn])
+            || is(target.offset(1, pawn_rank), &[Piece::Pawn])
+        {
+            return true;
+        }
+
+        if KNIGHT_OFFSETS
+            .iter()
+            .any(|(f, r)| is(target.offset(*f, *r), &[Piece::Knight]))
+        {
+            return true;
+        }
+
+        if KING_OFFSETS
+            .iter()
+            .any(|(f, r)| is(target.offset(*f, *r), &[Piece::King]))
+        {
+            return true;
+        }
+
+        let slides = |directions: &[(i8, i8)], pieces: &[Piece]| {
+            directions.iter().any(|(f, r)| {
+                let mut start = target;
+                while let Some(coordinate) = start.offset(*f, *r) {
+                    start = coordinate;
+                    if let Some(piece) = self.get(coordinate) {
+                        return piece.color == by && pieces.contains(&piece.piece);
+                    }
+                }
+                false
+            })
+        };
+
+        slides(&ROOK_DIRECTIONS, &[Piece::Rook, Piece::Queen])
+            || slides(&BISHOP_DIRECTIONS, &[Piece::Bishop, Piece::Queen])
+    }
 }
 
-impl FromFENString for Board {
-    fn from_fen(fen: &str) -> Result<Self, crate::fen::FromFENError>
-    where
-        Self: Sized,
-    {
+impl Board {
+    /// Parses the piece placement field of a FEN string.
+    ///
+    /// Errors are located at the offending character. In [FENMode::Lenient]
+    /// squares missing at the end of a rank or board are left empty
+    /// # Arguments
+    /// * `fen` - The piece placement field
+    /// * `mode` - How thoroughly to check the field
+    pub fn from_placement(fen: &str, mode: FENMode) -> Result<Self, FromFENError> {
         let mut board = Self::default();
+        let lenient = mode == FENMode::Lenient;
+        let at = |offset: usize, error: FromFENError| error.at(FENField::Placement, offset);
 
-        let mut rank = 7u8;
+        // The number of ranks started and the files filled on the current one
+        let mut ranks = 1u8;
         let mut file = 0u8;
 
-        // Iterate over all piece characters
-        for piece_char in fen.chars() {
-            // A '/' indicates a new rank
-            if piece_char == '/' {
-                file = 0;
-                rank -= 1;
-
-                // Check for out of range ranks
-                if rank > 8 {
-                    return Err(FromFENError::InvalidRankLayout(rank));
+        for (offset, c) in fen.chars().enumerate() {
+            // A '/' starts the next rank down
+            if c == '/' {
+                if file < 8 && !lenient {
+                    return Err(at(offset, FromFENError::InvalidFileLayout(file)));
                 }
+                if ranks == 8 {
+                    return Err(at(offset, FromFENError::InvalidRankLayout(ranks + 1)));
+                }
+                ranks += 1;
+                file = 0;
 
                 continue;
             }
 
-            // Try to parse a digit. In that case, add it to the file
-            match piece_char.to_digit(10) {
-                Some(num) => {
-                    file += num as u8;
-                    if file > 8 {
-                        return Err(FromFENError::InvalidFileLayout(file));
-                    }
-                }
-                // If we can't parse to a number, it can only be a placement
+            // A digit skips empty squares, anything else has to be a piece
+            match c.to_digit(10) {
+                Some(0) => return Err(at(offset, FromFENError::InvalidPlacementSymbol(c))),
+                Some(num) => file += num as u8,
                 None => {
-                    let piece = ColoredPiece::from_fen(piece_char)?;
-                    board.squares[file as usize][rank as usize] = Some(piece);
-
-                    file += 1;
-                    if file > 8 {
-                        return Err(FromFENError::InvalidFileLayout(file));
+                    let piece = ColoredPiece::from_fen(c).map_err(|e| at(offset, e))?;
+                    if file < 8 {
+                        board.squares[file as usize][8 - ranks as usize] = Some(piece);
                     }
+                    file += 1;
                 }
             }
+
+            if file > 8 {
+                return Err(at(offset, FromFENError::InvalidFileLayout(file)));
+            }
+        }
+
+        if !lenient {
+            let end = fen.chars().count();
+            if file < 8 {
+                return Err(at(end, FromFENError::InvalidFileLayout(file)));
+            }
+            if ranks < 8 {
+                return Err(at(end, FromFENError::InvalidRankLayout(ranks)));
+            }
         }
 
         Ok(board)
     }
 }
 
+impl FromFENString for Board {
+    fn from_fen(fen: &str) -> Result<Self, crate::fen::FromFENError>
+    where
+        Self: Sized,
+    {
+        Self::from_placement(fen, FENMode::Normal)
+    }
+}
+
 impl ToFENString for Board {
     fn to_fen(&self) -> String {
         let mut ranks: Vec<String> = Vec::new();
 
-        for rank in 0..8 {
+        for rank in (0..8).rev() {
             let mut string = String::new();
             let mut empty_counter: u8 = 0;
             for file in 0..8 {
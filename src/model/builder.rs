@@ -0,0 +1,157 @@
+//! Setting up positions piece by piece
+//!
+//! A [PositionBuilder] collects the parts of a position, e.g. for tests and
+//! tools, and checks them with [GameState::validate()] once it is built
+use crate::fen::FromFENError;
+
+use super::{
+    board::Board,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::{Coordinate, File},
+    game::GameState,
+    piece::ColoredPiece,
+};
+
+/// Builds a [GameState] of standard chess or Chess960 from its parts
+#[derive(Clone, Copy, Debug)]
+pub struct PositionBuilder {
+    board: Board,
+    side_to_move: Color,
+    castling: CastlingRights,
+    en_passant: Option<Coordinate>,
+    halfmove_clock: u16,
+    fullmove_counter: u16,
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&GameState> for PositionBuilder {
+    fn from(value: &GameState) -> Self {
+        Self {
+            board: value.board,
+            side_to_move: value.side_to_move,
+            castling: value.castling,
+            en_passant: value.en_passant,
+            halfmove_clock: value.halfmove_clock,
+            fullmove_counter: value.fullmove_counter,
+        }
+    }
+}
+
+impl PositionBuilder {
+    /// Starts with an empty board, white to move and no castling rights
+    pub fn new() -> Self {
+        Self {
+            board: Board::default(),
+            side_to_move: Color::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_counter: 1,
+        }
+    }
+
+    /// Replaces all pieces with the ones on `board`
+    /// # Arguments
+    /// * `board` - The board to start from
+    pub fn board(mut self, board: Board) -> Self {
+        self.board = board;
+        self
+    }
+
+    /// Places `piece` on `square`, replacing any piece there
+    /// # Arguments
+    /// * `square` - The square to place the piece on
+    /// * `piece` - The piece to place
+    pub fn piece(mut self, square: Coordinate, piece: ColoredPiece) -> Self {
+        self.board.set(square, piece);
+        self
+    }
+
+    /// Removes the piece on `square`, if any
+    /// # Arguments
+    /// * `square` - The square to empty
+    pub fn remove(mut self, square: Coordinate) -> Self {
+        self.board.remove(square);
+        self
+    }
+
+    /// Sets the color that has to make the next move
+    /// # Arguments
+    /// * `color` - The color to move
+    pub fn side_to_move(mut self, color: Color) -> Self {
+        self.side_to_move = color;
+        self
+    }
+
+    /// Replaces all castling rights
+    /// # Arguments
+    /// * `castling` - The castling abilities of both players
+    pub fn castling_rights(mut self, castling: CastlingRights) -> Self {
+        self.castling = castling;
+        self
+    }
+
+    /// Allows `color` to castle with the rook on `rook`, which is on
+    /// the `H` or `A` file in standard chess
+    /// # Arguments
+    /// * `color` - The color that may castle
+    /// * `kingside` - Whether the rook castles towards the `H` file
+    /// * `rook` - The file of the castling rook
+    pub fn castling(mut self, color: Color, kingside: bool, rook: File) -> Self {
+        self.castling.set(color, kingside, true);
+        if kingside {
+            self.castling.kingside_files[color as usize] = rook;
+        } else {
+            self.castling.queenside_files[color as usize] = rook;
+        }
+        self
+    }
+
+    /// Sets the square a pawn can be captured on en passant
+    /// # Arguments
+    /// * `square` - The square behind the pawn that was just pushed twice, if any
+    pub fn en_passant(mut self, square: Option<Coordinate>) -> Self {
+        self.en_passant = square;
+        self
+    }
+
+    /// Sets the number of halfmoves since the last capture or pawn move
+    /// # Arguments
+    /// * `halfmove_clock` - The number of halfmoves
+    pub fn halfmove_clock(mut self, halfmove_clock: u16) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    /// Sets the number of the full move
+    /// # Arguments
+    /// * `fullmove_counter` - The number of the full move, starting at 1
+    pub fn fullmove_counter(mut self, fullmove_counter: u16) -> Self {
+        self.fullmove_counter = fullmove_counter;
+        self
+    }
+
+    /// Builds the game state if it passes [GameState::validate()].
+    /// Castling moves are written as in Chess960 if the kings or rooks
+    /// of the castling rights do not start on their standard squares
+    pub fn build(self) -> Result<GameState, FromFENError> {
+        let mut state = GameState::new(
+            self.board,
+            self.side_to_move,
+            self.castling,
+            self.en_passant,
+            self.halfmove_clock,
+            self.fullmove_counter,
+        );
+        state.chess960 = state.needs_chess960();
+        state.validate()?;
+
+        Ok(state)
+    }
+}
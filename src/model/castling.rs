@@ -0,0 +1,325 @@
+//! Castling rights
+use crate::fen::{FromFENError, FromFENString, ToFENString};
+
+use super::{
+    board::Board,
+    color::Color,
+    coordinate::{Coordinate, File},
+    piece::{ColoredPiece, Piece},
+};
+
+/// The castling abilities of both players.
+///
+/// To support Chess960 the rights also remember the files of the castling
+/// rooks, which are the `H` and `A` file in standard chess
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CastlingRights {
+    /// White may castle towards the `H` file
+    pub white_kingside: bool,
+    /// White may castle towards the `A` file
+    pub white_queenside: bool,
+    /// Black may castle towards the `H` file
+    pub black_kingside: bool,
+    /// Black may castle towards the `A` file
+    pub black_queenside: bool,
+    /// The files of the rooks castling towards the `H` file, indexed by [Color]
+    pub kingside_files: [File; 2],
+    /// The files of the rooks castling towards the `A` file, indexed by [Color]
+    pub queenside_files: [File; 2],
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: false,
+            black_queenside: false,
+            kingside_files: [File::FileH; 2],
+            queenside_files: [File::FileA; 2],
+        }
+    }
+}
+
+impl CastlingRights {
+    /// All castling rights, as in the starting position
+    pub const ALL: Self = Self {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+        kingside_files: [File::FileH; 2],
+        queenside_files: [File::FileA; 2],
+    };
+
+    /// Returns whether `color` may castle towards the `H` file
+    pub fn kingside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_kingside,
+            Color::Black => self.black_kingside,
+        }
+    }
+
+    /// Returns whether `color` may castle towards the `A` file
+    pub fn queenside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_queenside,
+            Color::Black => self.black_queenside,
+        }
+    }
+
+    /// Sets whether `color` may castle towards the `H` or `A` file
+    /// # Arguments
+    /// * `color` - The color to set the right for
+    /// * `kingside` - Whether to set the right towards the `H` file
+    /// * `allowed` - Whether castling is allowed
+    pub fn set(&mut self, color: Color, kingside: bool, allowed: bool) {
+        match (color, kingside) {
+            (Color::White, true) => self.white_kingside = allowed,
+            (Color::White, false) => self.white_queenside = allowed,
+            (Color::Black, true) => self.black_kingside = allowed,
+            (Color::Black, false) => self.black_queenside = allowed,
+        }
+    }
+
+    /// Returns the file of the rook `color` castles with towards the `H` or `A` file
+    /// # Arguments
+    /// * `color` - The color of the rook
+    /// * `kingside` - Whether to return the rook castling towards the `H` file
+    pub fn rook_file(&self, color: Color, kingside: bool) -> File {
+        if kingside {
+            self.kingside_files[color as usize]
+        } else {
+            self.queenside_files[color as usize]
+        }
+    }
+
+    /// Removes all castling rights of `color`
+    pub fn remove(&mut self, color: Color) {
+        match color {
+            Color::White => {
+                self.white_kingside = false;
+                self.white_queenside = false;
+            }
+            Color::Black => {
+                self.black_kingside = false;
+                self.black_queenside = false;
+            }
+        }
+    }
+
+    /// Removes the right of the rook that castles from `square`,
+    /// e.g. when it moves, is captured or explodes
+    /// # Arguments
+    /// * `square` - The square a piece left or was removed from
+    pub fn remove_rook(&mut self, square: Coordinate) {
+        let color = match square.rank as u8 {
+            0 => Color::White,
+            7 => Color::Black,
+            _ => return,
+        };
+
+        for kingside in [true, false] {
+            if self.rook_file(color, kingside) == square.file {
+                self.set(color, kingside, false);
+            }
+        }
+    }
+
+    /// Returns whether all castling rooks are on the `H` and `A` files as in standard chess
+    pub fn is_standard(&self) -> bool {
+        self.kingside_files == [File::FileH; 2] && self.queenside_files == [File::FileA; 2]
+    }
+
+    /// Packs the rights into the lower 4 bits of a [u8] (`KQkq` from lowest to highest)
+    pub fn bits(&self) -> u8 {
+        self.white_kingside as u8
+            | (self.white_queenside as u8) << 1
+            | (self.black_kingside as u8) << 2
+            | (self.black_queenside as u8) << 3
+    }
+
+    /// Unpacks rights that have been packed using [CastlingRights::bits()],
+    /// the rooks are placed on the standard files
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            white_kingside: bits & 1 != 0,
+            white_queenside: bits & 2 != 0,
+            black_kingside: bits & 4 != 0,
+            black_queenside: bits & 8 != 0,
+            ..Default::default()
+        }
+    }
+
+    /// Parses a castling field for the pieces on `board`.
+    ///
+    /// Besides `KQkq` this accepts Shredder-FEN, which names the files of the
+    /// castling rooks (`HAha`), and X-FEN, where `K` and `Q` stand for the
+    /// outermost rook on that side of the king
+    /// # Arguments
+    /// * `fen` - The castling field
+    /// * `board` - The board the castling rights apply to
+    pub fn from_fen_for(fen: &str, board: &Board) -> Result<Self, FromFENError> {
+        Self::parse(fen, Some(board))
+    }
+
+    /// Parses a castling field, looking up the king and rooks on `board` if there is one
+    fn parse(fen: &str, board: Option<&Board>) -> Result<Self, FromFENError> {
+        let mut rights = Self::default();
+
+        if fen == "-" {
+            return Ok(rights);
+        }
+
+        for c in fen.chars() {
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let king = board
+                .and_then(|board| home_king(board, color))
+                .unwrap_or(File::FileE);
+
+            let (kingside, file) = match c.to_ascii_uppercase() {
+                'K' => (true, outermost_rook(board, color, king, true)),
+                'Q' => (false, outermost_rook(board, color, king, false)),
+                'A'..='H' => {
+                    let file = File::from_u8(c.to_ascii_uppercase() as u8 - b'A')
+                        .expect("[DEV] Internal File and Rank mishap");
+                    (file as u8 > king as u8, file)
+                }
+                _ => return Err(FromFENError::InvalidCastlingSymbol(c)),
+            };
+
+            rights.set(color, kingside, true);
+            if kingside {
+                rights.kingside_files[color as usize] = file;
+            } else {
+                rights.queenside_files[color as usize] = file;
+            }
+        }
+
+        Ok(rights)
+    }
+
+    /// Serializes the rights as a Shredder-FEN castling field, naming the rook files (`HAha`)
+    pub fn to_shredder_fen(&self) -> String {
+        self.format(|_, _, file| file)
+    }
+
+    /// Serializes the rights as an X-FEN castling field for the pieces on `board`.
+    ///
+    /// `K` and `Q` are used unless another rook stands further out on that
+    /// side of the king, which makes this the normal notation in standard chess
+    /// # Arguments
+    /// * `board` - The board the castling rights apply to
+    pub fn to_xfen(&self, board: &Board) -> String {
+        self.format(|color, kingside, file| {
+            let outermost = home_king(board, color).is_none_or(|king| {
+                outermost_rook(Some(board), color, king, kingside) as u8 == file as u8 - b'A'
+            });
+
+            match (outermost, kingside) {
+                (true, true) => 'K',
+                (true, false) => 'Q',
+                (false, _) => file,
+            }
+        })
+    }
+
+    /// Writes the rights in `KQkq` order, `symbol` returns the uppercase
+    /// letter of a right given its color, side and rook file letter
+    fn format(&self, symbol: impl Fn(Color, bool, char) -> char) -> String {
+        let mut string = String::new();
+
+        for color in [Color::White, Color::Black] {
+            for kingside in [true, false] {
+                let allowed = if kingside {
+                    self.kingside(color)
+                } else {
+                    self.queenside(color)
+                };
+                if !allowed {
+                    continue;
+                }
+
+                let file = (b'A' + self.rook_file(color, kingside) as u8) as char;
+                let c = symbol(color, kingside, file);
+                string.push(match color {
+                    Color::White => c,
+                    Color::Black => c.to_ascii_lowercase(),
+                });
+            }
+        }
+
+        if string.is_empty() {
+            string.push('-');
+        }
+
+        string
+    }
+}
+
+/// Returns the file of the king of `color` if it stands on its home rank
+fn home_king(board: &Board, color: Color) -> Option<File> {
+    board
+        .find_king(color)
+        .filter(|king| king.rank as u8 == home_rank(color))
+        .map(|king| king.file)
+}
+
+/// Returns the rank the pieces of `color` start on
+fn home_rank(color: Color) -> u8 {
+    match color {
+        Color::White => 0,
+        Color::Black => 7,
+    }
+}
+
+/// Finds the rook of `color` furthest from `king` on its home rank towards the `H` or `A` file,
+/// falling back to the standard files if there is no board or rook
+fn outermost_rook(board: Option<&Board>, color: Color, king: File, kingside: bool) -> File {
+    let rook = ColoredPiece {
+        piece: Piece::Rook,
+        color,
+    };
+    let files: Vec<u8> = if kingside {
+        (king as u8 + 1..8).rev().collect()
+    } else {
+        (0..king as u8).collect()
+    };
+
+    board
+        .and_then(|board| {
+            files.into_iter().find(|file| {
+                Coordinate::from_u8s(*file, home_rank(color))
+                    .is_some_and(|square| board.get(square) == Some(rook))
+            })
+        })
+        .and_then(File::from_u8)
+        .unwrap_or(if kingside { File::FileH } else { File::FileA })
+}
+
+impl FromFENString for CastlingRights {
+    /// Parses a castling field without knowing the board, so
+    /// `KQkq` refer to the standard rook files and the king is
+    /// assumed on the `E` file for Shredder-FEN letters
+    fn from_fen(fen: &str) -> Result<Self, FromFENError>
+    where
+        Self: Sized,
+    {
+        Self::parse(fen, None)
+    }
+}
+
+impl ToFENString for CastlingRights {
+    /// Writes `KQkq` for rooks on the standard files and the file letter for others
+    fn to_fen(&self) -> String {
+        self.format(|_, kingside, file| match (kingside, file) {
+            (true, 'H') => 'K',
+            (false, 'A') => 'Q',
+            _ => file,
+        })
+    }
+}
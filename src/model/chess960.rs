@@ -0,0 +1,166 @@
+//! Chess960 (Fischer Random Chess) starting positions
+//!
+//! The 960 starting positions are numbered as proposed by Reinhard
+//! Scharnagl: the index encodes the files of the light and dark squared
+//! bishop, the queen and the knights, the rooks and the king take the
+//! remaining files with the king in between. Index 518 is the standard
+//! starting position.
+use crate::fen::ToFENString;
+
+use super::{
+    board::Board,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::{Coordinate, File},
+    game::GameState,
+    piece::{ColoredPiece, Piece},
+};
+
+/// The number of Chess960 starting positions
+pub const CHESS960_POSITIONS: u16 = 960;
+
+/// The index of the standard starting position
+pub const STANDARD_INDEX: u16 = 518;
+
+/// The files of the two knights among the five files left after placing the bishops and queen
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// Returns the pieces on the first rank of the starting position `index`, from `A` to `H`
+/// # Arguments
+/// * `index` - The number of the starting position (`0`-`959`)
+pub fn back_rank(index: u16) -> Option<[Piece; 8]> {
+    if index >= CHESS960_POSITIONS {
+        return None;
+    }
+
+    let mut rank: [Option<Piece>; 8] = [None; 8];
+    let mut n = index as usize;
+
+    // The light squared bishop goes to b, d, f or h, the dark squared one to a, c, e or g
+    rank[(n % 4) * 2 + 1] = Some(Piece::Bishop);
+    n /= 4;
+    rank[(n % 4) * 2] = Some(Piece::Bishop);
+    n /= 4;
+
+    let place = |rank: &mut [Option<Piece>; 8], nth: usize, piece: Piece| {
+        let file = (0..8)
+            .filter(|f| rank[*f].is_none())
+            .nth(nth)
+            .expect("[DEV] Chess960 rank is full");
+        rank[file] = Some(piece);
+    };
+
+    place(&mut rank, n % 6, Piece::Queen);
+    n /= 6;
+
+    // Place the second knight first so the index of the first one stays valid
+    let (first, second) = KNIGHTS[n];
+    place(&mut rank, second, Piece::Knight);
+    place(&mut rank, first, Piece::Knight);
+
+    for piece in [Piece::Rook, Piece::King, Piece::Rook] {
+        place(&mut rank, 0, piece);
+    }
+
+    Some(rank.map(|piece| piece.expect("[DEV] Chess960 rank has a gap")))
+}
+
+impl Board {
+    /// Creates the board of the Chess960 starting position `index`
+    /// # Arguments
+    /// * `index` - The number of the starting position (`0`-`959`)
+    pub fn from_chess960(index: u16) -> Option<Board> {
+        let pieces = back_rank(index)?;
+        let mut board = Board::default();
+
+        for (file, piece) in pieces.iter().enumerate() {
+            for (color, rank, piece) in [
+                (Color::White, 0, *piece),
+                (Color::White, 1, Piece::Pawn),
+                (Color::Black, 6, Piece::Pawn),
+                (Color::Black, 7, *piece),
+            ] {
+                board.squares[file][rank] = Some(ColoredPiece { piece, color });
+            }
+        }
+
+        Some(board)
+    }
+
+    /// Returns the index of the Chess960 starting position this board shows, if it is one
+    pub fn chess960_index(&self) -> Option<u16> {
+        (0..CHESS960_POSITIONS).find(|index| {
+            Board::from_chess960(*index).is_some_and(|board| board.squares == self.squares)
+        })
+    }
+}
+
+impl GameState {
+    /// Creates the Chess960 starting position `index` with white to move and all castling rights
+    /// # Arguments
+    /// * `index` - The number of the starting position (`0`-`959`)
+    pub fn from_chess960(index: u16) -> Option<GameState> {
+        let board = Board::from_chess960(index)?;
+        let pieces = back_rank(index)?;
+        let rooks: Vec<File> = (0..8u8)
+            .filter(|file| pieces[*file as usize] == Piece::Rook)
+            .filter_map(File::from_u8)
+            .collect();
+
+        let castling = CastlingRights {
+            queenside_files: [rooks[0]; 2],
+            kingside_files: [rooks[1]; 2],
+            ..CastlingRights::ALL
+        };
+
+        let mut state = GameState::new(board, Color::White, castling, None, 0, 1);
+        state.chess960 = true;
+
+        Some(state)
+    }
+
+    /// Returns whether castling in this position only works under the Chess960 rules,
+    /// because a castling rook or king is not on its standard file
+    pub fn needs_chess960(&self) -> bool {
+        if !self.castling.is_standard() {
+            return true;
+        }
+
+        [Color::White, Color::Black].into_iter().any(|color| {
+            let rank = match color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let king = ColoredPiece {
+                piece: Piece::King,
+                color,
+            };
+            let king_home = Coordinate::from_u8s(File::FileE as u8, rank)
+                .is_some_and(|square| self.board.get(square) == Some(king));
+
+            (self.castling.kingside(color) || self.castling.queenside(color)) && !king_home
+        })
+    }
+
+    /// Serializes the position as FEN with a Shredder-FEN castling field,
+    /// which names the files of the castling rooks (`HAha`)
+    pub fn to_shredder_fen(&self) -> String {
+        let fen = self.to_fen();
+        let mut fields: Vec<&str> = fen.split(' ').collect();
+        let castling = self.castling.to_shredder_fen();
+        fields[2] = &castling;
+
+        fields.join(" ")
+    }
+}
@@ -4,7 +4,7 @@ use crate::fen::{FromFENChar, FromFENError};
 use std::fmt::Display;
 
 /// The color of player (black or white)
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Color {
     /// The white player / piece
     White,
@@ -12,6 +12,16 @@ pub enum Color {
     Black,
 }
 
+impl Color {
+    /// Returns the opposing color
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
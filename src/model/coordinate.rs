@@ -1,8 +1,10 @@
 use std::fmt::Display;
 
+use crate::fen::{FromFENError, FromFENString, ToFENString};
+
 /// A file on the chess board (`A`-`H`)
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum File {
     FileA = 0,
     FileB = 1,
@@ -15,6 +17,18 @@ pub enum File {
 }
 
 impl File {
+    /// All files from `A` to `H`
+    pub const ALL: [File; 8] = [
+        Self::FileA,
+        Self::FileB,
+        Self::FileC,
+        Self::FileD,
+        Self::FileE,
+        Self::FileF,
+        Self::FileG,
+        Self::FileH,
+    ];
+
     /// Tries to get a file enum from a [u8] between `0` and `7`
     pub fn from_u8(file: u8) -> Option<Self> {
         match file {
@@ -78,7 +92,7 @@ impl Display for File {
 
 /// A rank on the chessboard (`1`-`8`)
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Rank {
     Rank1 = 0,
     Rank2 = 1,
@@ -91,6 +105,18 @@ pub enum Rank {
 }
 
 impl Rank {
+    /// All ranks from `1` to `8`
+    pub const ALL: [Rank; 8] = [
+        Self::Rank1,
+        Self::Rank2,
+        Self::Rank3,
+        Self::Rank4,
+        Self::Rank5,
+        Self::Rank6,
+        Self::Rank7,
+        Self::Rank8,
+    ];
+
     /// Tries to get a rank enum from a [u8] between `0` and `7`
     pub fn from_u8(rank: u8) -> Option<Self> {
         match rank {
@@ -153,7 +179,7 @@ impl Display for Rank {
 }
 
 /// A coordinate on the chess board made up from [File]s and [Rank]s
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Coordinate {
     pub file: File,
     pub rank: Rank,
@@ -209,4 +235,43 @@ impl Coordinate {
             rank: self.rank.prev()?,
         })
     }
+
+    /// Returns the coordinate that is `file_offset` files and
+    /// `rank_offset` ranks away if it is still on the board
+    /// # Arguments
+    /// * `file_offset` - The files to move (positive towards `H`)
+    /// * `rank_offset` - The ranks to move (positive towards `8`)
+    pub fn offset(self, file_offset: i8, rank_offset: i8) -> Option<Coordinate> {
+        let file = u8::try_from(self.file as i8 + file_offset).ok()?;
+        let rank = u8::try_from(self.rank as i8 + rank_offset).ok()?;
+
+        Self::from_u8s(file, rank)
+    }
+}
+
+impl FromFENString for Coordinate {
+    fn from_fen(fen: &str) -> Result<Self, FromFENError>
+    where
+        Self: Sized,
+    {
+        let mut chars = fen.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file @ 'a'..='h'), Some(rank @ '1'..='8'), None) => {
+                Ok(Self::from_u8s(file as u8 - b'a', rank as u8 - b'1')
+                    .expect("[DEV] Internal File and Rank mishap"))
+            }
+            _ => Err(FromFENError::InvalidSquare(fen.to_string())),
+        }
+    }
+}
+
+impl ToFENString for Coordinate {
+    fn to_fen(&self) -> String {
+        format!(
+            "{}{}",
+            (b'a' + self.file as u8) as char,
+            (b'1' + self.rank as u8) as char
+        )
+    }
 }
@@ -1,27 +1,577 @@
 //! The game and its state
-use crate::fen::{FromFENError, FromFENString};
+use crate::fen::{FENField, FENMode, FromFENError, FromFENString, ToFENString};
 
-use super::board::Board;
+use super::{
+    board::Board,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::Coordinate,
+    moves::Move,
+    piece::{ColoredPiece, Piece},
+    square::Square,
+    validation::{locate, split_fields},
+    variant::{square_bit, Pockets, Variant, WINNING_CHECKS},
+    zobrist::KEYS,
+};
 
 /// A game state
+#[derive(Clone, Copy, Debug)]
 pub struct GameState {
     pub board: Board,
-    //pub side_to_move: Color,          //combine with others in bitmap?
-    //pub casteling_ability: u8,        //combine with others in bitmap?
-    //pub en_passant_target_square: u8, // in [0, 63]
-    //pub halfmove_clock: u8,           // max 50
-    //pub fullmove_counter: u16,
+    /// The color that has to make the next move
+    pub side_to_move: Color,
+    /// The castling abilities of both players
+    pub castling: CastlingRights,
+    /// The square a pawn can be captured on en passant
+    pub en_passant: Option<Coordinate>,
+    /// The number of halfmoves since the last capture or pawn move
+    pub halfmove_clock: u16,
+    /// The number of the full move, starting at 1 and incremented after black moved
+    pub fullmove_counter: u16,
+    /// Whether castling moves are written as the king capturing its own rook, as in Chess960
+    pub chess960: bool,
+    /// The rules the game is played by
+    pub variant: Variant,
+    /// The pieces both players can drop in Crazyhouse
+    pub pockets: Pockets,
+    /// The squares of pieces that were promoted from pawns (a mask as in [Square::bit()]),
+    /// they go back to the pocket as pawns when captured in Crazyhouse
+    pub promoted: u64,
+    /// The number of checks white and black have given in Three-check
+    pub checks: [u8; 2],
+    /// The Zobrist hash, kept up to date by [GameState::make_move()]
+    pub(super) hash: u64,
+}
+
+/// The ways a game can end
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The side to move is checkmated
+    Checkmate {
+        /// The color that delivered the mate
+        winner: Color,
+    },
+    /// The side to move has no legal moves but is not in check
+    Stalemate,
+    /// 50 moves have been played without a capture or pawn move
+    FiftyMoveRule,
+    /// The same position occurred for the third time
+    ThreefoldRepetition,
+    /// No side can possibly deliver mate
+    InsufficientMaterial,
+    /// A rule of the variant decided the game, e.g. a king reached the center
+    VariantWin {
+        /// The color that won
+        winner: Color,
+    },
+    /// A rule of the variant drew the game, e.g. both kings finished the race
+    VariantDraw,
+}
+
+impl Outcome {
+    /// Returns the winner, if there is one
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            Self::Checkmate { winner } | Self::VariantWin { winner } => Some(*winner),
+            _ => None,
+        }
+    }
+
+    /// Returns the result of the game this outcome leads to
+    pub fn result(&self) -> GameResult {
+        match self.winner() {
+            Some(Color::White) => GameResult::WhiteWins,
+            Some(Color::Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+/// The result of a finished game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    /// White won the game
+    WhiteWins,
+    /// Black won the game
+    BlackWins,
+    /// The game ended in a draw
+    Draw,
+}
+
+impl GameResult {
+    /// Returns the points white scored (`1.0`, `0.5` or `0.0`)
+    pub fn white_score(&self) -> f32 {
+        match self {
+            Self::WhiteWins => 1.0,
+            Self::Draw => 0.5,
+            Self::BlackWins => 0.0,
+        }
+    }
+}
+
+impl GameState {
+    /// Creates a new game state from its parts
+    /// # Arguments
+    /// * `board` - The pieces on the board
+    /// * `side_to_move` - The color that has to make the next move
+    /// * `castling` - The castling abilities of both players
+    /// * `en_passant` - The square a pawn can be captured on en passant
+    /// * `halfmove_clock` - The halfmoves since the last capture or pawn move
+    /// * `fullmove_counter` - The number of the full move
+    pub fn new(
+        board: Board,
+        side_to_move: Color,
+        castling: CastlingRights,
+        en_passant: Option<Coordinate>,
+        halfmove_clock: u16,
+        fullmove_counter: u16,
+    ) -> Self {
+        let mut state = Self {
+            board,
+            side_to_move,
+            castling,
+            en_passant,
+            halfmove_clock,
+            fullmove_counter,
+            chess960: false,
+            variant: Variant::Standard,
+            pockets: Pockets::default(),
+            promoted: 0,
+            checks: [0; 2],
+            hash: 0,
+        };
+        state.hash = state.compute_hash();
+
+        state
+    }
+
+    /// Returns the Zobrist hash of this position
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Recomputes the Zobrist hash after fields have been changed directly
+    pub fn update_hash(&mut self) {
+        self.hash = self.compute_hash();
+    }
+
+    /// Computes the Zobrist hash of this position from scratch
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for (coordinate, piece) in self.board.get_occupied_fields_fm() {
+            hash ^= KEYS.piece(piece, coordinate);
+        }
+
+        if self.side_to_move == Color::Black {
+            hash ^= KEYS.black_to_move;
+        }
+
+        hash ^= KEYS.castling[self.castling.bits() as usize];
+
+        if let Some(en_passant) = self.en_passant {
+            hash ^= KEYS.en_passant[en_passant.file as usize];
+        }
+
+        for color in [Color::White, Color::Black] {
+            for piece in Piece::ALL {
+                let count = self.pockets.count(color, piece);
+                if count > 0 {
+                    hash ^= KEYS.pocket(color, piece, count);
+                }
+            }
+            hash ^= KEYS.check(color, self.checks[color as usize]);
+        }
+
+        hash
+    }
+
+    /// Returns whether the side to move is in check, never in Antichess where kings are not royal
+    pub fn is_check(&self) -> bool {
+        self.variant != Variant::Antichess
+            && self
+                .board
+                .find_king(self.side_to_move)
+                .is_some_and(|king| self.is_square_attacked(king, self.side_to_move.opposite()))
+    }
+
+    /// Returns whether `mv` captures a piece (including en passant)
+    pub fn is_capture(&self, mv: Move) -> bool {
+        self.board.get(mv.to).is_some_and(|target| {
+            self.board
+                .get(mv.from)
+                .is_some_and(|p| p.color != target.color)
+        }) || self.is_en_passant(mv)
+    }
+
+    /// Returns whether `mv` is an en passant capture
+    pub fn is_en_passant(&self, mv: Move) -> bool {
+        Some(mv.to) == self.en_passant
+            && self
+                .board
+                .get(mv.from)
+                .is_some_and(|piece| piece.piece == Piece::Pawn)
+    }
+
+    /// Returns whether `mv` is a castling move, either the king moving
+    /// two files or, as in Chess960, the king capturing its own rook
+    pub fn is_castling(&self, mv: Move) -> bool {
+        let Some(king) = self
+            .board
+            .get(mv.from)
+            .filter(|piece| piece.piece == Piece::King)
+        else {
+            return false;
+        };
+
+        self.board.get(mv.to)
+            == Some(ColoredPiece {
+                piece: Piece::Rook,
+                color: king.color,
+            })
+            || (mv.from.file as i8 - mv.to.file as i8).abs() == 2
+    }
+
+    /// Plays `mv` and returns the resulting game state.
+    ///
+    /// The move is expected to be at least pseudo-legal
+    /// # Arguments
+    /// * `mv` - The move to play
+    pub fn make_move(&self, mv: Move) -> GameState {
+        if let Some(piece) = mv.drop {
+            return self.make_drop(piece, mv.to);
+        }
+
+        let mut next = *self;
+        let piece = self
+            .board
+            .get(mv.from)
+            .expect("[DEV] Tried to move from an empty square");
+        let castling = self.is_castling(mv);
+        let capture = self.is_capture(mv);
+
+        next.hash ^= KEYS.castling[self.castling.bits() as usize];
+        if let Some(en_passant) = self.en_passant {
+            next.hash ^= KEYS.en_passant[en_passant.file as usize];
+        }
+
+        next.halfmove_clock += 1;
+        next.en_passant = None;
+
+        // Remove a captured piece
+        if let Some(captured) = self.board.get(mv.to).filter(|_| !castling) {
+            next.hash ^= KEYS.piece(captured, mv.to);
+            next.halfmove_clock = 0;
+            next.pocket_capture(piece.color, captured.piece, mv.to);
+        } else if self.is_en_passant(mv) {
+            let victim = Coordinate {
+                file: mv.to.file,
+                rank: mv.from.rank,
+            };
+            let captured = self
+                .board
+                .get(victim)
+                .expect("[DEV] En passant without pawn");
+            next.hash ^= KEYS.piece(captured, victim);
+            next.board.remove(victim);
+            next.pocket_capture(piece.color, captured.piece, victim);
+        }
+
+        // Promoted pieces stay marked as such when they move
+        let promoted = mv.promotion.is_some() || self.promoted & square_bit(mv.from) != 0;
+        next.promoted &= !(square_bit(mv.from) | square_bit(mv.to));
+        if promoted {
+            next.promoted |= square_bit(mv.to);
+        }
+
+        if castling {
+            // The king and rook end on the same squares as in standard chess
+            let kingside = mv.to.file as u8 > mv.from.file as u8;
+            let square = |file: u8| {
+                Coordinate::from_u8s(file, mv.from.rank as u8)
+                    .expect("[DEV] Internal File and Rank mishap")
+            };
+            let rook_from = square(self.castling.rook_file(piece.color, kingside) as u8);
+            let (king_to, rook_to) = if kingside {
+                (square(6), square(5))
+            } else {
+                (square(2), square(3))
+            };
+            let rook = self
+                .board
+                .get(rook_from)
+                .expect("[DEV] Castling without rook");
+
+            // Both pieces are lifted first, they may land on each other's squares
+            next.board.remove(mv.from);
+            next.board.remove(rook_from);
+            next.board.set(king_to, piece);
+            next.board.set(rook_to, rook);
+            next.hash ^= KEYS.piece(piece, mv.from) ^ KEYS.piece(piece, king_to);
+            next.hash ^= KEYS.piece(rook, rook_from) ^ KEYS.piece(rook, rook_to);
+        } else {
+            // Move the piece itself, promoting it if needed
+            let placed = ColoredPiece {
+                piece: mv.promotion.unwrap_or(piece.piece),
+                color: piece.color,
+            };
+            next.board.remove(mv.from);
+            next.board.set(mv.to, placed);
+            next.hash ^= KEYS.piece(piece, mv.from) ^ KEYS.piece(placed, mv.to);
+        }
+
+        match piece.piece {
+            Piece::Pawn => {
+                next.halfmove_clock = 0;
+
+                // Only remember en passant squares that can actually be captured on,
+                // the double pushes from the first rank in Horde cannot be
+                let second_rank = match piece.color {
+                    Color::White => 1,
+                    Color::Black => 6,
+                };
+                if (mv.to.rank as i8 - mv.from.rank as i8).abs() == 2
+                    && mv.from.rank as u8 == second_rank
+                {
+                    let enemy_pawn = ColoredPiece {
+                        piece: Piece::Pawn,
+                        color: piece.color.opposite(),
+                    };
+                    if [-1, 1].iter().any(|f| {
+                        mv.to
+                            .offset(*f, 0)
+                            .is_some_and(|c| self.board.get(c) == Some(enemy_pawn))
+                    }) {
+                        // The skipped square lies between the start and target square
+                        next.en_passant = Coordinate::from_u8s(
+                            mv.from.file as u8,
+                            (mv.from.rank as u8 + mv.to.rank as u8) / 2,
+                        );
+                    }
+                }
+            }
+            Piece::King => next.castling.remove(piece.color),
+            _ => {}
+        }
+
+        // Moving from or onto the square of a castling rook removes its right
+        next.castling.remove_rook(mv.from);
+        next.castling.remove_rook(mv.to);
+
+        if capture && self.variant == Variant::Atomic {
+            next.explode(mv.to);
+        }
+
+        if piece.color == Color::Black {
+            next.fullmove_counter += 1;
+        }
+        next.side_to_move = piece.color.opposite();
+
+        next.hash ^= KEYS.black_to_move;
+        next.hash ^= KEYS.castling[next.castling.bits() as usize];
+        if let Some(en_passant) = next.en_passant {
+            next.hash ^= KEYS.en_passant[en_passant.file as usize];
+        }
+
+        next.count_check(piece.color);
+        next
+    }
+
+    /// Passes the turn to the other side without moving a piece
+    pub fn make_null_move(&self) -> GameState {
+        let mut next = *self;
+
+        if let Some(en_passant) = self.en_passant {
+            next.hash ^= KEYS.en_passant[en_passant.file as usize];
+        }
+        next.en_passant = None;
+        next.side_to_move = self.side_to_move.opposite();
+        next.hash ^= KEYS.black_to_move;
+        next.halfmove_clock += 1;
+
+        next
+    }
+
+    /// Returns whether neither side has enough material left to deliver mate
+    pub fn is_insufficient_material(&self) -> bool {
+        // Kings can always walk to the center or race, pieces in the pocket can be dropped
+        // and the remaining variants are won by losing or taking material
+        if matches!(
+            self.variant,
+            Variant::KingOfTheHill | Variant::Antichess | Variant::Horde | Variant::RacingKings
+        ) || !self.pockets.is_empty()
+        {
+            return false;
+        }
+
+        let mut minors = Vec::new();
+
+        for (coordinate, piece) in self.board.get_occupied_fields_fm() {
+            match piece.piece {
+                Piece::King => {}
+                Piece::Knight | Piece::Bishop => minors.push((coordinate, piece)),
+                _ => return false,
+            }
+        }
+
+        match minors.as_slice() {
+            [] | [_] => true,
+            [(c1, p1), (c2, p2)] => {
+                // Two bishops of different color on squares of the same color
+                p1.piece == Piece::Bishop
+                    && p2.piece == Piece::Bishop
+                    && p1.color != p2.color
+                    && Square::from(*c1).color() == Square::from(*c2).color()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the outcome of the game if it has ended.
+    ///
+    /// Repetitions are not detected as that requires the game history
+    pub fn outcome(&self) -> Option<Outcome> {
+        if let Some(outcome) = self.variant_outcome() {
+            return Some(outcome);
+        }
+
+        if self.legal_moves().is_empty() {
+            return Some(if self.is_check() {
+                Outcome::Checkmate {
+                    winner: self.side_to_move.opposite(),
+                }
+            } else {
+                Outcome::Stalemate
+            });
+        }
+
+        if self.halfmove_clock >= 100 {
+            return Some(Outcome::FiftyMoveRule);
+        }
+
+        if self.is_insufficient_material() {
+            return Some(Outcome::InsufficientMaterial);
+        }
+
+        None
+    }
+}
+
+impl GameState {
+    /// Parses a position in FEN notation, checking it as thoroughly as `mode` demands.
+    ///
+    /// Errors are located at the field and character offset they were found at
+    /// # Arguments
+    /// * `fen` - The FEN string
+    /// * `mode` - How thoroughly to check the string and position
+    pub fn from_fen_with(fen: &str, mode: FENMode) -> Result<GameState, FromFENError> {
+        let fields = split_fields(fen);
+        let end = fen.chars().count();
+
+        if let Some((_, extra)) = fields.get(FENField::ALL.len()) {
+            if mode == FENMode::Strict {
+                return Err(FromFENError::ExtraField(extra.to_string()));
+            }
+        }
+
+        // Missing fields take their default in lenient mode
+        let field = |field: FENField, default: &'static str| match fields.get(field as usize) {
+            Some(found) => Ok(*found),
+            None if mode == FENMode::Lenient => Ok((end, default)),
+            None => Err(FromFENError::MissingField(field)),
+        };
+
+        let (offset, placement) = field(FENField::Placement, "")?;
+        let board = Board::from_placement(placement, mode)
+            .map_err(|e| e.at(FENField::Placement, offset))?;
+
+        let (offset, side_to_move) = field(FENField::SideToMove, "w")?;
+        let side_to_move = match side_to_move {
+            "w" => Color::White,
+            "b" => Color::Black,
+            s => {
+                return Err(
+                    FromFENError::InvalidSideToMove(s.to_string()).at(FENField::SideToMove, offset)
+                )
+            }
+        };
+
+        let (offset, castling) = field(FENField::Castling, "-")?;
+        let castling = CastlingRights::from_fen_for(castling, &board).map_err(|e| {
+            let index = match e {
+                FromFENError::InvalidCastlingSymbol(c) => castling.chars().position(|s| s == c),
+                _ => None,
+            };
+            e.at(FENField::Castling, offset + index.unwrap_or(0))
+        })?;
+
+        let (offset, en_passant) = field(FENField::EnPassant, "-")?;
+        let en_passant = match en_passant {
+            "-" => None,
+            s => Some(Coordinate::from_fen(s).map_err(|e| e.at(FENField::EnPassant, offset))?),
+        };
+
+        let counter = |counter: FENField, default: &'static str| {
+            let (offset, value) = field(counter, default)?;
+            value
+                .parse::<u16>()
+                .map_err(|_| FromFENError::InvalidCounter(value.to_string()).at(counter, offset))
+        };
+        let halfmove_clock = counter(FENField::HalfmoveClock, "0")?;
+        let fullmove_counter = counter(FENField::FullmoveCounter, "1")?;
+
+        let mut state = Self::new(
+            board,
+            side_to_move,
+            castling,
+            en_passant,
+            halfmove_clock,
+            fullmove_counter,
+        );
+        state.chess960 = state.needs_chess960();
+
+        if mode == FENMode::Strict {
+            state.validate().map_err(|e| locate(e, &fields))?;
+        }
+
+        Ok(state)
+    }
 }
 
 impl FromFENString for GameState {
+    /// Parses a complete and well-formed FEN string, see [GameState::from_fen_with()]
+    /// for partial FENs or checking the position itself
     fn from_fen(fen: &str) -> Result<Self, FromFENError> {
-        let elements: Vec<&str> = fen.split(" ").collect();
-        if elements.len() < 6 {
-            return Err(FromFENError::InvalidFormat);
+        Self::from_fen_with(fen, FENMode::Normal)
+    }
+}
+
+impl ToFENString for GameState {
+    fn to_fen(&self) -> String {
+        let mut fields = vec![
+            self.placement_fen(),
+            match self.side_to_move {
+                Color::White => "w".to_string(),
+                Color::Black => "b".to_string(),
+            },
+            self.castling.to_xfen(&self.board),
+            self.en_passant
+                .map(|c| c.to_fen())
+                .unwrap_or_else(|| "-".to_string()),
+        ];
+
+        if self.variant == Variant::ThreeCheck {
+            let remaining =
+                |color: Color| WINNING_CHECKS.saturating_sub(self.checks[color as usize]);
+            fields.push(format!(
+                "{}+{}",
+                remaining(Color::White),
+                remaining(Color::Black)
+            ));
         }
-        let board = Board::from_fen(elements[0]).unwrap();
-        println!("{:#?}", board);
 
-        Ok(Self { board })
+        fields.push(self.halfmove_clock.to_string());
+        fields.push(self.fullmove_counter.to_string());
+        fields.join(" ")
     }
 }
@@ -0,0 +1,229 @@
+//! Move generation for a game state
+use super::{
+    color::Color,
+    coordinate::Coordinate,
+    game::GameState,
+    moves::Move,
+    piece::{ColoredPiece, Piece},
+    variant::Variant,
+};
+
+/// The pieces a pawn can promote to, strongest first
+pub const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+impl GameState {
+    /// Generates all moves of the side to move that obey the piece movement rules,
+    /// but may leave the own king in check
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+        let color = self.side_to_move;
+        let last_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+
+        for (from, piece) in self.board.get_occupied_fields_rm() {
+            if piece.color != color {
+                continue;
+            }
+
+            let mut targets = piece.get_all_moves(&self.board, from);
+            if self.variant == Variant::Horde {
+                self.horde_double_push(from, piece, &mut targets);
+            }
+
+            for to in targets {
+                // Kings cannot capture in Atomic, they would explode themselves
+                if self.variant == Variant::Atomic
+                    && piece.piece == Piece::King
+                    && self.board.get(to).is_some()
+                {
+                    continue;
+                }
+
+                if piece.piece == Piece::Pawn && to.rank as u8 == last_rank {
+                    // Kings are ordinary pieces a pawn can promote to in Antichess
+                    let king = (self.variant == Variant::Antichess).then_some(Piece::King);
+                    for promotion in PROMOTION_PIECES.into_iter().chain(king) {
+                        moves.push(Move {
+                            from,
+                            to,
+                            promotion: Some(promotion),
+                            drop: None,
+                        });
+                    }
+                } else {
+                    moves.push(Move::new(from, to));
+                }
+            }
+
+            if piece.piece == Piece::Pawn {
+                if let Some(en_passant) = self.en_passant {
+                    let forward = match color {
+                        Color::White => 1,
+                        Color::Black => -1,
+                    };
+                    if [-1, 1]
+                        .iter()
+                        .any(|f| from.offset(*f, forward) == Some(en_passant))
+                    {
+                        moves.push(Move::new(from, en_passant));
+                    }
+                }
+            }
+
+            if piece.piece == Piece::King && self.variant != Variant::Antichess {
+                self.castling_moves(from, &mut moves);
+            }
+        }
+
+        if self.variant == Variant::Crazyhouse {
+            self.drop_moves(&mut moves);
+        }
+
+        moves
+    }
+
+    /// Generates all legal moves of the side to move, none if a rule of the variant ended the game
+    pub fn legal_moves(&self) -> Vec<Move> {
+        // An Antichess game ends exactly when there are no moves, no need to generate them twice
+        if self.variant != Variant::Antichess && self.variant_outcome().is_some() {
+            return Vec::new();
+        }
+
+        let moves: Vec<Move> = self
+            .pseudo_legal_moves()
+            .into_iter()
+            .filter(|mv| self.is_legal_pseudo(*mv))
+            .collect();
+
+        // Captures are compulsory in Antichess
+        if self.variant == Variant::Antichess && moves.iter().any(|mv| self.is_capture(*mv)) {
+            return moves
+                .into_iter()
+                .filter(|mv| self.is_capture(*mv))
+                .collect();
+        }
+
+        moves
+    }
+
+    /// Checks whether `mv` is among the legal moves of the side to move
+    /// # Arguments
+    /// * `mv` - The move to check
+    pub fn is_legal(&self, mv: Move) -> bool {
+        // Whether a move is legal in Antichess depends on all other moves
+        if self.variant == Variant::Antichess {
+            return self.legal_moves().contains(&mv);
+        }
+
+        self.pseudo_legal_moves().contains(&mv) && self.is_legal_pseudo(mv)
+    }
+
+    /// Checks whether the pseudo-legal `mv` leaves the own king safe,
+    /// in Racing Kings it must not give check either
+    pub(super) fn is_legal_pseudo(&self, mv: Move) -> bool {
+        // Kings are not royal in Antichess
+        if self.variant == Variant::Antichess {
+            return true;
+        }
+
+        let next = self.make_move(mv);
+        let Some(king) = next.board.find_king(self.side_to_move) else {
+            // The white horde has no king to protect
+            return self.variant == Variant::Horde && self.side_to_move == Color::White;
+        };
+
+        // Exploding the enemy king wins at once, even when in check
+        if self.variant == Variant::Atomic && next.board.find_king(next.side_to_move).is_none() {
+            return true;
+        }
+
+        if self.variant == Variant::RacingKings && next.is_check() {
+            return false;
+        }
+
+        !next.is_square_attacked(king, next.side_to_move)
+    }
+
+    /// Adds the double push of a pawn on its first rank to `targets` in Horde
+    fn horde_double_push(
+        &self,
+        from: Coordinate,
+        pawn: ColoredPiece,
+        targets: &mut Vec<Coordinate>,
+    ) {
+        let (first_rank, direction) = match pawn.color {
+            Color::White => (0, 1),
+            Color::Black => (7, -1),
+        };
+        if pawn.piece != Piece::Pawn || from.rank as u8 != first_rank {
+            return;
+        }
+
+        if let (Some(single), Some(double)) =
+            (from.offset(0, direction), from.offset(0, 2 * direction))
+        {
+            if self.board.get(single).is_none() && self.board.get(double).is_none() {
+                targets.push(double);
+            }
+        }
+    }
+
+    /// Adds the castling moves of the king on `king` to `moves`.
+    ///
+    /// The king and rook may start on any file as in Chess960, but they have to
+    /// reach their standard squares over empty squares without the king
+    /// passing through check
+    fn castling_moves(&self, king: Coordinate, moves: &mut Vec<Move>) {
+        let color = self.side_to_move;
+        let home_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+
+        if king.rank as u8 != home_rank {
+            return;
+        }
+
+        let enemy = color.opposite();
+        let square = |file: u8| {
+            Coordinate::from_u8s(file, home_rank).expect("[DEV] Internal File and Rank mishap")
+        };
+        let span = |a: u8, b: u8| a.min(b)..=a.max(b);
+        let rook = ColoredPiece {
+            piece: Piece::Rook,
+            color,
+        };
+
+        for kingside in [true, false] {
+            let allowed = if kingside {
+                self.castling.kingside(color)
+            } else {
+                self.castling.queenside(color)
+            };
+            let king_from = king.file as u8;
+            let rook_from = self.castling.rook_file(color, kingside) as u8;
+
+            if !allowed
+                || self.board.get(square(rook_from)) != Some(rook)
+                || (rook_from > king_from) != kingside
+            {
+                continue;
+            }
+
+            let (king_to, rook_to) = if kingside { (6, 5) } else { (2, 3) };
+
+            let is_empty = span(king_from, king_to)
+                .chain(span(rook_from, rook_to))
+                .all(|f| f == king_from || f == rook_from || self.board.get(square(f)).is_none());
+            let is_safe =
+                span(king_from, king_to).all(|f| !self.is_square_attacked(square(f), enemy));
+
+            if is_empty && is_safe {
+                let to = if self.chess960 { rook_from } else { king_to };
+                moves.push(Move::new(king, square(to)));
+            }
+        }
+    }
+}
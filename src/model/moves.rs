@@ -0,0 +1,117 @@
+//! Moves on the chess board
+use std::fmt::Display;
+
+use crate::fen::{FromFENChar, FromFENString, ToFENChar, ToFENString};
+
+use super::{coordinate::Coordinate, piece::Piece};
+
+/// A move of a piece from one square to another
+///
+/// Castling is expressed as the king moving two files towards the rook,
+/// or as the king capturing its own rook in Chess960.
+/// Drops of pieces from the pocket in Crazyhouse start and end on the same square
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    /// The square the piece moves from
+    pub from: Coordinate,
+    /// The square the piece moves to
+    pub to: Coordinate,
+    /// The piece a pawn gets promoted to
+    pub promotion: Option<Piece>,
+    /// The piece placed on `to` from the pocket
+    pub drop: Option<Piece>,
+}
+
+impl Move {
+    /// Creates a new move without promotion
+    /// # Arguments
+    /// * `from` - The square the piece moves from
+    /// * `to` - The square the piece moves to
+    pub fn new(from: Coordinate, to: Coordinate) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+            drop: None,
+        }
+    }
+
+    /// Creates a drop of `piece` from the pocket onto `to`
+    /// # Arguments
+    /// * `piece` - The piece to drop
+    /// * `to` - The square the piece is placed on
+    pub fn new_drop(piece: Piece, to: Coordinate) -> Self {
+        Self {
+            from: to,
+            to,
+            promotion: None,
+            drop: Some(piece),
+        }
+    }
+
+    /// Parses a move in long algebraic (UCI) notation, e.g. `e2e4`, `e7e8q` or the drop `N@f3`
+    /// # Arguments
+    /// * `string` - The string to parse
+    pub fn from_uci(string: &str) -> Option<Self> {
+        if !(4..=5).contains(&string.len()) || !string.is_ascii() {
+            return None;
+        }
+
+        if let Some((piece, square)) = string.split_once('@') {
+            let piece = match piece.chars().collect::<Vec<char>>().as_slice() {
+                [c] => Piece::from_fen(*c).ok()?,
+                _ => return None,
+            };
+            if piece == Piece::King {
+                return None;
+            }
+            let to = Coordinate::from_fen(square).ok()?;
+            return Some(Self::new_drop(piece, to));
+        }
+
+        let from = Coordinate::from_u8s(
+            string.as_bytes()[0].wrapping_sub(b'a'),
+            string.as_bytes()[1].wrapping_sub(b'1'),
+        )?;
+        let to = Coordinate::from_u8s(
+            string.as_bytes()[2].wrapping_sub(b'a'),
+            string.as_bytes()[3].wrapping_sub(b'1'),
+        )?;
+        let promotion = match string.chars().nth(4) {
+            Some(c) if c.is_ascii_lowercase() => match Piece::from_fen(c).ok()? {
+                Piece::Pawn => return None,
+                piece => Some(piece),
+            },
+            Some(_) => return None,
+            None => None,
+        };
+
+        Some(Self {
+            from,
+            to,
+            promotion,
+            drop: None,
+        })
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(piece) = self.drop {
+            return write!(
+                f,
+                "{}@{}",
+                piece.to_fen().to_ascii_uppercase(),
+                self.to.to_fen()
+            );
+        }
+
+        write!(f, "{}{}", self.from.to_fen(), self.to.to_fen())?;
+
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.to_fen())?;
+        }
+
+        Ok(())
+    }
+}
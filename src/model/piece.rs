@@ -6,7 +6,7 @@ use super::{board::Board, color::Color, coordinate::Coordinate};
 use std::fmt::{Debug, Display};
 
 /// A chess piece that is in some color
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ColoredPiece {
     /// The piece
     pub piece: Piece,
@@ -21,78 +21,136 @@ impl Display for ColoredPiece {
 }
 
 impl ColoredPiece {
+    /// Returns all squares this piece could move to from `starting_square`
+    /// without taking the rest of the game state (castling, en passant, pins) into account
+    /// # Arguments
+    /// * `board` - The board the piece is on
+    /// * `starting_square` - The square the piece stands on
     pub fn get_all_moves(&self, board: &Board, starting_square: Coordinate) -> Vec<Coordinate> {
+        let mut targets = Vec::new();
+
         match self.piece {
-            Piece::Pawn => Vec::new(),
-            Piece::Rook => {
-                let mut targets = Vec::new();
-
-                // Files positive
-                let mut start = starting_square;
-                while let Some(coordinate) = start.next_file() {
-                    start = coordinate;
-                    if let Some(piece) = board.get(coordinate) {
-                        if piece.color != self.color {
-                            targets.push(coordinate);
+            Piece::Pawn => {
+                let (direction, start_rank) = match self.color {
+                    Color::White => (1, 1),
+                    Color::Black => (-1, 6),
+                };
+
+                // Pushes are only possible onto empty squares
+                if let Some(single) = starting_square.offset(0, direction) {
+                    if board.get(single).is_none() {
+                        targets.push(single);
+
+                        if starting_square.rank as i8 == start_rank {
+                            if let Some(double) = single.offset(0, direction) {
+                                if board.get(double).is_none() {
+                                    targets.push(double);
+                                }
+                            }
                         }
-                        break;
-                    } else {
-                        targets.push(coordinate)
                     }
                 }
 
-                // Files negative
-                let mut start = starting_square;
-                while let Some(coordinate) = start.prev_file() {
-                    start = coordinate;
-                    if let Some(piece) = board.get(coordinate) {
-                        if piece.color != self.color {
+                // Captures are only possible onto enemy pieces
+                for file_offset in [-1, 1] {
+                    if let Some(coordinate) = starting_square.offset(file_offset, direction) {
+                        if board
+                            .get(coordinate)
+                            .is_some_and(|piece| piece.color != self.color)
+                        {
                             targets.push(coordinate);
                         }
-                        break;
-                    } else {
-                        targets.push(coordinate)
                     }
                 }
+            }
+            Piece::Rook => self.slide(board, starting_square, &ROOK_DIRECTIONS, &mut targets),
+            Piece::Knight => self.step(board, starting_square, &KNIGHT_OFFSETS, &mut targets),
+            Piece::Bishop => self.slide(board, starting_square, &BISHOP_DIRECTIONS, &mut targets),
+            Piece::Queen => {
+                self.slide(board, starting_square, &ROOK_DIRECTIONS, &mut targets);
+                self.slide(board, starting_square, &BISHOP_DIRECTIONS, &mut targets);
+            }
+            Piece::King => self.step(board, starting_square, &KING_OFFSETS, &mut targets),
+        }
 
-                // Ranks positive
-                let mut start = starting_square;
-                while let Some(coordinate) = start.next_rank() {
-                    start = coordinate;
-                    if let Some(piece) = board.get(coordinate) {
-                        if piece.color != self.color {
-                            targets.push(coordinate);
-                        }
-                        break;
-                    } else {
-                        targets.push(coordinate)
-                    }
-                }
+        targets
+    }
 
-                // Ranks negative
-                let mut start = starting_square;
-                while let Some(coordinate) = start.prev_rank() {
-                    start = coordinate;
-                    if let Some(piece) = board.get(coordinate) {
-                        if piece.color != self.color {
-                            targets.push(coordinate);
-                        }
-                        break;
-                    } else {
-                        targets.push(coordinate)
+    /// Walks along `directions` until a piece or the end of the board is hit
+    fn slide(
+        &self,
+        board: &Board,
+        starting_square: Coordinate,
+        directions: &[(i8, i8)],
+        targets: &mut Vec<Coordinate>,
+    ) {
+        for (file_offset, rank_offset) in directions {
+            let mut start = starting_square;
+            while let Some(coordinate) = start.offset(*file_offset, *rank_offset) {
+                start = coordinate;
+                if let Some(piece) = board.get(coordinate) {
+                    if piece.color != self.color {
+                        targets.push(coordinate);
                     }
+                    break;
+                } else {
+                    targets.push(coordinate)
                 }
+            }
+        }
+    }
 
-                targets
+    /// Jumps to every square in `offsets` that is not occupied by an own piece
+    fn step(
+        &self,
+        board: &Board,
+        starting_square: Coordinate,
+        offsets: &[(i8, i8)],
+        targets: &mut Vec<Coordinate>,
+    ) {
+        for (file_offset, rank_offset) in offsets {
+            if let Some(coordinate) = starting_square.offset(*file_offset, *rank_offset) {
+                if board
+                    .get(coordinate)
+                    .is_none_or(|piece| piece.color != self.color)
+                {
+                    targets.push(coordinate);
+                }
             }
-            Piece::Knight => Vec::new(),
-            Piece::Bishop => Vec::new(),
-            Piece::Queen => Vec::new(),
-            Piece::King => Vec::new(),
         }
     }
 }
 
+/// The directions a rook slides in as `(file, rank)` offsets
+pub const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// The directions a bishop slides in as `(file, rank)` offsets
+pub const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// The squares a knight can jump to as `(file, rank)` offsets
+pub const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+/// The squares a king can step to as `(file, rank)` offsets
+pub const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
 impl FromFENChar for ColoredPiece {
     fn from_fen(fen: char) -> Result<Self, FromFENError>
     where
@@ -116,7 +174,7 @@ impl ToFENChar for ColoredPiece {
 }
 
 /// A chess piece
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Piece {
     Pawn,
     Rook,
@@ -126,6 +184,18 @@ pub enum Piece {
     King,
 }
 
+impl Piece {
+    /// All pieces, in the order of their discriminants
+    pub const ALL: [Piece; 6] = [
+        Piece::Pawn,
+        Piece::Rook,
+        Piece::Knight,
+        Piece::Bishop,
+        Piece::Queen,
+        Piece::King,
+    ];
+}
+
 impl Display for Piece {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
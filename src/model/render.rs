@@ -0,0 +1,202 @@
+//! Rendering the board as text for terminals
+use std::fmt::Display;
+
+use colored::Colorize;
+
+use crate::fen::ToFENChar;
+
+use super::{
+    board::Board,
+    color::Color,
+    coordinate::Coordinate,
+    game::GameState,
+    moves::Move,
+    piece::{ColoredPiece, Piece},
+    square::{Square, SquareColor},
+};
+
+/// How pieces are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PieceStyle {
+    /// FEN letters, uppercase for white and lowercase for black
+    #[default]
+    Ascii,
+    /// Unicode chess figurines
+    Unicode,
+}
+
+/// The options for rendering a board
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    /// How pieces are drawn
+    pub style: PieceStyle,
+    /// Whether to color the squares using ANSI escape codes
+    pub colors: bool,
+    /// Whether to show the rank and file labels
+    pub labels: bool,
+    /// Whether to show the board from black's side
+    pub flipped: bool,
+    /// The move whose squares are highlighted
+    pub highlight: Option<Move>,
+    /// The square of a king in check to highlight
+    pub check: Option<Coordinate>,
+}
+
+/// The background colors of light and dark squares, in the normal and highlighted state
+const LIGHT: (u8, u8, u8) = (240, 217, 181);
+const DARK: (u8, u8, u8) = (181, 136, 99);
+const LIGHT_HIGHLIGHT: (u8, u8, u8) = (205, 210, 106);
+const DARK_HIGHLIGHT: (u8, u8, u8) = (170, 162, 58);
+const CHECK: (u8, u8, u8) = (220, 60, 50);
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            style: PieceStyle::Ascii,
+            colors: false,
+            labels: true,
+            flipped: false,
+            highlight: None,
+            check: None,
+        }
+    }
+}
+
+impl PieceStyle {
+    /// Returns the symbol of `piece`
+    pub fn symbol(&self, piece: ColoredPiece) -> char {
+        match self {
+            Self::Ascii => piece.to_fen(),
+            Self::Unicode => match (piece.color, piece.piece) {
+                (Color::White, Piece::King) => '♔',
+                (Color::White, Piece::Queen) => '♕',
+                (Color::White, Piece::Rook) => '♖',
+                (Color::White, Piece::Bishop) => '♗',
+                (Color::White, Piece::Knight) => '♘',
+                (Color::White, Piece::Pawn) => '♙',
+                (Color::Black, Piece::King) => '♚',
+                (Color::Black, Piece::Queen) => '♛',
+                (Color::Black, Piece::Rook) => '♜',
+                (Color::Black, Piece::Bishop) => '♝',
+                (Color::Black, Piece::Knight) => '♞',
+                (Color::Black, Piece::Pawn) => '♟',
+            },
+        }
+    }
+}
+
+impl Board {
+    /// Renders the board as text, one line per rank.
+    ///
+    /// Without colors, highlighted squares are marked with `[ ]`
+    /// and the checked king with `( )`
+    /// # Arguments
+    /// * `options` - How to render the board
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let ranks: Vec<u8> = if options.flipped {
+            (0..8).collect()
+        } else {
+            (0..8).rev().collect()
+        };
+        let files: Vec<u8> = if options.flipped {
+            (0..8).rev().collect()
+        } else {
+            (0..8).collect()
+        };
+
+        let mut lines = Vec::new();
+        for rank in &ranks {
+            let mut line = String::new();
+            if options.labels {
+                line.push_str(&format!(" {} ", rank + 1));
+            }
+
+            for file in &files {
+                let coordinate = Coordinate::from_u8s(*file, *rank)
+                    .expect("[DEV] Internal File and Rank mishap");
+                line.push_str(&self.render_square(coordinate, options));
+            }
+            lines.push(line);
+        }
+
+        if options.labels {
+            let labels: String = files
+                .iter()
+                .map(|file| format!(" {} ", (b'a' + file) as char))
+                .collect();
+            lines.push(format!("   {labels}"));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Renders a single square, three characters wide
+    fn render_square(&self, coordinate: Coordinate, options: &RenderOptions) -> String {
+        let piece = self.get(coordinate);
+        let highlighted = options
+            .highlight
+            .is_some_and(|mv| mv.from == coordinate || mv.to == coordinate);
+        let checked = options.check == Some(coordinate);
+
+        if !options.colors {
+            let symbol = piece.map_or('.', |p| options.style.symbol(p));
+            return if checked {
+                format!("({symbol})")
+            } else if highlighted {
+                format!("[{symbol}]")
+            } else {
+                format!(" {symbol} ")
+            };
+        }
+
+        let symbol = match piece {
+            Some(piece) => {
+                let text = format!(" {} ", options.style.symbol(piece));
+                match piece.color {
+                    Color::White => text.bright_white().bold(),
+                    Color::Black => text.black().bold(),
+                }
+            }
+            None => "   ".normal(),
+        };
+
+        let light = Square::from(coordinate).color() == SquareColor::Light;
+        let (r, g, b) = match (checked, highlighted, light) {
+            (true, _, _) => CHECK,
+            (false, true, true) => LIGHT_HIGHLIGHT,
+            (false, true, false) => DARK_HIGHLIGHT,
+            (false, false, true) => LIGHT,
+            (false, false, false) => DARK,
+        };
+
+        symbol.on_truecolor(r, g, b).to_string()
+    }
+}
+
+impl GameState {
+    /// Renders the board as text, highlighting the king of the side to move if it is in check
+    /// # Arguments
+    /// * `options` - How to render the board
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let mut options = *options;
+
+        if options.check.is_none() && self.is_check() {
+            options.check = self.board.find_king(self.side_to_move);
+        }
+
+        self.board.render(&options)
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+impl Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.render(&RenderOptions::default()))?;
+        write!(f, "{} to move", self.side_to_move)
+    }
+}
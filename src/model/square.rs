@@ -0,0 +1,353 @@
+//! Squares as a compact index and the geometry of the board
+//!
+//! A [Square] packs a [Coordinate] into a single byte, counting from `A1` (`0`)
+//! along the ranks to `H8` (`63`). Sets of squares are [u64] masks with the bit
+//! of each square's index set, like the ones returned by [Square::between()].
+use std::{fmt::Display, str::FromStr};
+
+use crate::fen::{FromFENError, FromFENString};
+
+use super::coordinate::{Coordinate, File, Rank};
+
+/// A square of the board, `rank * 8 + file` from `A1` (`0`) to `H8` (`63`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+/// The eight directions a piece can slide in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards rank 8
+    North,
+    /// Towards `H8`
+    NorthEast,
+    /// Towards the `H` file
+    East,
+    /// Towards `H1`
+    SouthEast,
+    /// Towards rank 1
+    South,
+    /// Towards `A1`
+    SouthWest,
+    /// Towards the `A` file
+    West,
+    /// Towards `A8`
+    NorthWest,
+}
+
+/// The color of a square, `A1` is dark
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
+impl Direction {
+    /// All directions, clockwise starting from north
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Returns the file and rank a single step in this direction moves by
+    pub const fn offsets(self) -> (i8, i8) {
+        match self {
+            Self::North => (0, 1),
+            Self::NorthEast => (1, 1),
+            Self::East => (1, 0),
+            Self::SouthEast => (1, -1),
+            Self::South => (0, -1),
+            Self::SouthWest => (-1, -1),
+            Self::West => (-1, 0),
+            Self::NorthWest => (-1, 1),
+        }
+    }
+
+    /// Returns the direction pointing the other way
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::NorthEast => Self::SouthWest,
+            Self::East => Self::West,
+            Self::SouthEast => Self::NorthWest,
+            Self::South => Self::North,
+            Self::SouthWest => Self::NorthEast,
+            Self::West => Self::East,
+            Self::NorthWest => Self::SouthEast,
+        }
+    }
+
+    /// Returns whether pieces move along files and ranks in this direction, like a rook
+    pub fn is_straight(self) -> bool {
+        matches!(self, Self::North | Self::East | Self::South | Self::West)
+    }
+
+    /// Returns whether pieces move along diagonals in this direction, like a bishop
+    pub fn is_diagonal(self) -> bool {
+        !self.is_straight()
+    }
+}
+
+impl Square {
+    /// The number of squares on the board
+    pub const COUNT: usize = 64;
+
+    pub const A1: Square = Square(0);
+    pub const H1: Square = Square(7);
+    pub const A8: Square = Square(56);
+    pub const H8: Square = Square(63);
+
+    /// Creates a square from its index
+    /// # Arguments
+    /// * `index` - The index from `0` (`A1`) to `63` (`H8`)
+    pub const fn new(index: u8) -> Option<Square> {
+        if index < 64 {
+            Some(Square(index))
+        } else {
+            None
+        }
+    }
+
+    /// Creates the square on `file` and `rank`
+    /// # Arguments
+    /// * `file` - The file of the square
+    /// * `rank` - The rank of the square
+    pub const fn from_file_rank(file: File, rank: Rank) -> Square {
+        Square(rank as u8 * 8 + file as u8)
+    }
+
+    /// Returns the index of the square from `0` (`A1`) to `63` (`H8`)
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the mask with only this square's bit set
+    pub const fn bit(self) -> u64 {
+        1 << self.0
+    }
+
+    /// Returns the file of the square
+    pub fn file(self) -> File {
+        File::from_u8(self.0 % 8).expect("[DEV] Internal File and Rank mishap")
+    }
+
+    /// Returns the rank of the square
+    pub fn rank(self) -> Rank {
+        Rank::from_u8(self.0 / 8).expect("[DEV] Internal File and Rank mishap")
+    }
+
+    /// Iterates over all squares from `A1` to `H8`, rank by rank
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+
+    /// Iterates over the squares of `rank` from the `A` to the `H` file
+    pub fn on_rank(rank: Rank) -> impl Iterator<Item = Square> {
+        File::ALL
+            .into_iter()
+            .map(move |file| Square::from_file_rank(file, rank))
+    }
+
+    /// Iterates over the squares of `file` from rank 1 to rank 8
+    pub fn on_file(file: File) -> impl Iterator<Item = Square> {
+        Rank::ALL
+            .into_iter()
+            .map(move |rank| Square::from_file_rank(file, rank))
+    }
+
+    /// Iterates over the squares whose bit is set in `mask`, in index order
+    /// # Arguments
+    /// * `mask` - The set of squares
+    pub fn in_mask(mask: u64) -> impl Iterator<Item = Square> {
+        Square::all().filter(move |square| mask & square.bit() != 0)
+    }
+
+    /// Returns the square that is `file_offset` files and
+    /// `rank_offset` ranks away if it is still on the board
+    /// # Arguments
+    /// * `file_offset` - The files to move (positive towards `H`)
+    /// * `rank_offset` - The ranks to move (positive towards `8`)
+    pub const fn offset(self, file_offset: i8, rank_offset: i8) -> Option<Square> {
+        let file = (self.0 % 8) as i8 + file_offset;
+        let rank = (self.0 / 8) as i8 + rank_offset;
+
+        if file < 0 || file > 7 || rank < 0 || rank > 7 {
+            return None;
+        }
+
+        Some(Square(rank as u8 * 8 + file as u8))
+    }
+
+    /// Returns the neighbouring square in `direction` if it is on the board
+    /// # Arguments
+    /// * `direction` - The direction to step in
+    pub const fn step(self, direction: Direction) -> Option<Square> {
+        let (file, rank) = direction.offsets();
+        self.offset(file, rank)
+    }
+
+    /// Iterates over the squares in `direction` up to the edge of the board, excluding this one
+    /// # Arguments
+    /// * `direction` - The direction to walk in
+    pub fn ray(self, direction: Direction) -> impl Iterator<Item = Square> {
+        std::iter::successors(self.step(direction), move |square| square.step(direction))
+    }
+
+    /// Returns the direction to walk from this square to reach `other`,
+    /// if they share a file, rank or diagonal
+    /// # Arguments
+    /// * `other` - The square to reach
+    pub fn direction_to(self, other: Square) -> Option<Direction> {
+        let file = other.file() as i8 - self.file() as i8;
+        let rank = other.rank() as i8 - self.rank() as i8;
+
+        if self == other || (file != 0 && rank != 0 && file.abs() != rank.abs()) {
+            return None;
+        }
+
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.offsets() == (file.signum(), rank.signum()))
+    }
+
+    /// Returns the number of king moves between the two squares
+    /// # Arguments
+    /// * `other` - The other square
+    pub fn chebyshev_distance(self, other: Square) -> u8 {
+        let (file, rank) = self.distances(other);
+        file.max(rank)
+    }
+
+    /// Returns the number of rook steps between the two squares
+    /// # Arguments
+    /// * `other` - The other square
+    pub fn manhattan_distance(self, other: Square) -> u8 {
+        let (file, rank) = self.distances(other);
+        file + rank
+    }
+
+    /// Returns the number of files and ranks between the two squares
+    fn distances(self, other: Square) -> (u8, u8) {
+        (
+            (self.0 % 8).abs_diff(other.0 % 8),
+            (self.0 / 8).abs_diff(other.0 / 8),
+        )
+    }
+
+    /// Returns whether the square is light or dark
+    pub fn color(self) -> SquareColor {
+        if (self.0 % 8 + self.0 / 8).is_multiple_of(2) {
+            SquareColor::Dark
+        } else {
+            SquareColor::Light
+        }
+    }
+
+    /// Returns the squares strictly between the two squares if they share
+    /// a file, rank or diagonal, otherwise an empty mask
+    /// # Arguments
+    /// * `other` - The other square
+    pub fn between(self, other: Square) -> u64 {
+        BETWEEN[self.index()][other.index()]
+    }
+
+    /// Returns the whole file, rank or diagonal through both squares,
+    /// including them, or an empty mask if they share none
+    /// # Arguments
+    /// * `other` - The other square
+    pub fn line(self, other: Square) -> u64 {
+        LINE[self.index()][other.index()]
+    }
+}
+
+/// The squares between two squares, indexed by both squares
+static BETWEEN: [[u64; 64]; 64] = generate_lines(false);
+
+/// The lines through two squares, indexed by both squares
+static LINE: [[u64; 64]; 64] = generate_lines(true);
+
+/// Generates the [BETWEEN] table or, with `full`, the [LINE] table by
+/// walking all eight directions from every square
+const fn generate_lines(full: bool) -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+
+    let mut from = 0;
+    while from < 64 {
+        let mut d = 0;
+        while d < 8 {
+            let direction = Direction::ALL[d];
+            let mut passed = 0u64;
+            let mut current = Square(from as u8).step(direction);
+
+            while let Some(to) = current {
+                table[from][to.index()] = if full {
+                    Square(from as u8).bit()
+                        | walk(Square(from as u8), direction)
+                        | walk(Square(from as u8), direction.opposite())
+                } else {
+                    passed
+                };
+                passed |= to.bit();
+                current = to.step(direction);
+            }
+            d += 1;
+        }
+        from += 1;
+    }
+
+    table
+}
+
+/// Returns the squares in `direction` from `square` up to the edge, usable in const context
+const fn walk(square: Square, direction: Direction) -> u64 {
+    let mut mask = 0;
+    let mut current = square.step(direction);
+
+    while let Some(next) = current {
+        mask |= next.bit();
+        current = next.step(direction);
+    }
+
+    mask
+}
+
+impl From<Coordinate> for Square {
+    fn from(value: Coordinate) -> Self {
+        Square::from_file_rank(value.file, value.rank)
+    }
+}
+
+impl From<Square> for Coordinate {
+    fn from(value: Square) -> Self {
+        Coordinate {
+            file: value.file(),
+            rank: value.rank(),
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = FromFENError;
+
+    /// Parses a square in lowercase algebraic notation (`e4`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Coordinate::from_fen(s).map(Square::from)
+    }
+}
+
+impl Display for Square {
+    /// Writes the square in lowercase algebraic notation (`e4`)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            (b'a' + self.0 % 8) as char,
+            (b'1' + self.0 / 8) as char
+        )
+    }
+}
@@ -0,0 +1,264 @@
+//! SVG diagrams of positions
+//!
+//! The pieces are drawn with built-in vector glyphs, so the diagrams do not
+//! depend on fonts or other files. Moves can be marked with arrows and
+//! squares can be highlighted.
+use std::fmt::Write;
+
+use super::{
+    board::Board,
+    color::Color,
+    coordinate::Coordinate,
+    game::GameState,
+    moves::Move,
+    piece::{ColoredPiece, Piece},
+    square::{Square, SquareColor},
+};
+
+/// The size of the glyphs, they are scaled to the square size
+const GLYPH_SIZE: f64 = 45.0;
+
+const LIGHT: &str = "#f0d9b5";
+const DARK: &str = "#b58863";
+const LAST_MOVE: &str = "#cdd26a";
+const HIGHLIGHT: &str = "#3c9bd9";
+const CHECK: &str = "#dc3c32";
+const ARROW: &str = "#15781b";
+
+/// The options for drawing a diagram
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// The size of a square in pixels
+    pub square_size: u32,
+    /// Whether to draw the rank and file labels around the board
+    pub coordinates: bool,
+    /// Whether to show the board from black's side
+    pub flipped: bool,
+    /// The move that led to the position, its squares are marked
+    pub last_move: Option<Move>,
+    /// The moves to draw arrows for
+    pub arrows: Vec<Move>,
+    /// The squares to highlight
+    pub highlights: Vec<Coordinate>,
+    /// The square of a king in check to mark
+    pub check: Option<Coordinate>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 45,
+            coordinates: true,
+            flipped: false,
+            last_move: None,
+            arrows: Vec::new(),
+            highlights: Vec::new(),
+            check: None,
+        }
+    }
+}
+
+/// The glyph of a piece type, drawn in a 45x45 box.
+///
+/// Parts with the `detail` class are drawn in the contrasting color
+fn glyph(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => {
+            r#"<path d="M22.5 9a4 4 0 0 0-3.2 6.4 6.5 6.5 0 0 0-2.3 5 6.5 6.5 0 0 0 2.7 5.3C15.7 27.3 13 31.2 13 36h19c0-4.8-2.7-8.7-6.7-10.3a6.5 6.5 0 0 0 2.7-5.3 6.5 6.5 0 0 0-2.3-5A4 4 0 0 0 22.5 9z"/><path d="M11 36h23v3H11z"/>"#
+        }
+        Piece::Rook => {
+            r#"<path d="M11 36h23v3H11z"/><path d="M13 36l1.5-4h16l1.5 4z"/><path d="M15.5 32V17h14v15z"/><path d="M14 17l-1-6h4v2.5h3V11h5v2.5h3V11h4l-1 6z"/>"#
+        }
+        Piece::Knight => {
+            r#"<path d="M11 36h23v3H11z"/><path d="M14 36c0-7 3-10 6-13-2 0-4 1-5 2l-3 1c-2 0-3-1-2-3l3-6c1-3 4-6 8-7l3-3 1 3 3-1c-1 3-1 6 1 9 3 5 4 11 4 18z"/><circle cx="19" cy="15" r="1.2" class="detail"/>"#
+        }
+        Piece::Bishop => {
+            r#"<path d="M11 36h23v3H11z"/><path d="M15 33c0-5 2-9 4-11-3-2-4-5-4-8 0-4 4-7 7.5-9 3.5 2 7.5 5 7.5 9 0 3-1 6-4 8 2 2 4 6 4 11z"/><circle cx="22.5" cy="4.5" r="2"/><path d="M22.5 10v7M19 13.5h7" class="detail"/>"#
+        }
+        Piece::Queen => {
+            r#"<path d="M11 36h23v3H11z"/><path d="M12 33L9 15l3.5 9 3-12 3.5 10 3.5-12 3.5 12 3.5-10 3 12 3.5-9-3 18z"/><circle cx="9" cy="13" r="2"/><circle cx="15.5" cy="10" r="2"/><circle cx="22.5" cy="8" r="2"/><circle cx="29.5" cy="10" r="2"/><circle cx="36" cy="13" r="2"/>"#
+        }
+        Piece::King => {
+            r#"<path d="M22.5 6v10M18.5 10h8" fill="none"/><path d="M11 36h23v3H11z"/><path d="M13 33c-3-5-4-10 0-13 3-2 6-1 9.5 3 3.5-4 6.5-5 9.5-3 4 3 3 8 0 13z"/>"#
+        }
+    }
+}
+
+/// The id of the definition of `piece`
+fn glyph_id(piece: ColoredPiece) -> String {
+    let color = match piece.color {
+        Color::White => "white",
+        Color::Black => "black",
+    };
+
+    format!("{color}-{}", piece.piece.to_string().to_lowercase())
+}
+
+impl Board {
+    /// Draws the board as an SVG image
+    /// # Arguments
+    /// * `options` - What to draw
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.square_size as f64;
+        let margin = if options.coordinates { size / 2.0 } else { 0.0 };
+        let total = size * 8.0 + margin * 2.0;
+
+        // The top left corner of a square
+        let position = |c: Coordinate| {
+            let (column, row) = if options.flipped {
+                (7 - c.file as u8, c.rank as u8)
+            } else {
+                (c.file as u8, 7 - c.rank as u8)
+            };
+            (margin + column as f64 * size, margin + row as f64 * size)
+        };
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 {total} {total}" width="{total}" height="{total}">"#
+        );
+
+        svg.push_str("<defs>\n");
+        for color in [Color::White, Color::Black] {
+            let (fill, detail) = match color {
+                Color::White => ("#fff", "#000"),
+                Color::Black => ("#000", "#fff"),
+            };
+            for piece in Piece::ALL {
+                let id = glyph_id(ColoredPiece { piece, color });
+                let glyph = glyph(piece).replace(
+                    r#"class="detail""#,
+                    &format!(r#"fill="{detail}" stroke="{detail}""#),
+                );
+                let _ = writeln!(
+                    svg,
+                    r##"<g id="{id}" fill="{fill}" stroke="#000" stroke-width="1.5" stroke-linecap="round" stroke-linejoin="round">{glyph}</g>"##
+                );
+            }
+        }
+        let _ = writeln!(
+            svg,
+            r#"<marker id="arrowhead" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="3" markerHeight="3" orient="auto"><path d="M0 0L10 5L0 10z" fill="{ARROW}"/></marker>"#
+        );
+        svg.push_str("</defs>\n");
+
+        if options.coordinates {
+            let _ = writeln!(
+                svg,
+                r##"<rect x="0" y="0" width="{total}" height="{total}" fill="#404040"/>"##
+            );
+        }
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let coordinate =
+                    Coordinate::from_u8s(file, rank).expect("[DEV] Internal File and Rank mishap");
+                let (x, y) = position(coordinate);
+                let color = match Square::from(coordinate).color() {
+                    SquareColor::Light => LIGHT,
+                    SquareColor::Dark => DARK,
+                };
+
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{color}"/>"#
+                );
+            }
+        }
+
+        // Square markers, drawn over the squares and below the pieces
+        let mut marks: Vec<(Coordinate, &str)> = Vec::new();
+        if let Some(mv) = options.last_move {
+            marks.push((mv.from, LAST_MOVE));
+            marks.push((mv.to, LAST_MOVE));
+        }
+        marks.extend(options.highlights.iter().map(|c| (*c, HIGHLIGHT)));
+        if let Some(check) = options.check {
+            marks.push((check, CHECK));
+        }
+        for (coordinate, color) in marks {
+            let (x, y) = position(coordinate);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x}" y="{y}" width="{size}" height="{size}" fill="{color}" fill-opacity="0.7"/>"#
+            );
+        }
+
+        let scale = size / GLYPH_SIZE;
+        for (coordinate, piece) in self.get_occupied_fields_fm() {
+            let (x, y) = position(coordinate);
+            let _ = writeln!(
+                svg,
+                r##"<use xlink:href="#{}" href="#{}" transform="translate({x} {y}) scale({scale})"/>"##,
+                glyph_id(piece),
+                glyph_id(piece)
+            );
+        }
+
+        for mv in &options.arrows {
+            let center = |c: Coordinate| {
+                let (x, y) = position(c);
+                (x + size / 2.0, y + size / 2.0)
+            };
+            let (x1, y1) = center(mv.from);
+            let (x2, y2) = center(mv.to);
+
+            // Stop short of the center so the head ends inside the target square
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+            let shorten = size * 0.3;
+            let (x2, y2) = (
+                x2 - (x2 - x1) / length * shorten,
+                y2 - (y2 - y1) / length * shorten,
+            );
+
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{ARROW}" stroke-width="{}" stroke-linecap="round" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+                size * 0.2
+            );
+        }
+
+        if options.coordinates {
+            let font_size = size * 0.3;
+            for i in 0..8u8 {
+                let file = if options.flipped { 7 - i } else { i };
+                let rank = if options.flipped { i } else { 7 - i };
+                let offset = margin + i as f64 * size + size / 2.0;
+
+                for y in [margin / 2.0, total - margin / 2.0] {
+                    let _ = writeln!(
+                        svg,
+                        r##"<text x="{offset}" y="{y}" font-size="{font_size}" font-family="sans-serif" fill="#e0e0e0" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                        (b'a' + file) as char
+                    );
+                }
+                for x in [margin / 2.0, total - margin / 2.0] {
+                    let _ = writeln!(
+                        svg,
+                        r##"<text x="{x}" y="{offset}" font-size="{font_size}" font-family="sans-serif" fill="#e0e0e0" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                        rank + 1
+                    );
+                }
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl GameState {
+    /// Draws the position as an SVG image, marking the king of the side to move if it is in check
+    /// # Arguments
+    /// * `options` - What to draw
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let mut options = options.clone();
+
+        if options.check.is_none() && self.is_check() {
+            options.check = self.board.find_king(self.side_to_move);
+        }
+
+        self.board.to_svg(&options)
+    }
+}
@@ -0,0 +1,118 @@
+//! Symmetry transforms of boards and positions
+//!
+//! Flipping the board vertically and swapping the colors leads to a position
+//! that is just as good for the other side, which is useful to test the
+//! evaluation and to augment training data. Mirroring the board horizontally
+//! keeps the value of a position as long as no one can castle.
+use super::{
+    board::Board, coordinate::Coordinate, game::GameState, piece::ColoredPiece, square::Square,
+};
+
+/// Returns the coordinate on the same file with the rank counted from the other side
+fn flip_coordinate(square: Coordinate) -> Coordinate {
+    Coordinate::from_u8s(square.file as u8, 7 - square.rank as u8)
+        .expect("[DEV] Internal File and Rank mishap")
+}
+
+/// Returns the coordinate on the same rank with the file counted from the other side
+fn mirror_coordinate(square: Coordinate) -> Coordinate {
+    Coordinate::from_u8s(7 - square.file as u8, square.rank as u8)
+        .expect("[DEV] Internal File and Rank mishap")
+}
+
+/// Moves every square in `mask` to `transform(square)`
+fn transform_mask(mask: u64, transform: fn(Coordinate) -> Coordinate) -> u64 {
+    Square::in_mask(mask)
+        .map(|square| Square::from(transform(square.into())).bit())
+        .fold(0, |mask, bit| mask | bit)
+}
+
+impl Board {
+    /// Returns the board with rank 1 and rank 8 swapped, the pieces keep their colors
+    pub fn flip_vertical(&self) -> Board {
+        let mut board = *self;
+        for file in board.squares.iter_mut() {
+            file.reverse();
+        }
+
+        board
+    }
+
+    /// Returns the board with the `A` and `H` files swapped
+    pub fn mirror_horizontal(&self) -> Board {
+        let mut board = *self;
+        board.squares.reverse();
+
+        board
+    }
+
+    /// Returns the board turned by 180 degrees, the pieces keep their colors.
+    /// [GameState::flip_and_mirror()] turns a position and swaps the colors instead
+    pub fn rotate(&self) -> Board {
+        self.flip_vertical().mirror_horizontal()
+    }
+
+    /// Returns the board with all white pieces turned black and vice versa
+    pub fn swap_colors(&self) -> Board {
+        let mut board = *self;
+        for piece in board.squares.iter_mut().flatten().flatten() {
+            *piece = ColoredPiece {
+                piece: piece.piece,
+                color: piece.color.opposite(),
+            };
+        }
+
+        board
+    }
+}
+
+impl GameState {
+    /// Returns the position seen from the other side: the board is flipped
+    /// vertically and the colors of the pieces, the side to move, the castling
+    /// rights, the pockets and the checks given are swapped
+    pub fn flip_colors(&self) -> GameState {
+        let mut state = *self;
+
+        state.board = self.board.flip_vertical().swap_colors();
+        state.side_to_move = self.side_to_move.opposite();
+        state.en_passant = self.en_passant.map(flip_coordinate);
+        state.promoted = transform_mask(self.promoted, flip_coordinate);
+        state.pockets.swap();
+        state.checks.reverse();
+
+        state.castling.white_kingside = self.castling.black_kingside;
+        state.castling.white_queenside = self.castling.black_queenside;
+        state.castling.black_kingside = self.castling.white_kingside;
+        state.castling.black_queenside = self.castling.white_queenside;
+        state.castling.kingside_files.reverse();
+        state.castling.queenside_files.reverse();
+
+        state.update_hash();
+        state
+    }
+
+    /// Returns the position with the `A` and `H` files swapped.
+    ///
+    /// Castling is not symmetric, the mirrored king would castle to the
+    /// other side of the board. All castling rights are removed instead
+    pub fn mirror(&self) -> GameState {
+        let mut state = *self;
+
+        state.board = self.board.mirror_horizontal();
+        state.en_passant = self.en_passant.map(mirror_coordinate);
+        state.promoted = transform_mask(self.promoted, mirror_coordinate);
+        state.castling = Default::default();
+
+        state.update_hash();
+        state
+    }
+
+    /// Returns the position turned by 180 degrees with the colors swapped,
+    /// the same as [GameState::flip_colors()] followed by [GameState::mirror()].
+    ///
+    /// Unlike [Board::rotate()] the colors change, so the pawns keep moving
+    /// towards the side they came from and the position stays playable
+    pub fn flip_and_mirror(&self) -> GameState {
+        self.flip_colors().mirror()
+    }
+}
@@ -0,0 +1,255 @@
+//! Checks whether a position can occur in a game
+//!
+//! Parsing a FEN string only makes sure it is well-formed. [GameState::validate()]
+//! checks the position itself against the rules of standard chess and Chess960,
+//! errors can then be located in the FEN string the position was parsed from.
+use crate::fen::{FENField, FromFENError, ToFENChar};
+
+use super::{
+    color::Color,
+    coordinate::{Coordinate, File},
+    game::GameState,
+    piece::{ColoredPiece, Piece},
+};
+
+impl GameState {
+    /// Checks that the position can occur in a game of standard chess or Chess960:
+    /// * Both players have exactly one king
+    /// * No pawn stands on the first or last rank
+    /// * The player who just moved is not in check
+    /// * The king and rook of every castling right are on their squares
+    /// * A pawn was just pushed past the en passant square
+    /// * The move counters fit the number of moves played
+    pub fn validate(&self) -> Result<(), FromFENError> {
+        let pieces = self.board.get_occupied_fields_rm();
+
+        for color in [Color::White, Color::Black] {
+            let king = ColoredPiece {
+                piece: Piece::King,
+                color,
+            };
+            let count = pieces.iter().filter(|(_, piece)| *piece == king).count();
+            if count != 1 {
+                return Err(FromFENError::KingCount { color, count });
+            }
+        }
+
+        if let Some((square, _)) = pieces.iter().find(|(square, piece)| {
+            piece.piece == Piece::Pawn && (square.rank as u8 == 0 || square.rank as u8 == 7)
+        }) {
+            return Err(FromFENError::PawnOnBackRank(*square));
+        }
+
+        let enemy = self.side_to_move.opposite();
+        let enemy_king = self
+            .board
+            .find_king(enemy)
+            .expect("[DEV] King count already checked");
+        if self.board.is_attacked(enemy_king, self.side_to_move) {
+            return Err(FromFENError::OpponentInCheck(enemy));
+        }
+
+        self.validate_castling()?;
+        self.validate_en_passant()?;
+
+        if self.fullmove_counter == 0 {
+            return Err(FromFENError::ImplausibleCounter {
+                field: FENField::FullmoveCounter,
+                value: self.fullmove_counter,
+            });
+        }
+
+        // The halfmove clock cannot count more moves than were played
+        let played = 2 * (self.fullmove_counter - 1) + self.side_to_move as u16;
+        if self.halfmove_clock > played || (self.en_passant.is_some() && self.halfmove_clock != 0) {
+            return Err(FromFENError::ImplausibleCounter {
+                field: FENField::HalfmoveClock,
+                value: self.halfmove_clock,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the king and rook of every castling right are on their home rank
+    fn validate_castling(&self) -> Result<(), FromFENError> {
+        for color in [Color::White, Color::Black] {
+            let home_rank = match color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let king = self
+                .board
+                .find_king(color)
+                .expect("[DEV] King count already checked");
+            let rook = ColoredPiece {
+                piece: Piece::Rook,
+                color,
+            };
+
+            for kingside in [true, false] {
+                let allowed = if kingside {
+                    self.castling.kingside(color)
+                } else {
+                    self.castling.queenside(color)
+                };
+                if !allowed {
+                    continue;
+                }
+
+                let file = self.castling.rook_file(color, kingside);
+                let rook_home = Coordinate::from_u8s(file as u8, home_rank)
+                    .is_some_and(|square| self.board.get(square) == Some(rook));
+                if king.rank as u8 != home_rank
+                    || !rook_home
+                    || (file as u8 > king.file as u8) != kingside
+                {
+                    return Err(FromFENError::InconsistentCastling(castling_symbol(
+                        color, kingside, file,
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a pawn of the player who just moved stands in front of the
+    /// en passant square, with the square itself and the one behind it empty
+    fn validate_en_passant(&self) -> Result<(), FromFENError> {
+        let Some(en_passant) = self.en_passant else {
+            return Ok(());
+        };
+
+        let (rank, forward) = match self.side_to_move {
+            Color::White => (5, -1),
+            Color::Black => (2, 1),
+        };
+        let pawn = ColoredPiece {
+            piece: Piece::Pawn,
+            color: self.side_to_move.opposite(),
+        };
+
+        let pushed = en_passant.rank as u8 == rank
+            && self.board.get(en_passant).is_none()
+            && en_passant
+                .offset(0, forward)
+                .is_some_and(|square| self.board.get(square) == Some(pawn))
+            && en_passant
+                .offset(0, -forward)
+                .is_some_and(|square| self.board.get(square).is_none());
+
+        if pushed {
+            Ok(())
+        } else {
+            Err(FromFENError::InconsistentEnPassant(en_passant))
+        }
+    }
+}
+
+/// Returns the castling symbol of a right as written in FEN, `KQkq` or the rook file
+fn castling_symbol(color: Color, kingside: bool, file: File) -> char {
+    let symbol = match (kingside, file) {
+        (true, File::FileH) => 'K',
+        (false, File::FileA) => 'Q',
+        _ => (b'A' + file as u8) as char,
+    };
+
+    match color {
+        Color::White => symbol,
+        Color::Black => symbol.to_ascii_lowercase(),
+    }
+}
+
+/// Splits a FEN string at whitespace into its fields with the character offset they start at
+/// # Arguments
+/// * `fen` - The FEN string
+pub(super) fn split_fields(fen: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+
+    for (offset, (byte, c)) in fen.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((offset, byte)),
+            (true, Some((first, from))) => {
+                fields.push((first, &fen[from..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((first, from)) = start {
+        fields.push((first, &fen[from..]));
+    }
+
+    fields
+}
+
+/// Locates an error of [GameState::validate()] in the FEN string the position was parsed from
+/// # Arguments
+/// * `error` - The error to locate
+/// * `fields` - The fields of the FEN string as returned by [split_fields()]
+pub(super) fn locate(error: FromFENError, fields: &[(usize, &str)]) -> FromFENError {
+    let field_start = |field: FENField| fields.get(field as usize).map_or(0, |(start, _)| *start);
+    let placement = fields.first().map_or("", |(_, placement)| *placement);
+
+    let (field, offset) = match &error {
+        // The second king of a color is the one too many
+        FromFENError::KingCount { color, .. } => {
+            let king = ColoredPiece {
+                piece: Piece::King,
+                color: *color,
+            }
+            .to_fen();
+            let offset = placement
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == king)
+                .nth(1);
+            (FENField::Placement, offset.map_or(0, |(offset, _)| offset))
+        }
+        FromFENError::PawnOnBackRank(square) => (
+            FENField::Placement,
+            square_offset(placement, *square).unwrap_or(0),
+        ),
+        FromFENError::OpponentInCheck(_) => (FENField::SideToMove, 0),
+        FromFENError::InconsistentCastling(symbol) => {
+            let castling = fields
+                .get(FENField::Castling as usize)
+                .map_or("", |(_, castling)| *castling);
+            let offset = castling.chars().position(|c| c == *symbol);
+            (FENField::Castling, offset.unwrap_or(0))
+        }
+        FromFENError::InconsistentEnPassant(_) => (FENField::EnPassant, 0),
+        FromFENError::ImplausibleCounter { field, .. } => (*field, 0),
+        _ => return error,
+    };
+
+    error.at(field, field_start(field) + offset)
+}
+
+/// Finds the character describing `square` in a piece placement field
+fn square_offset(placement: &str, square: Coordinate) -> Option<usize> {
+    let (mut file, mut rank) = (0u8, 7u8);
+
+    for (offset, c) in placement.chars().enumerate() {
+        match c {
+            '/' => {
+                file = 0;
+                rank = rank.checked_sub(1)?;
+            }
+            c => match c.to_digit(10) {
+                Some(num) => file += num as u8,
+                None => {
+                    if (file, rank) == (square.file as u8, square.rank as u8) {
+                        return Some(offset);
+                    }
+                    file += 1;
+                }
+            },
+        }
+    }
+
+    None
+}
@@ -0,0 +1,560 @@
+//! Chess variants
+//!
+//! The rules of all variants are implemented by [GameState] itself, its
+//! [Variant] selects where they deviate from standard chess:
+//!
+//! | Variant           | Deviation                                                        |
+//! | ----------------- | ---------------------------------------------------------------- |
+//! | Crazyhouse        | Captured pieces go to the pocket and can be dropped as a move    |
+//! | Atomic            | Captures explode all pieces but pawns around the target square   |
+//! | King of the Hill  | A king reaching one of the four center squares wins              |
+//! | Three-check       | Giving the third check wins                                      |
+//! | Antichess         | Captures are forced, kings are not royal, losing everything wins |
+//! | Horde             | White has 36 pawns and no king, losing all of them loses         |
+//! | Racing Kings      | Checks are forbidden, the first king on the eighth rank wins     |
+//!
+//! Positions of variants are written in FEN with the usual extensions:
+//! the pocket in brackets after the placement (`[Qn]`) with promoted pieces
+//! marked by `~`, and the remaining checks before the move counters (`3+3`).
+use std::fmt::Display;
+
+use crate::fen::{
+    FENMode, FromFENChar, FromFENError, FromFENString, ToFENChar, ToFENString, FEN_START,
+};
+
+use super::{
+    color::Color,
+    coordinate::Coordinate,
+    game::{GameState, Outcome},
+    moves::Move,
+    piece::{ColoredPiece, Piece, KING_OFFSETS},
+    square::Square,
+    zobrist::KEYS,
+};
+
+/// The number of checks that wins a game of Three-check
+pub const WINNING_CHECKS: u8 = 3;
+
+/// The squares a king has to reach in King of the Hill (`D4`, `E4`, `D5`, `E5`)
+const CENTER: [(u8, u8); 4] = [(3, 3), (4, 3), (3, 4), (4, 4)];
+
+/// The pieces that can be in a pocket, in the order they are written in FEN
+const POCKET_PIECES: [Piece; 5] = [
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
+/// The rules a game is played by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Standard chess
+    #[default]
+    Standard,
+    /// Captured pieces change sides and can be dropped back onto the board
+    Crazyhouse,
+    /// Captures explode the capturing piece and all pieces but pawns around the target
+    Atomic,
+    /// Bringing the king to the center wins
+    KingOfTheHill,
+    /// Checking the opponent three times wins
+    ThreeCheck,
+    /// Captures are compulsory and the player without moves or pieces wins
+    Antichess,
+    /// A horde of white pawns without a king fights the black army
+    Horde,
+    /// Both kings race to the eighth rank without ever giving check
+    RacingKings,
+}
+
+impl Variant {
+    /// All variants, standard chess first
+    pub const ALL: [Variant; 8] = [
+        Variant::Standard,
+        Variant::Crazyhouse,
+        Variant::Atomic,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Antichess,
+        Variant::Horde,
+        Variant::RacingKings,
+    ];
+
+    /// Returns the name of the variant in the `UCI_Variant` option
+    pub fn uci_name(&self) -> &'static str {
+        match self {
+            Self::Standard => "chess",
+            Self::Crazyhouse => "crazyhouse",
+            Self::Atomic => "atomic",
+            Self::KingOfTheHill => "kingofthehill",
+            Self::ThreeCheck => "3check",
+            Self::Antichess => "antichess",
+            Self::Horde => "horde",
+            Self::RacingKings => "racingkings",
+        }
+    }
+
+    /// Finds a variant by its name in the `UCI_Variant` option, ignoring the case
+    /// # Arguments
+    /// * `name` - The name of the variant
+    pub fn from_uci_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.uci_name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the starting position of the variant in FEN notation
+    pub fn start_fen(&self) -> &'static str {
+        match self {
+            Self::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Self::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+            Self::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Self::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+            Self::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+            _ => FEN_START,
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            Self::Standard => "Standard",
+            Self::Crazyhouse => "Crazyhouse",
+            Self::Atomic => "Atomic",
+            Self::KingOfTheHill => "King of the Hill",
+            Self::ThreeCheck => "Three-check",
+            Self::Antichess => "Antichess",
+            Self::Horde => "Horde",
+            Self::RacingKings => "Racing Kings",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The captured pieces both players can drop in Crazyhouse
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pockets {
+    /// The number of pieces, indexed by [Color] and [Piece]
+    counts: [[u8; 5]; 2],
+}
+
+impl Pockets {
+    /// Returns how many pieces of type `piece` `color` can drop
+    pub fn count(&self, color: Color, piece: Piece) -> u8 {
+        match piece {
+            Piece::King => 0,
+            _ => self.counts[color as usize][piece as usize],
+        }
+    }
+
+    /// Puts a piece of type `piece` into the pocket of `color`
+    pub fn add(&mut self, color: Color, piece: Piece) {
+        if piece != Piece::King {
+            self.counts[color as usize][piece as usize] += 1;
+        }
+    }
+
+    /// Takes a piece of type `piece` out of the pocket of `color`
+    pub fn remove(&mut self, color: Color, piece: Piece) {
+        if piece != Piece::King {
+            let count = &mut self.counts[color as usize][piece as usize];
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Swaps the pockets of white and black
+    pub fn swap(&mut self) {
+        self.counts.swap(0, 1);
+    }
+
+    /// Returns whether no player has a piece to drop
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().flatten().all(|count| *count == 0)
+    }
+}
+
+impl FromFENString for Pockets {
+    /// Parses the pieces in the pocket without the brackets (`QRrpp`), `-` for none
+    fn from_fen(fen: &str) -> Result<Self, FromFENError>
+    where
+        Self: Sized,
+    {
+        let mut pockets = Self::default();
+
+        for c in fen.chars().filter(|c| *c != '-') {
+            let piece = ColoredPiece::from_fen(c)?;
+            if piece.piece == Piece::King {
+                return Err(FromFENError::InvalidPlacementSymbol(c));
+            }
+            pockets.add(piece.color, piece.piece);
+        }
+
+        Ok(pockets)
+    }
+}
+
+impl ToFENString for Pockets {
+    fn to_fen(&self) -> String {
+        let mut string = String::new();
+
+        for color in [Color::White, Color::Black] {
+            for piece in POCKET_PIECES {
+                for _ in 0..self.count(color, piece) {
+                    string.push(ColoredPiece { piece, color }.to_fen());
+                }
+            }
+        }
+
+        string
+    }
+}
+
+/// Returns the bit of `square` in a mask of squares, see [Square::bit()]
+pub(super) fn square_bit(square: Coordinate) -> u64 {
+    Square::from(square).bit()
+}
+
+impl GameState {
+    /// Parses a position of `variant` in FEN notation with the extensions of the variant
+    /// # Arguments
+    /// * `fen` - The FEN string
+    /// * `variant` - The rules the game is played by
+    pub fn from_variant_fen(fen: &str, variant: Variant) -> Result<GameState, FromFENError> {
+        Self::from_variant_fen_with(fen, variant, FENMode::Normal)
+    }
+
+    /// Parses a position of `variant` in FEN notation, checking it as thoroughly as `mode`
+    /// demands. [GameState::validate()] knows the rules of standard chess only, so the
+    /// positions of other variants are never checked beyond being well-formed
+    /// # Arguments
+    /// * `fen` - The FEN string
+    /// * `variant` - The rules the game is played by
+    /// * `mode` - How thoroughly to check the string and position
+    pub fn from_variant_fen_with(
+        fen: &str,
+        variant: Variant,
+        mode: FENMode,
+    ) -> Result<GameState, FromFENError> {
+        if variant == Variant::Standard {
+            return Self::from_fen_with(fen, mode);
+        }
+
+        let mut fields: Vec<String> = fen.split_whitespace().map(str::to_string).collect();
+        if fields.is_empty() {
+            return Err(FromFENError::InvalidFormat);
+        }
+
+        let mut pockets = Pockets::default();
+        let mut promoted = Vec::new();
+        if variant == Variant::Crazyhouse {
+            let placement = fields[0].clone();
+
+            // The pocket is either in brackets or written as a ninth rank
+            let (board, pocket) = if let Some((board, pocket)) = placement.split_once('[') {
+                (board.to_string(), pocket.trim_end_matches(']').to_string())
+            } else if placement.matches('/').count() == 8 {
+                let (board, pocket) = placement
+                    .rsplit_once('/')
+                    .expect("[DEV] Placement without ranks");
+                (board.to_string(), pocket.to_string())
+            } else {
+                (placement, String::new())
+            };
+            pockets = Pockets::from_fen(&pocket)?;
+
+            // Promoted pieces are marked with a '~' after their letter
+            let (mut file, mut rank) = (0u8, 7u8);
+            for c in board.chars() {
+                match c {
+                    '/' => {
+                        file = 0;
+                        rank = rank.saturating_sub(1);
+                    }
+                    '~' => promoted.extend(Coordinate::from_u8s(file.wrapping_sub(1), rank)),
+                    c => file += c.to_digit(10).map_or(1, |n| n as u8),
+                }
+            }
+
+            fields[0] = board.replace('~', "");
+        }
+
+        let mut checks = [0; 2];
+        if variant == Variant::ThreeCheck {
+            let invalid = |field: &str| FromFENError::InvalidCounter(field.to_string());
+
+            // Either the remaining checks after en passant (`3+3`)
+            // or the given checks at the end (`+0+0`)
+            if let Some(index) = (4..fields.len()).find(|i| fields[*i].contains('+')) {
+                let field = fields.remove(index);
+                let counts: Vec<&str> = field.trim_start_matches('+').split('+').collect();
+                let [white, black] = counts.as_slice() else {
+                    return Err(invalid(&field));
+                };
+                let white: u8 = white.parse().map_err(|_| invalid(&field))?;
+                let black: u8 = black.parse().map_err(|_| invalid(&field))?;
+
+                checks = if field.starts_with('+') {
+                    [white, black]
+                } else {
+                    [
+                        WINNING_CHECKS.saturating_sub(white),
+                        WINNING_CHECKS.saturating_sub(black),
+                    ]
+                };
+            }
+        }
+
+        let mode = match mode {
+            FENMode::Strict => FENMode::Normal,
+            mode => mode,
+        };
+        let mut state = GameState::from_fen_with(&fields.join(" "), mode)?;
+        state.variant = variant;
+        state.pockets = pockets;
+        state.promoted = promoted.into_iter().map(square_bit).fold(0, |a, b| a | b);
+        state.checks = checks.map(|c| c.min(WINNING_CHECKS));
+        state.update_hash();
+
+        Ok(state)
+    }
+
+    /// Returns the piece placement in FEN with the pocket and promoted pieces in Crazyhouse
+    pub(super) fn placement_fen(&self) -> String {
+        if self.variant != Variant::Crazyhouse {
+            return self.board.to_fen();
+        }
+
+        let mut ranks = Vec::new();
+        for rank in (0..8).rev() {
+            let mut string = String::new();
+            let mut empty = 0;
+
+            for file in 0..8 {
+                let square =
+                    Coordinate::from_u8s(file, rank).expect("[DEV] Internal File and Rank mishap");
+                match self.board.get(square) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            string.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        string.push(piece.to_fen());
+                        if self.promoted & square_bit(square) != 0 {
+                            string.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                string.push_str(&empty.to_string());
+            }
+            ranks.push(string);
+        }
+
+        format!("{}[{}]", ranks.join("/"), self.pockets.to_fen())
+    }
+
+    /// Returns the outcome if the game ended by a rule of the variant,
+    /// either [Outcome::VariantWin] or [Outcome::VariantDraw]
+    pub fn variant_outcome(&self) -> Option<Outcome> {
+        let colors = [Color::White, Color::Black];
+
+        let winner = match self.variant {
+            Variant::Standard | Variant::Crazyhouse => None,
+            Variant::Atomic => colors
+                .into_iter()
+                .find(|color| self.board.find_king(*color).is_none())
+                .map(|color| color.opposite()),
+            Variant::KingOfTheHill => colors.into_iter().find(|color| {
+                self.board
+                    .find_king(*color)
+                    .is_some_and(|king| CENTER.contains(&(king.file as u8, king.rank as u8)))
+            }),
+            Variant::ThreeCheck => colors
+                .into_iter()
+                .find(|color| self.checks[*color as usize] >= WINNING_CHECKS),
+            // Having no pieces left means having no moves as well
+            Variant::Antichess => self
+                .pseudo_legal_moves()
+                .is_empty()
+                .then_some(self.side_to_move),
+            Variant::Horde => self
+                .board
+                .get_occupied_fields_fm()
+                .iter()
+                .all(|(_, piece)| piece.color != Color::White)
+                .then_some(Color::Black),
+            Variant::RacingKings => return self.race_outcome(),
+        };
+
+        winner.map(|winner| Outcome::VariantWin { winner })
+    }
+
+    /// Decides the race to the eighth rank in Racing Kings.
+    ///
+    /// When the white king arrives first black gets one more move,
+    /// reaching the eighth rank with it draws the game
+    fn race_outcome(&self) -> Option<Outcome> {
+        let arrived = |color: Color| {
+            self.board
+                .find_king(color)
+                .filter(|king| king.rank as u8 == 7)
+        };
+
+        match (arrived(Color::White), arrived(Color::Black)) {
+            (Some(_), Some(_)) => Some(Outcome::VariantDraw),
+            (None, Some(_)) => Some(Outcome::VariantWin {
+                winner: Color::Black,
+            }),
+            (Some(_), None) if self.side_to_move == Color::White || !self.can_catch_up() => {
+                Some(Outcome::VariantWin {
+                    winner: Color::White,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the black king can legally step onto the eighth rank
+    fn can_catch_up(&self) -> bool {
+        let Some(king) = self.board.find_king(Color::Black) else {
+            return false;
+        };
+
+        self.pseudo_legal_moves()
+            .into_iter()
+            .any(|mv| mv.from == king && mv.to.rank as u8 == 7 && self.is_legal_pseudo(mv))
+    }
+
+    /// Checks whether `by` attacks `square` in a way that counts for check.
+    ///
+    /// In Atomic kings cannot capture, so a square next to the enemy king is never attacked
+    /// # Arguments
+    /// * `square` - The square to check
+    /// * `by` - The attacking color
+    pub fn is_square_attacked(&self, square: Coordinate, by: Color) -> bool {
+        if self.variant == Variant::Atomic && self.is_next_to_king(square, by) {
+            return false;
+        }
+
+        self.board.is_attacked(square, by)
+    }
+
+    /// Returns whether the king of `color` stands on a square next to `square`
+    fn is_next_to_king(&self, square: Coordinate, color: Color) -> bool {
+        let king = ColoredPiece {
+            piece: Piece::King,
+            color,
+        };
+
+        KING_OFFSETS
+            .iter()
+            .any(|(f, r)| square.offset(*f, *r).and_then(|c| self.board.get(c)) == Some(king))
+    }
+
+    /// Adds the drops of the side to move to `moves`
+    pub(super) fn drop_moves(&self, moves: &mut Vec<Move>) {
+        let color = self.side_to_move;
+
+        for piece in POCKET_PIECES {
+            if self.pockets.count(color, piece) == 0 {
+                continue;
+            }
+
+            for file in 0..8 {
+                for rank in 0..8 {
+                    // Pawns cannot be dropped on the first and last rank
+                    if piece == Piece::Pawn && (rank == 0 || rank == 7) {
+                        continue;
+                    }
+                    let to = Coordinate::from_u8s(file, rank)
+                        .expect("[DEV] Internal File and Rank mishap");
+                    if self.board.get(to).is_none() {
+                        moves.push(Move::new_drop(piece, to));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Plays the drop of `piece` onto `to` and returns the resulting game state
+    pub(super) fn make_drop(&self, piece: Piece, to: Coordinate) -> GameState {
+        let mut next = *self;
+        let color = self.side_to_move;
+        let placed = ColoredPiece { piece, color };
+
+        if let Some(en_passant) = self.en_passant {
+            next.hash ^= KEYS.en_passant[en_passant.file as usize];
+        }
+        next.en_passant = None;
+
+        let count = self.pockets.count(color, piece);
+        next.pockets.remove(color, piece);
+        next.hash ^= KEYS.pocket(color, piece, count) ^ KEYS.pocket(color, piece, count - 1);
+
+        next.board.set(to, placed);
+        next.hash ^= KEYS.piece(placed, to);
+
+        next.halfmove_clock += 1;
+        if color == Color::Black {
+            next.fullmove_counter += 1;
+        }
+        next.side_to_move = color.opposite();
+        next.hash ^= KEYS.black_to_move;
+
+        next.count_check(color);
+        next
+    }
+
+    /// Puts a piece captured by `color` into its pocket in Crazyhouse,
+    /// promoted pieces turn back into pawns
+    pub(super) fn pocket_capture(&mut self, color: Color, captured: Piece, square: Coordinate) {
+        if self.variant != Variant::Crazyhouse {
+            return;
+        }
+
+        let piece = if self.promoted & square_bit(square) != 0 {
+            Piece::Pawn
+        } else {
+            captured
+        };
+        let count = self.pockets.count(color, piece);
+        self.pockets.add(color, piece);
+        self.hash ^= KEYS.pocket(color, piece, count) ^ KEYS.pocket(color, piece, count + 1);
+    }
+
+    /// Removes the piece on `square` and all pieces but pawns around it in Atomic
+    pub(super) fn explode(&mut self, square: Coordinate) {
+        let around = KING_OFFSETS
+            .iter()
+            .filter_map(|(f, r)| square.offset(*f, *r))
+            .filter(|c| self.board.get(*c).is_some_and(|p| p.piece != Piece::Pawn));
+
+        for victim in std::iter::once(square).chain(around).collect::<Vec<_>>() {
+            if let Some(piece) = self.board.get(victim) {
+                self.hash ^= KEYS.piece(piece, victim);
+                self.board.remove(victim);
+                self.castling.remove_rook(victim);
+                if piece.piece == Piece::King {
+                    self.castling.remove(piece.color);
+                }
+            }
+        }
+    }
+
+    /// Counts a check given by `color` in Three-check
+    pub(super) fn count_check(&mut self, color: Color) {
+        if self.variant == Variant::ThreeCheck && self.is_check() {
+            let count = self.checks[color as usize];
+            self.checks[color as usize] = count + 1;
+            self.hash ^= KEYS.check(color, count) ^ KEYS.check(color, count + 1);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Zobrist hashing keys used to identify positions
+use super::{
+    color::Color,
+    coordinate::Coordinate,
+    piece::{ColoredPiece, Piece},
+};
+
+/// The random keys a Zobrist hash is built from
+pub struct ZobristKeys {
+    /// One key per color, piece and square (`[color][piece][file * 8 + rank]`)
+    pub pieces: [[[u64; 64]; 6]; 2],
+    /// The key that is toggled when black is to move
+    pub black_to_move: u64,
+    /// One key per combination of castling rights
+    pub castling: [u64; 16],
+    /// One key per en passant file
+    pub en_passant: [u64; 8],
+    /// One key per color, piece and number of pieces in the pocket (`[color][piece][count]`)
+    pub pockets: [[[u64; MAX_POCKET + 1]; 5]; 2],
+    /// One key per color and number of checks given in Three-check
+    pub checks: [[u64; 4]; 2],
+}
+
+/// The largest number of pieces of one kind in a pocket that changes the hash
+pub const MAX_POCKET: usize = 16;
+
+/// The keys used by the engine, generated at compile time from a fixed seed
+pub const KEYS: ZobristKeys = ZobristKeys::generate(0x4B4E_4947_4854_4D52);
+
+impl ZobristKeys {
+    /// Generates a set of keys using the splitmix64 generator seeded with `seed`
+    const fn generate(seed: u64) -> Self {
+        let mut state = seed;
+        let mut keys = Self {
+            pieces: [[[0; 64]; 6]; 2],
+            black_to_move: 0,
+            castling: [0; 16],
+            en_passant: [0; 8],
+            pockets: [[[0; MAX_POCKET + 1]; 5]; 2],
+            checks: [[0; 4]; 2],
+        };
+
+        let mut color = 0;
+        while color < 2 {
+            let mut piece = 0;
+            while piece < 6 {
+                let mut square = 0;
+                while square < 64 {
+                    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                    keys.pieces[color][piece][square] = splitmix(state);
+                    square += 1;
+                }
+                piece += 1;
+            }
+            color += 1;
+        }
+
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        keys.black_to_move = splitmix(state);
+
+        let mut i = 0;
+        while i < 16 {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            keys.castling[i] = splitmix(state);
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < 8 {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            keys.en_passant[i] = splitmix(state);
+            i += 1;
+        }
+
+        let mut color = 0;
+        while color < 2 {
+            let mut piece = 0;
+            while piece < 5 {
+                // No pieces in the pocket leave the hash as it is
+                let mut count = 1;
+                while count <= MAX_POCKET {
+                    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                    keys.pockets[color][piece][count] = splitmix(state);
+                    count += 1;
+                }
+                piece += 1;
+            }
+
+            let mut count = 1;
+            while count < 4 {
+                state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                keys.checks[color][count] = splitmix(state);
+                count += 1;
+            }
+            color += 1;
+        }
+
+        keys
+    }
+
+    /// Returns the key for `piece` standing on `coordinate`
+    pub fn piece(&self, piece: ColoredPiece, coordinate: Coordinate) -> u64 {
+        let color = match piece.color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+
+        self.pieces[color][piece.piece as usize]
+            [coordinate.file as usize * 8 + coordinate.rank as usize]
+    }
+
+    /// Returns the key for `count` pieces of type `piece` in the pocket of `color`
+    pub fn pocket(&self, color: Color, piece: Piece, count: u8) -> u64 {
+        self.pockets[color as usize][piece as usize][(count as usize).min(MAX_POCKET)]
+    }
+
+    /// Returns the key for `color` having given `count` checks
+    pub fn check(&self, color: Color, count: u8) -> u64 {
+        self.checks[color as usize][(count as usize).min(3)]
+    }
+}
+
+/// The output function of the splitmix64 generator
+const fn splitmix(state: u64) -> u64 {
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,220 @@
+//! Stuff to work with games in Portable Game Notation (PGN)
+//!
+//! A [Game] holds the tags and the move tree of a single game.
+//! Games can be read from PGN files using the [PGNReader].
+use std::{fmt::Display, time::Duration};
+
+use crate::{
+    fen::{FromFENError, FromFENString, FEN_START},
+    game::{GameResult, GameState},
+    moves::Move,
+    san::FromSANError,
+};
+
+mod reader;
+pub use reader::*;
+
+mod writer;
+
+/// The tags every PGN game should have, in their export order
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// A game with its tags and moves
+#[derive(Clone, Debug)]
+pub struct Game {
+    /// The tags as `(name, value)` pairs in the order they appeared
+    pub tags: Vec<(String, String)>,
+    /// The position the game started from
+    pub start: GameState,
+    /// The moves of the game, the main line
+    pub mainline: Variation,
+    /// The result of the game, [None] if it is unknown or still ongoing
+    pub result: Option<GameResult>,
+}
+
+/// A sequence of moves, either the main line or an alternative to it
+#[derive(Clone, Debug, Default)]
+pub struct Variation {
+    /// The comments before the first move
+    pub comments: Vec<String>,
+    /// The moves
+    pub moves: Vec<MoveNode>,
+}
+
+/// A move within a [Variation] together with its annotations
+#[derive(Clone, Debug)]
+pub struct MoveNode {
+    /// The move itself
+    pub mv: Move,
+    /// The Numeric Annotation Glyphs, e.g. `1` for `!`
+    pub nags: Vec<u8>,
+    /// The comments after the move, without embedded commands like `[%clk]`
+    pub comments: Vec<String>,
+    /// The remaining clock time from a `[%clk]` command
+    pub clock: Option<Duration>,
+    /// The evaluation from an `[%eval]` command
+    pub eval: Option<PGNEval>,
+    /// Alternatives to this move, played from the position before it
+    pub variations: Vec<Variation>,
+}
+
+/// An evaluation annotated with an `[%eval]` command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PGNEval {
+    /// An evaluation in centipawns from white's point of view
+    Centipawns(i32),
+    /// A forced mate in that many moves, negative if black mates
+    Mate(i32),
+}
+
+/// An error that occurred while reading PGN
+#[derive(Debug)]
+pub struct PGNError {
+    /// The line the error occurred in, starting at `1`
+    pub line: usize,
+    /// The column the error occurred in, starting at `1`
+    pub column: usize,
+    /// What went wrong
+    pub kind: PGNErrorKind,
+}
+
+/// The kinds of errors that can occur while reading PGN
+#[derive(Debug)]
+pub enum PGNErrorKind {
+    /// A token appeared where it is not allowed
+    UnexpectedToken(String),
+    /// A string or comment has not been closed before the end of the input
+    UnexpectedEnd,
+    /// The `FEN` tag holds an invalid position
+    InvalidFEN(FromFENError),
+    /// A move could not be replayed
+    IllegalMove(FromSANError),
+}
+
+impl Display for PGNError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl Display for PGNErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedToken(token) => write!(f, "Unexpected token '{token}'"),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            Self::InvalidFEN(e) => write!(f, "Invalid FEN tag: {e}"),
+            Self::IllegalMove(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Game {
+    /// Creates a new game without tags and moves starting from `start`
+    /// # Arguments
+    /// * `start` - The position the game starts from
+    pub fn new(start: GameState) -> Self {
+        Self {
+            tags: Vec::new(),
+            start,
+            mainline: Variation::default(),
+            result: None,
+        }
+    }
+
+    /// Creates a new game from `start` with `moves` as the main line
+    /// # Arguments
+    /// * `start` - The position the game starts from
+    /// * `moves` - The moves played, they have to be legal
+    pub fn from_moves(start: GameState, moves: &[Move]) -> Self {
+        let mut game = Self::new(start);
+        game.mainline.moves = moves.iter().map(|mv| MoveNode::new(*mv)).collect();
+
+        game
+    }
+
+    /// Returns the value of the tag `name`
+    /// # Arguments
+    /// * `name` - The name of the tag, case sensitive
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the tag `name` to `value`, replacing an existing value
+    /// # Arguments
+    /// * `name` - The name of the tag
+    /// * `value` - The new value
+    pub fn set_tag<N: ToString, V: ToString>(&mut self, name: N, value: V) {
+        let name = name.to_string();
+
+        match self.tags.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.tags.push((name, value.to_string())),
+        }
+    }
+
+    /// Returns the moves of the main line
+    pub fn mainline_moves(&self) -> Vec<Move> {
+        self.mainline.moves.iter().map(|node| node.mv).collect()
+    }
+
+    /// Returns the position at the end of the main line
+    pub fn end_state(&self) -> GameState {
+        self.mainline
+            .moves
+            .iter()
+            .fold(self.start, |state, node| state.make_move(node.mv))
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(GameState::from_fen(FEN_START).expect("[DEV] Invalid starting position"))
+    }
+}
+
+impl MoveNode {
+    /// Creates a new node for `mv` without annotations
+    /// # Arguments
+    /// * `mv` - The move
+    pub fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            clock: None,
+            eval: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// Parses a result token (`1-0`, `0-1`, `1/2-1/2` or `*`)
+/// # Returns
+/// [None] if `token` is not a result, [Some(None)] for an unknown result (`*`)
+pub fn parse_result(token: &str) -> Option<Option<GameResult>> {
+    match token {
+        "1-0" => Some(Some(GameResult::WhiteWins)),
+        "0-1" => Some(Some(GameResult::BlackWins)),
+        "1/2-1/2" => Some(Some(GameResult::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+/// Formats a result as a result token (`1-0`, `0-1`, `1/2-1/2` or `*`)
+pub fn result_token(result: Option<GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::WhiteWins) => "1-0",
+        Some(GameResult::BlackWins) => "0-1",
+        Some(GameResult::Draw) => "1/2-1/2",
+        None => "*",
+    }
+}
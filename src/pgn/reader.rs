@@ -0,0 +1,538 @@
+//! A streaming reader for PGN files
+use std::{io::BufRead, time::Duration};
+
+use crate::{
+    color::Color,
+    error::{Error, ErrorExt, ErrorType},
+    fen::FENMode,
+    game::{GameResult, GameState},
+    str,
+};
+
+use super::{parse_result, Game, MoveNode, PGNError, PGNErrorKind, PGNEval, Variation};
+
+/// A lexical token of PGN
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    /// `[`
+    TagStart,
+    /// `]`
+    TagEnd,
+    /// A quoted string with escapes resolved
+    String(String),
+    /// Move text, move numbers, tag names and results
+    Symbol(String),
+    /// `.`
+    Period,
+    /// `*`
+    Asterisk,
+    /// `$n` or a suffix annotation like `!?`
+    Nag(u8),
+    /// `(`
+    VariationStart,
+    /// `)`
+    VariationEnd,
+    /// The text of a `{}` or `;` comment
+    Comment(String),
+}
+
+/// A token and the line and column it starts at
+type Positioned = (Token, usize, usize);
+
+/// Reads games one by one from PGN text
+///
+/// Every game gets replayed through [GameState], so all moves
+/// in the returned games are known to be legal.
+/// After an error the reader skips to the next game.
+pub struct PGNReader<R: BufRead> {
+    reader: R,
+    line: Vec<char>,
+    line_number: usize,
+    column: usize,
+    peeked: Option<Positioned>,
+    eof: bool,
+    games: usize,
+}
+
+impl<R: BufRead> PGNReader<R> {
+    /// Creates a new reader reading from `reader`
+    /// # Arguments
+    /// * `reader` - The source of the PGN text
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            line_number: 0,
+            column: 0,
+            peeked: None,
+            eof: false,
+            games: 0,
+        }
+    }
+
+    /// Reads the next game, [None] if the input is exhausted
+    pub fn read_game(&mut self) -> Option<Result<Game, Error>> {
+        // Skip stray tokens between games
+        loop {
+            match self.peek() {
+                Ok(None) => return None,
+                Ok(Some((Token::TagStart, _, _))) => break,
+                Ok(Some((Token::Symbol(_), _, _))) => break,
+                Ok(Some(_)) => {
+                    let _ = self.next();
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        self.games += 1;
+        let line = self.line_number;
+        let game = self.parse_game();
+
+        if game.is_err() {
+            self.recover();
+        }
+
+        Some(game.ctx(str!("Reading game #{} starting at line {line}", self.games)))
+    }
+
+    /// Parses the tags and the move text of a game
+    fn parse_game(&mut self) -> Result<Game, Error> {
+        let mut game = Game::default();
+        // Where the value of the first FEN tag starts, for locating errors in it
+        let mut fen_location = None;
+
+        while let Some((Token::TagStart, _, _)) = self.peek()? {
+            self.next()?;
+
+            let name = match self.next()? {
+                Some((Token::Symbol(name), _, _)) => name,
+                token => return Err(self.unexpected(token)),
+            };
+            let value = match self.next()? {
+                Some((Token::String(value), line, column)) => {
+                    if name == "FEN" && fen_location.is_none() {
+                        fen_location = Some((line, column));
+                    }
+                    value
+                }
+                token => return Err(self.unexpected(token)),
+            };
+            match self.next()? {
+                Some((Token::TagEnd, _, _)) => {}
+                token => return Err(self.unexpected(token)),
+            }
+
+            game.tags.push((name, value));
+        }
+
+        if let (Some(fen), Some((line, column))) = (game.tag("FEN"), fen_location) {
+            game.start = GameState::from_fen_with(fen, FENMode::Strict)
+                .map_err(|e| pgn_error(line, column, PGNErrorKind::InvalidFEN(e)))?;
+        }
+
+        let (mainline, result) = self.parse_variation(game.start, false)?;
+        game.mainline = mainline;
+        game.result = result;
+
+        Ok(game)
+    }
+
+    /// Parses moves until the end of the variation or game
+    /// # Arguments
+    /// * `state` - The position before the first move
+    /// * `nested` - Whether this is a variation that ends with `)`
+    /// # Returns
+    /// The variation and, for the main line, the result token
+    fn parse_variation(
+        &mut self,
+        mut state: GameState,
+        nested: bool,
+    ) -> Result<(Variation, Option<GameResult>), Error> {
+        let mut variation = Variation::default();
+        let mut previous = state;
+
+        loop {
+            let Some((token, line, column)) = self.peek()? else {
+                if nested {
+                    return Err(self.unexpected(None));
+                }
+                return Ok((variation, None));
+            };
+
+            match token {
+                // A new game begins without a result token
+                Token::TagStart if !nested => return Ok((variation, None)),
+                Token::VariationEnd if nested => {
+                    self.next()?;
+                    return Ok((variation, None));
+                }
+                Token::Asterisk if !nested => {
+                    self.next()?;
+                    return Ok((variation, None));
+                }
+                Token::Period => {
+                    self.next()?;
+                }
+                Token::Nag(nag) => {
+                    self.next()?;
+                    if let Some(node) = variation.moves.last_mut() {
+                        node.nags.push(nag);
+                    }
+                }
+                Token::Comment(text) => {
+                    self.next()?;
+                    match variation.moves.last_mut() {
+                        Some(node) => parse_comment(&text, node),
+                        None => {
+                            let text = text.trim();
+                            if !text.is_empty() {
+                                variation.comments.push(text.to_string());
+                            }
+                        }
+                    }
+                }
+                Token::VariationStart if !variation.moves.is_empty() => {
+                    self.next()?;
+                    let (alternative, _) = self.parse_variation(previous, true)?;
+                    if let Some(node) = variation.moves.last_mut() {
+                        node.variations.push(alternative);
+                    }
+                }
+                Token::Symbol(symbol) => {
+                    self.next()?;
+
+                    if let Some(result) = parse_result(&symbol) {
+                        if nested {
+                            return Err(self.unexpected(Some((
+                                Token::Symbol(symbol),
+                                line,
+                                column,
+                            ))));
+                        }
+                        return Ok((variation, result));
+                    }
+
+                    // Move numbers
+                    if symbol.chars().all(|c| c.is_ascii_digit()) {
+                        continue;
+                    }
+
+                    let mv = state
+                        .parse_san(&symbol)
+                        .map_err(|e| pgn_error(line, column, PGNErrorKind::IllegalMove(e)))
+                        .ctx(str!("Replaying move {} '{symbol}'", move_number(&state)))?;
+
+                    previous = state;
+                    state = state.make_move(mv);
+                    variation.moves.push(MoveNode::new(mv));
+                }
+                // A tag at the start of a line begins the next game, it is left
+                // for the reader to continue with after the error
+                Token::TagStart if column == 1 => {
+                    return Err(self.unexpected(Some((token, line, column))));
+                }
+                _ => {
+                    self.next()?;
+                    return Err(self.unexpected(Some((token, line, column))));
+                }
+            }
+        }
+    }
+
+    /// Skips tokens until the next game starts
+    fn recover(&mut self) {
+        loop {
+            match self.peek() {
+                Ok(None) => return,
+                Ok(Some((Token::TagStart, _, 1))) => return,
+                Ok(Some((Token::Symbol(s), _, _))) if parse_result(&s).is_some() => {
+                    self.peeked = None;
+                    return;
+                }
+                Ok(Some((Token::Asterisk, _, _))) => {
+                    self.peeked = None;
+                    return;
+                }
+                _ => self.peeked = None,
+            }
+        }
+    }
+
+    /// Creates the error for an unexpected token
+    fn unexpected(&self, token: Option<Positioned>) -> Error {
+        match token {
+            Some((token, line, column)) => pgn_error(
+                line,
+                column,
+                PGNErrorKind::UnexpectedToken(format!("{token:?}")),
+            ),
+            None => pgn_error(
+                self.line_number,
+                self.column + 1,
+                PGNErrorKind::UnexpectedEnd,
+            ),
+        }
+    }
+
+    /// Returns the next token without consuming it
+    fn peek(&mut self) -> Result<Option<Positioned>, Error> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+
+        Ok(self.peeked.clone())
+    }
+
+    /// Consumes the next token
+    fn next(&mut self) -> Result<Option<Positioned>, Error> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.lex(),
+        }
+    }
+
+    /// Reads the next line into the buffer, returns `false` at the end of the input
+    fn read_line(&mut self) -> Result<bool, Error> {
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .ctx(str!("Reading line {}", self.line_number + 1))?;
+
+            if read == 0 {
+                self.eof = true;
+                return Ok(false);
+            }
+
+            self.line_number += 1;
+            self.column = 0;
+            self.line = line.chars().collect();
+
+            // Lines starting with '%' are escaped and ignored
+            if self.line.first() != Some(&'%') {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Returns the current character without consuming it, reading lines as needed
+    fn current(&mut self) -> Result<Option<char>, Error> {
+        while self.column >= self.line.len() {
+            if self.eof || !self.read_line()? {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(self.line[self.column]))
+    }
+
+    /// Reads the next token from the input
+    fn lex(&mut self) -> Result<Option<Positioned>, Error> {
+        // Skip whitespace
+        while let Some(c) = self.current()? {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.column += 1;
+        }
+
+        let Some(c) = self.current()? else {
+            return Ok(None);
+        };
+        let (line, column) = (self.line_number, self.column + 1);
+        self.column += 1;
+
+        let token = match c {
+            '[' => Token::TagStart,
+            ']' => Token::TagEnd,
+            '(' => Token::VariationStart,
+            ')' => Token::VariationEnd,
+            '.' => Token::Period,
+            '*' => Token::Asterisk,
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match self.line.get(self.column) {
+                        Some('\\')
+                            if matches!(self.line.get(self.column + 1), Some('"' | '\\')) =>
+                        {
+                            string.push(self.line[self.column + 1]);
+                            self.column += 2;
+                        }
+                        Some('"') => {
+                            self.column += 1;
+                            break;
+                        }
+                        Some(c) if *c != '\n' && *c != '\r' => {
+                            string.push(*c);
+                            self.column += 1;
+                        }
+                        _ => return Err(pgn_error(line, column, PGNErrorKind::UnexpectedEnd)),
+                    }
+                }
+                Token::String(string)
+            }
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match self.current()? {
+                        Some('}') => {
+                            self.column += 1;
+                            break;
+                        }
+                        Some(c) => {
+                            comment.push(c);
+                            self.column += 1;
+                        }
+                        None => return Err(pgn_error(line, column, PGNErrorKind::UnexpectedEnd)),
+                    }
+                }
+                Token::Comment(comment)
+            }
+            ';' => {
+                let comment: String = self.line[self.column..].iter().collect();
+                self.column = self.line.len();
+                Token::Comment(comment.trim_end().to_string())
+            }
+            '$' => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                match digits.parse() {
+                    Ok(nag) => Token::Nag(nag),
+                    Err(_) => {
+                        return Err(pgn_error(
+                            line,
+                            column,
+                            PGNErrorKind::UnexpectedToken(format!("${digits}")),
+                        ))
+                    }
+                }
+            }
+            '!' | '?' => {
+                let annotation = format!("{c}{}", self.take_while(|c| c == '!' || c == '?'));
+                match annotation.as_str() {
+                    "!" => Token::Nag(1),
+                    "?" => Token::Nag(2),
+                    "!!" => Token::Nag(3),
+                    "??" => Token::Nag(4),
+                    "!?" => Token::Nag(5),
+                    "?!" => Token::Nag(6),
+                    _ => {
+                        return Err(pgn_error(
+                            line,
+                            column,
+                            PGNErrorKind::UnexpectedToken(annotation),
+                        ))
+                    }
+                }
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let rest = self.take_while(|c| c.is_ascii_alphanumeric() || "_+#=:-/".contains(c));
+                Token::Symbol(format!("{c}{rest}"))
+            }
+            c => {
+                return Err(pgn_error(
+                    line,
+                    column,
+                    PGNErrorKind::UnexpectedToken(c.to_string()),
+                ))
+            }
+        };
+
+        Ok(Some((token, line, column)))
+    }
+
+    /// Consumes characters of the current line as long as `predicate` holds
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> String {
+        let mut string = String::new();
+
+        while let Some(c) = self.line.get(self.column) {
+            if !predicate(*c) {
+                break;
+            }
+            string.push(*c);
+            self.column += 1;
+        }
+
+        string
+    }
+}
+
+impl<R: BufRead> Iterator for PGNReader<R> {
+    type Item = Result<Game, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_game()
+    }
+}
+
+/// Creates an [Error] for a PGN error at `line` and `column`
+fn pgn_error(line: usize, column: usize, kind: PGNErrorKind) -> Error {
+    Error::new(ErrorType::PGN(PGNError { line, column, kind }))
+}
+
+/// Formats the move number of the next move in `state`, e.g. `12.` or `12...`
+fn move_number(state: &GameState) -> String {
+    match state.side_to_move {
+        Color::White => format!("{}.", state.fullmove_counter),
+        Color::Black => format!("{}...", state.fullmove_counter),
+    }
+}
+
+/// Adds a comment to `node`, extracting `[%clk]` and `[%eval]` commands
+fn parse_comment(text: &str, node: &mut MoveNode) {
+    let mut remaining = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[%") {
+        let Some(end) = rest[start..].find(']') else {
+            break;
+        };
+
+        let command = &rest[start + 2..start + end];
+        let mut parts = command.split_whitespace();
+        let handled = match (parts.next(), parts.next()) {
+            (Some("clk"), Some(clock)) => parse_clock(clock).map(|c| node.clock = Some(c)),
+            (Some("eval"), Some(eval)) => parse_eval(eval).map(|e| node.eval = Some(e)),
+            _ => None,
+        };
+
+        remaining.push_str(&rest[..start]);
+        if handled.is_none() {
+            remaining.push_str(&rest[start..start + end + 1]);
+        }
+        rest = &rest[start + end + 1..];
+    }
+    remaining.push_str(rest);
+
+    let remaining = remaining
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if !remaining.is_empty() {
+        node.comments.push(remaining);
+    }
+}
+
+/// Parses a clock time in the form `h:mm:ss(.f)`
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Parses an evaluation in pawns (`0.35`) or as a mate (`#-3`)
+fn parse_eval(eval: &str) -> Option<PGNEval> {
+    match eval.strip_prefix('#') {
+        Some(mate) => mate.parse().ok().map(PGNEval::Mate),
+        None => eval
+            .parse::<f64>()
+            .ok()
+            .map(|pawns| PGNEval::Centipawns((pawns * 100.0).round() as i32)),
+    }
+}
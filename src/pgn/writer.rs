@@ -0,0 +1,216 @@
+//! Rendering games to PGN export format
+use std::time::Duration;
+
+use crate::{
+    color::Color,
+    fen::{ToFENString, FEN_START},
+    game::GameState,
+};
+
+use super::{result_token, Game, MoveNode, PGNEval, Variation, SEVEN_TAG_ROSTER};
+
+/// The maximum length of a line in export format
+const MAX_LINE_LENGTH: usize = 79;
+
+impl Game {
+    /// Renders the game in PGN export format.
+    ///
+    /// The seven tag roster comes first (with placeholders for missing tags),
+    /// followed by `SetUp` and `FEN` for games not starting from the initial position
+    /// and all other tags. The move text is wrapped at 80 columns.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let start_fen = self.start.to_fen();
+        let custom_start = start_fen != FEN_START;
+
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => result_token(self.result).to_string(),
+                "Date" => self.tag(name).unwrap_or("????.??.??").to_string(),
+                _ => self.tag(name).unwrap_or("?").to_string(),
+            };
+            push_tag(&mut pgn, name, &value);
+        }
+
+        if custom_start {
+            push_tag(&mut pgn, "SetUp", "1");
+            push_tag(&mut pgn, "FEN", &start_fen);
+        }
+
+        for (name, value) in &self.tags {
+            let written = SEVEN_TAG_ROSTER.contains(&name.as_str())
+                || (custom_start && (name == "SetUp" || name == "FEN"));
+            if !written {
+                push_tag(&mut pgn, name, value);
+            }
+        }
+
+        pgn.push('\n');
+
+        let mut writer = MoveTextWriter::default();
+        writer.variation(&self.mainline, self.start);
+        writer.token(result_token(self.result));
+        pgn.push_str(&writer.finish());
+        pgn.push_str("\n\n");
+
+        pgn
+    }
+}
+
+/// Appends a tag pair to `pgn`, escaping the value
+fn push_tag(pgn: &mut String, name: &str, value: &str) {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    pgn.push_str(&format!("[{name} \"{escaped}\"]\n"));
+}
+
+/// Collects move text tokens and wraps them into lines
+#[derive(Default)]
+struct MoveTextWriter {
+    lines: Vec<String>,
+    line: String,
+    /// Text to glue to the front of the next token, e.g. `(`
+    prefix: String,
+}
+
+impl MoveTextWriter {
+    /// Adds a token, starting a new line if it does not fit anymore
+    fn token(&mut self, token: &str) {
+        let token = format!("{}{token}", std::mem::take(&mut self.prefix));
+
+        if !self.line.is_empty() && self.line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+            self.lines.push(std::mem::take(&mut self.line));
+        }
+        if !self.line.is_empty() {
+            self.line.push(' ');
+        }
+        self.line.push_str(&token);
+    }
+
+    /// Glues `suffix` to the end of the last token, moving the token
+    /// to a new line if it does not fit anymore
+    fn suffix(&mut self, suffix: &str) {
+        if self.line.len() + suffix.len() > MAX_LINE_LENGTH {
+            if let Some(space) = self.line.rfind(' ') {
+                let last = self.line.split_off(space);
+                self.lines.push(std::mem::take(&mut self.line));
+                self.line = last[1..].to_string();
+            }
+        }
+        self.line.push_str(suffix);
+    }
+
+    /// Adds a `{}` comment, splitting it into words so it can be wrapped
+    fn comment(&mut self, comment: &str) {
+        let words: Vec<&str> = comment.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => self.token("{}"),
+            [word] => self.token(&format!("{{{word}}}")),
+            [first, middle @ .., last] => {
+                self.token(&format!("{{{first}"));
+                for word in middle {
+                    self.token(word);
+                }
+                self.token(&format!("{last}}}"));
+            }
+        }
+    }
+
+    /// Writes the moves of `variation` played from `state`
+    fn variation(&mut self, variation: &Variation, mut state: GameState) {
+        for comment in &variation.comments {
+            self.comment(&comment.replace('}', ""));
+        }
+
+        // Black moves need their number at the start and after interruptions
+        let mut needs_number = true;
+
+        for node in &variation.moves {
+            match state.side_to_move {
+                Color::White => self.token(&format!("{}.", state.fullmove_counter)),
+                Color::Black if needs_number => {
+                    self.token(&format!("{}...", state.fullmove_counter))
+                }
+                Color::Black => {}
+            }
+            self.token(&node.mv.to_san(&state));
+            needs_number = false;
+
+            for nag in &node.nags {
+                self.token(&format!("${nag}"));
+            }
+
+            if let Some(comment) = node_comment(node) {
+                self.comment(&comment);
+                needs_number = true;
+            }
+
+            for alternative in &node.variations {
+                self.prefix.push('(');
+                self.variation(alternative, state);
+                if self.prefix.is_empty() {
+                    self.suffix(")");
+                } else {
+                    self.prefix.clear();
+                    self.token("()");
+                }
+                needs_number = true;
+            }
+
+            state = state.make_move(node.mv);
+        }
+    }
+
+    /// Returns the wrapped lines joined by line breaks
+    fn finish(mut self) -> String {
+        if !self.line.is_empty() {
+            self.lines.push(self.line);
+        }
+
+        self.lines.join("\n")
+    }
+}
+
+/// Builds the comment of `node` including its `[%eval]` and `[%clk]` commands
+fn node_comment(node: &MoveNode) -> Option<String> {
+    let mut parts = Vec::new();
+
+    if let Some(eval) = node.eval {
+        parts.push(match eval {
+            PGNEval::Centipawns(cp) => format!("[%eval {:.2}]", cp as f64 / 100.0),
+            PGNEval::Mate(moves) => format!("[%eval #{moves}]"),
+        });
+    }
+
+    if let Some(clock) = node.clock {
+        parts.push(format!("[%clk {}]", format_clock(clock)));
+    }
+
+    for comment in &node.comments {
+        parts.push(comment.replace('}', ""));
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// Formats a clock time as `h:mm:ss`, with tenths of seconds if needed
+fn format_clock(clock: Duration) -> String {
+    let seconds = clock.as_secs();
+    let tenths = clock.subsec_millis() / 100;
+    let base = format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    if tenths == 0 {
+        base
+    } else {
+        format!("{base}.{tenths}")
+    }
+}
@@ -0,0 +1,401 @@
+//! Opening books in the Polyglot `.bin` format
+//!
+//! A book is a sorted list of 16 byte entries (all numbers big endian):
+//! the Polyglot key of the position (8 bytes), the move (2 bytes),
+//! the weight of the move (2 bytes) and a learn value (4 bytes).
+use std::{collections::HashMap, io::BufRead, path::Path};
+
+use crate::{
+    color::Color,
+    coordinate::Coordinate,
+    error::{Error, ErrorExt},
+    game::{GameResult, GameState},
+    moves::Move,
+    pgn::PGNReader,
+    piece::{ColoredPiece, Piece},
+    random::Random,
+    str,
+};
+
+mod keys;
+pub use keys::RANDOM64;
+
+/// The size of a book entry in bytes
+pub const ENTRY_SIZE: usize = 16;
+
+/// A single entry of a Polyglot book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    /// The Polyglot key of the position
+    pub key: u64,
+    /// The move in Polyglot encoding
+    pub mv: u16,
+    /// The weight of the move, higher is better
+    pub weight: u16,
+    /// The learn value, unused by the engine
+    pub learn: u32,
+}
+
+/// How to choose among the book moves of a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum BookSelection {
+    /// Always play the move with the highest weight
+    Best,
+    /// Pick a random move, with probabilities proportional to the weights
+    Weighted,
+}
+
+/// A Polyglot opening book
+#[derive(Clone, Debug, Default)]
+pub struct PolyglotBook {
+    entries: Vec<BookEntry>,
+}
+
+impl GameState {
+    /// Computes the standard Polyglot key of this position
+    pub fn polyglot_key(&self) -> u64 {
+        let mut key = 0;
+
+        for (coordinate, piece) in self.board.get_occupied_fields_fm() {
+            let kind = match piece.piece {
+                Piece::Pawn => 0,
+                Piece::Knight => 1,
+                Piece::Bishop => 2,
+                Piece::Rook => 3,
+                Piece::Queen => 4,
+                Piece::King => 5,
+            } * 2
+                + (piece.color == Color::White) as usize;
+
+            key ^= RANDOM64[64 * kind + 8 * coordinate.rank as usize + coordinate.file as usize];
+        }
+
+        let castling = [
+            self.castling.white_kingside,
+            self.castling.white_queenside,
+            self.castling.black_kingside,
+            self.castling.black_queenside,
+        ];
+        for (i, right) in castling.iter().enumerate() {
+            if *right {
+                key ^= RANDOM64[768 + i];
+            }
+        }
+
+        // The en passant file only counts if a pawn could capture there
+        if let Some(en_passant) = self.en_passant {
+            let rank = match self.side_to_move {
+                Color::White => 4,
+                Color::Black => 3,
+            };
+            let pawn = ColoredPiece {
+                piece: Piece::Pawn,
+                color: self.side_to_move,
+            };
+            let capturable = [-1, 1].iter().any(|f| {
+                Coordinate::from_u8s(en_passant.file as u8, rank)
+                    .and_then(|c| c.offset(*f, 0))
+                    .is_some_and(|c| self.board.get(c) == Some(pawn))
+            });
+            if capturable {
+                key ^= RANDOM64[772 + en_passant.file as usize];
+            }
+        }
+
+        if self.side_to_move == Color::White {
+            key ^= RANDOM64[780];
+        }
+
+        key
+    }
+}
+
+impl BookEntry {
+    /// Parses an entry from its binary representation
+    pub fn from_bytes(bytes: &[u8; ENTRY_SIZE]) -> Self {
+        Self {
+            key: u64::from_be_bytes(bytes[0..8].try_into().expect("[DEV] Slice size mishap")),
+            mv: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        }
+    }
+
+    /// Serializes the entry to its binary representation
+    pub fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.mv.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+
+    /// Decodes the move of this entry in `state`, [None] if it is not legal there
+    /// # Arguments
+    /// * `state` - The position the entry belongs to
+    pub fn decode_move(&self, state: &GameState) -> Option<Move> {
+        let square = |bits: u16| Coordinate::from_u8s((bits & 7) as u8, ((bits >> 3) & 7) as u8);
+        let from = square(self.mv >> 6)?;
+        let mut to = square(self.mv)?;
+        let promotion = match (self.mv >> 12) & 7 {
+            0 => None,
+            1 => Some(Piece::Knight),
+            2 => Some(Piece::Bishop),
+            3 => Some(Piece::Rook),
+            4 => Some(Piece::Queen),
+            _ => return None,
+        };
+
+        // Castling is encoded as the king capturing its own rook, which is
+        // already how castling is written in Chess960 positions
+        let king = state.board.get(from);
+        let target = state.board.get(to);
+        if !state.chess960
+            && king.is_some_and(|k| k.piece == Piece::King)
+            && target
+                .is_some_and(|t| t.piece == Piece::Rook && Some(t.color) == king.map(|k| k.color))
+        {
+            let direction = if to.file as u8 > from.file as u8 {
+                2
+            } else {
+                -2
+            };
+            to = from.offset(direction, 0)?;
+        }
+
+        let mv = Move {
+            from,
+            to,
+            promotion,
+            drop: None,
+        };
+        state.is_legal(mv).then_some(mv)
+    }
+
+    /// Encodes `mv` played in `state` in Polyglot format
+    /// # Arguments
+    /// * `mv` - The move to encode
+    /// * `state` - The position the move is played in
+    pub fn encode_move(mv: Move, state: &GameState) -> u16 {
+        let mut to = mv.to;
+
+        if state.is_castling(mv) {
+            let kingside = mv.to.file as u8 > mv.from.file as u8;
+            let color = state.side_to_move;
+            to = Coordinate {
+                file: state.castling.rook_file(color, kingside),
+                rank: mv.from.rank,
+            };
+        }
+
+        let promotion = match mv.promotion {
+            None => 0,
+            Some(Piece::Knight) => 1,
+            Some(Piece::Bishop) => 2,
+            Some(Piece::Rook) => 3,
+            Some(_) => 4,
+        };
+
+        to.file as u16
+            | (to.rank as u16) << 3
+            | (mv.from.file as u16) << 6
+            | (mv.from.rank as u16) << 9
+            | promotion << 12
+    }
+}
+
+impl PolyglotBook {
+    /// Creates a book from `entries`, sorting them as required by the format
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)));
+
+        Self { entries }
+    }
+
+    /// Parses a book from its binary representation, a trailing partial entry is ignored
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_entries(
+            bytes
+                .chunks_exact(ENTRY_SIZE)
+                .map(|chunk| {
+                    BookEntry::from_bytes(chunk.try_into().expect("[DEV] Chunk size mishap"))
+                })
+                .collect(),
+        )
+    }
+
+    /// Loads a book from the file at `path`
+    /// # Arguments
+    /// * `path` - The path to the `.bin` file
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).ctx(str!("Reading opening book {}", path.display()))?;
+
+        Ok(Self::from_bytes(&bytes))
+    }
+
+    /// Serializes the book to its binary representation
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries.iter().flat_map(|e| e.to_bytes()).collect()
+    }
+
+    /// Writes the book to the file at `path`
+    /// # Arguments
+    /// * `path` - The path to the `.bin` file
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.to_bytes()).ctx(str!("Writing opening book {}", path.display()))
+    }
+
+    /// Returns all entries
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    /// Looks up the entries for `state` using binary search
+    /// # Arguments
+    /// * `state` - The position to look up
+    pub fn lookup(&self, state: &GameState) -> &[BookEntry] {
+        let key = state.polyglot_key();
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = start + self.entries[start..].partition_point(|e| e.key == key);
+
+        &self.entries[start..end]
+    }
+
+    /// Returns the legal book moves for `state` with their weights, best first
+    /// # Arguments
+    /// * `state` - The position to look up
+    pub fn moves(&self, state: &GameState) -> Vec<(Move, u16)> {
+        let mut moves: Vec<(Move, u16)> = self
+            .lookup(state)
+            .iter()
+            .filter_map(|e| Some((e.decode_move(state)?, e.weight)))
+            .collect();
+        moves.sort_by_key(|(_, weight)| std::cmp::Reverse(*weight));
+
+        moves
+    }
+
+    /// Chooses a book move for `state`, [None] if the position is not in the book
+    /// # Arguments
+    /// * `state` - The position to choose a move for
+    /// * `selection` - How to choose among the book moves
+    /// * `random` - The random number generator for weighted selection
+    pub fn pick(
+        &self,
+        state: &GameState,
+        selection: BookSelection,
+        random: &mut Random,
+    ) -> Option<Move> {
+        let moves = self.moves(state);
+
+        match selection {
+            BookSelection::Best => moves.first().map(|(mv, _)| *mv),
+            BookSelection::Weighted => {
+                let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+                if total == 0 {
+                    return moves.first().map(|(mv, _)| *mv);
+                }
+
+                let mut choice = random.below(total);
+                for (mv, weight) in &moves {
+                    if choice < *weight as u64 {
+                        return Some(*mv);
+                    }
+                    choice -= *weight as u64;
+                }
+
+                None
+            }
+        }
+    }
+}
+
+/// The settings for building a book from games
+#[derive(Clone, Copy, Debug)]
+pub struct BookBuildConfig {
+    /// Moves that occurred in fewer games are left out
+    pub min_games: u32,
+    /// Only the first this many plies of each game are used
+    pub max_ply: usize,
+}
+
+/// Statistics about a built book
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BookBuildStats {
+    /// The number of games that have been read
+    pub games: u64,
+    /// The number of games that could not be read
+    pub errors: u64,
+}
+
+/// Builds a book from the games of a PGN collection.
+///
+/// Each move is weighted by its results: 2 points per win and
+/// 1 point per draw for the side that played it
+/// # Arguments
+/// * `pgn` - The PGN collection
+/// * `config` - The filters to apply
+pub fn build_book<R: BufRead>(
+    pgn: R,
+    config: &BookBuildConfig,
+) -> Result<(PolyglotBook, BookBuildStats), Error> {
+    // (key, move) -> (games, points)
+    let mut moves: HashMap<(u64, u16), (u32, u64)> = HashMap::new();
+    let mut stats = BookBuildStats::default();
+
+    for game in PGNReader::new(pgn) {
+        let game = match game {
+            Ok(game) => game,
+            Err(e) => {
+                log::warn!("Skipping game: {e}");
+                stats.errors += 1;
+                continue;
+            }
+        };
+        stats.games += 1;
+
+        let mut state = game.start;
+        for node in game.mainline.moves.iter().take(config.max_ply) {
+            let points = match (game.result, state.side_to_move) {
+                (Some(GameResult::WhiteWins), Color::White) => 2,
+                (Some(GameResult::BlackWins), Color::Black) => 2,
+                (Some(GameResult::Draw), _) => 1,
+                _ => 0,
+            };
+
+            let entry = moves
+                .entry((
+                    state.polyglot_key(),
+                    BookEntry::encode_move(node.mv, &state),
+                ))
+                .or_default();
+            entry.0 += 1;
+            entry.1 += points;
+
+            state = state.make_move(node.mv);
+        }
+    }
+
+    moves.retain(|_, (games, _)| *games >= config.min_games);
+
+    // Scale the weights down if they do not fit
+    let max = moves.values().map(|(_, points)| *points).max().unwrap_or(0);
+    let scale = if max > u16::MAX as u64 {
+        u16::MAX as f64 / max as f64
+    } else {
+        1.0
+    };
+
+    let entries = moves
+        .into_iter()
+        .map(|((key, mv), (_, points))| BookEntry {
+            key,
+            mv,
+            weight: (points as f64 * scale) as u16,
+            learn: 0,
+        })
+        .collect();
+
+    Ok((PolyglotBook::from_entries(entries), stats))
+}
@@ -0,0 +1,69 @@
+//! A small, seedable pseudo random number generator
+//!
+//! The generator is not suited for cryptographic purposes,
+//! but fast and reproducible, which is what the engine tools need.
+
+/// A xorshift64* pseudo random number generator
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Creates a new generator from `seed`. Equal seeds produce equal sequences
+    /// # Arguments
+    /// * `seed` - The seed to start from
+    pub fn new(seed: u64) -> Self {
+        // Scramble the seed so similar seeds produce unrelated sequences,
+        // the state must never be `0`
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        Self {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// Creates a new generator seeded from the system clock
+    pub fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Self::new(nanos)
+    }
+
+    /// Returns the next random [u64]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a random number in `0..bound`
+    /// # Arguments
+    /// * `bound` - The exclusive upper bound, must not be `0`
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Returns a random number in `range`
+    /// # Arguments
+    /// * `range` - The inclusive range to pick from
+    pub fn range(&mut self, range: std::ops::RangeInclusive<u64>) -> u64 {
+        range.start() + self.below(range.end() - range.start() + 1)
+    }
+
+    /// Picks a random element from `slice`, [None] if it is empty
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            None
+        } else {
+            slice.get(self.below(slice.len() as u64) as usize)
+        }
+    }
+}
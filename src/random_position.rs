@@ -0,0 +1,199 @@
+//! Generation of random legal positions
+//!
+//! Positions are sampled for fuzzing and puzzle creation: the pieces of a
+//! material signature are dropped on random squares and the result is kept
+//! if it is legal and meets the [Constraints]. Positions have no castling
+//! rights and no en passant square, and the same seed always leads to the
+//! same positions.
+use crate::{
+    board::Board,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::Coordinate,
+    game::GameState,
+    piece::{ColoredPiece, Piece},
+    random::Random,
+    syzygy::{Material, MaterialError},
+};
+
+/// The number of placements tried for a single position before giving up
+const MAX_ATTEMPTS: usize = 100_000;
+
+/// The number of pieces of every type both sides may have in a random position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaterialRange {
+    /// The fewest and most pieces, indexed by [Color] and [Piece]
+    pub counts: [[(u8, u8); 6]; 2],
+}
+
+/// The optional conditions a random position has to meet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// The side to move is not in check
+    pub not_in_check: bool,
+    /// The side to move has no legal capture
+    pub no_captures: bool,
+    /// Pawns may stand on the first and last rank, which cannot happen in a game
+    pub back_rank_pawns: bool,
+}
+
+/// Samples random legal positions, it ends once a position cannot be found
+#[derive(Clone, Debug)]
+pub struct PositionGenerator {
+    /// The pieces to place
+    pub material: MaterialRange,
+    /// The conditions every position meets
+    pub constraints: Constraints,
+    /// The color to move, random if [None]
+    pub side_to_move: Option<Color>,
+    random: Random,
+}
+
+impl MaterialRange {
+    /// Parses a signature like `KQPvKR` with exact piece counts,
+    /// the first side is white
+    /// # Arguments
+    /// * `signature` - The pieces of white and black separated by `v`
+    pub fn parse(signature: &str) -> Result<Self, MaterialError> {
+        let range = Self::from(Material::parse(signature)?);
+
+        if range.is_possible() {
+            Ok(range)
+        } else {
+            Err(MaterialError::Impossible(signature.to_string()))
+        }
+    }
+
+    /// Returns the range with between `min` and `max` pieces of type `piece` for `color`
+    /// # Arguments
+    /// * `color` - The color of the pieces
+    /// * `piece` - The type of the pieces, kings are always exactly one
+    /// * `min` - The fewest pieces
+    /// * `max` - The most pieces
+    pub fn with(mut self, color: Color, piece: Piece, min: u8, max: u8) -> Self {
+        if piece != Piece::King {
+            self.counts[color as usize][piece as usize] = (min, max.max(min));
+        }
+        self
+    }
+
+    /// Returns whether a legal position can have the fewest pieces of this range:
+    /// one king and at most 8 pawns and 16 pieces per side
+    pub fn is_possible(&self) -> bool {
+        self.counts.iter().all(|counts| {
+            counts[Piece::King as usize] == (1, 1)
+                && counts[Piece::Pawn as usize].0 <= 8
+                && counts.iter().map(|(min, _)| *min as u32).sum::<u32>() <= 16
+        })
+    }
+
+    /// Picks a random number of pieces within the range for every type and color
+    /// # Arguments
+    /// * `random` - The random number generator
+    fn sample(&self, random: &mut Random) -> Vec<ColoredPiece> {
+        let mut pieces = Vec::new();
+
+        for color in [Color::White, Color::Black] {
+            for piece in Piece::ALL {
+                let (min, max) = self.counts[color as usize][piece as usize];
+                let count = random.range(min as u64..=max as u64);
+                pieces.extend((0..count).map(|_| ColoredPiece { piece, color }));
+            }
+        }
+
+        pieces
+    }
+}
+
+impl From<Material> for MaterialRange {
+    fn from(value: Material) -> Self {
+        let mut counts = [[(0, 0); 6]; 2];
+
+        for color in [Color::White, Color::Black] {
+            for piece in Piece::ALL {
+                let count = value.count(color, piece);
+                counts[color as usize][piece as usize] = (count, count);
+            }
+        }
+
+        Self { counts }
+    }
+}
+
+impl PositionGenerator {
+    /// Creates a generator for positions with `material` that meet `constraints`
+    /// # Arguments
+    /// * `material` - The pieces to place
+    /// * `constraints` - The conditions every position meets
+    /// * `seed` - The seed of the random number generator
+    pub fn new(material: MaterialRange, constraints: Constraints, seed: u64) -> Self {
+        Self {
+            material,
+            constraints,
+            side_to_move: None,
+            random: Random::new(seed),
+        }
+    }
+
+    /// Samples the next random position, [None] if none was found after [MAX_ATTEMPTS] tries
+    pub fn generate(&mut self) -> Option<GameState> {
+        if !self.material.is_possible() {
+            return None;
+        }
+
+        (0..MAX_ATTEMPTS).find_map(|_| self.try_generate())
+    }
+
+    /// Places the pieces once and returns the position if it is legal and meets the constraints
+    fn try_generate(&mut self) -> Option<GameState> {
+        let side_to_move = match self.side_to_move {
+            Some(color) => color,
+            None if self.random.below(2) == 0 => Color::White,
+            None => Color::Black,
+        };
+
+        let mut board = Board::default();
+        let mut free: Vec<Coordinate> = (0..64)
+            .filter_map(|index| Coordinate::from_u8s(index % 8, index / 8))
+            .collect();
+
+        for piece in self.material.sample(&mut self.random) {
+            let allowed: Vec<usize> = (0..free.len())
+                .filter(|i| {
+                    piece.piece != Piece::Pawn
+                        || self.constraints.back_rank_pawns
+                        || (1..=6).contains(&(free[*i].rank as u8))
+                })
+                .collect();
+            let index = *self.random.choose(&allowed)?;
+            board.set(free.swap_remove(index), piece);
+        }
+
+        // The player who just moved cannot be in check
+        let waiting_king = board.find_king(side_to_move.opposite())?;
+        if board.is_attacked(waiting_king, side_to_move) {
+            return None;
+        }
+
+        let state = GameState::new(board, side_to_move, CastlingRights::default(), None, 0, 1);
+
+        if self.constraints.not_in_check && state.is_check() {
+            return None;
+        }
+        if self.constraints.no_captures
+            && state.legal_moves().iter().any(|mv| state.is_capture(*mv))
+        {
+            return None;
+        }
+
+        Some(state)
+    }
+}
+
+impl Iterator for PositionGenerator {
+    type Item = GameState;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.generate()
+    }
+}
@@ -0,0 +1,263 @@
+//! Stuff to work with moves in Standard Algebraic Notation (SAN)
+use std::fmt::Display;
+
+use crate::{
+    coordinate::Coordinate,
+    fen::{FromFENChar, FromFENString, ToFENChar, ToFENString},
+    game::GameState,
+    moves::Move,
+    piece::Piece,
+};
+
+/// Possible errors that can arise when parsing SAN strings
+#[derive(Debug)]
+pub enum FromSANError {
+    /// The string is not a move in any known notation
+    InvalidFormat(String),
+    /// The string describes a move that is not legal in the position
+    IllegalMove(String),
+    /// The string describes more than one legal move
+    AmbiguousMove {
+        /// The string that has been parsed
+        san: String,
+        /// The legal moves that match it, in SAN
+        candidates: Vec<String>,
+    },
+}
+
+impl Display for FromSANError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat(san) => write!(f, "'{san}' is not a valid move"),
+            Self::IllegalMove(san) => write!(f, "'{san}' is not a legal move"),
+            Self::AmbiguousMove { san, candidates } => write!(
+                f,
+                "'{san}' is ambiguous, it could be {}",
+                candidates.join(" or ")
+            ),
+        }
+    }
+}
+
+impl Move {
+    /// Formats this move in Standard Algebraic Notation, e.g. `Nbd7`, `exd6`, `e8=Q+`, `O-O`
+    /// or the drop `N@f3`
+    /// # Arguments
+    /// * `state` - The position the move is played in, the move has to be legal in it
+    pub fn to_san(&self, state: &GameState) -> String {
+        if self.drop.is_some() {
+            return format!("{self}{}", check_suffix(state, *self));
+        }
+
+        let Some(piece) = state.board.get(self.from) else {
+            return self.to_string();
+        };
+
+        let san = if state.is_castling(*self) {
+            if self.to.file as u8 > self.from.file as u8 {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let mut san = String::new();
+            let capture = state.is_capture(*self);
+
+            if piece.piece == Piece::Pawn {
+                if capture {
+                    san.push(file_char(self.from));
+                }
+            } else {
+                san.push(piece.piece.to_fen().to_ascii_uppercase());
+
+                // Other pieces of the same kind that can reach the target square
+                let others: Vec<Move> = state
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|mv| {
+                        mv.to == self.to
+                            && mv.from != self.from
+                            && state.board.get(mv.from) == Some(piece)
+                    })
+                    .collect();
+
+                if !others.is_empty() {
+                    if others.iter().all(|mv| mv.from.file != self.from.file) {
+                        san.push(file_char(self.from));
+                    } else if others.iter().all(|mv| mv.from.rank != self.from.rank) {
+                        san.push(rank_char(self.from));
+                    } else {
+                        san.push_str(&self.from.to_fen());
+                    }
+                }
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&self.to.to_fen());
+
+            if let Some(promotion) = self.promotion {
+                san.push('=');
+                san.push(promotion.to_fen().to_ascii_uppercase());
+            }
+
+            san
+        };
+
+        format!("{san}{}", check_suffix(state, *self))
+    }
+}
+
+/// Returns `+` if `mv` gives check, `#` if it mates and nothing otherwise
+fn check_suffix(state: &GameState, mv: Move) -> &'static str {
+    let next = state.make_move(mv);
+
+    if !next.is_check() {
+        ""
+    } else if next.legal_moves().is_empty() {
+        "#"
+    } else {
+        "+"
+    }
+}
+
+impl GameState {
+    /// Parses a move in Standard Algebraic Notation and checks it for legality.
+    ///
+    /// Common sloppy variants are accepted as well: missing or superfluous disambiguation
+    /// (`Nbd7`, `Ng1f3`), missing capture marks, promotions without `=` (`e8Q`),
+    /// castling with zeros (`0-0`), long algebraic notation (`e2e4`, `e2-e4`),
+    /// pawn drops without letter (`@e4`) and trailing annotations (`+`, `#`, `!?`)
+    /// # Arguments
+    /// * `san` - The move to parse
+    pub fn parse_san(&self, san: &str) -> Result<Move, FromSANError> {
+        let trimmed = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let invalid = || FromSANError::InvalidFormat(san.to_string());
+
+        // Drops from the pocket
+        if let Some((piece, square)) = trimmed.split_once('@') {
+            let piece = match piece {
+                "" => Piece::Pawn,
+                piece => Piece::from_fen(piece.chars().next().ok_or_else(invalid)?)
+                    .ok()
+                    .filter(|_| piece.len() == 1)
+                    .ok_or_else(invalid)?,
+            };
+            let to = Coordinate::from_fen(square).map_err(|_| invalid())?;
+            let mv = Move::new_drop(piece, to);
+
+            return if self.is_legal(mv) {
+                Ok(mv)
+            } else {
+                Err(FromSANError::IllegalMove(san.to_string()))
+            };
+        }
+
+        // Castling
+        let castling = trimmed.replace('0', "O").to_ascii_uppercase();
+        if castling == "O-O" || castling == "O-O-O" {
+            let kingside = castling == "O-O";
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|mv| {
+                    self.is_castling(*mv) && (mv.to.file as u8 > mv.from.file as u8) == kingside
+                })
+                .ok_or_else(|| FromSANError::IllegalMove(san.to_string()));
+        }
+
+        let mut chars: Vec<char> = trimmed.chars().collect();
+
+        // The moving piece, pawns have no letter
+        let piece = match chars.first() {
+            Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => {
+                let piece = Piece::from_fen(*c).map_err(|_| invalid())?;
+                chars.remove(0);
+                Some(piece)
+            }
+            Some(_) => None,
+            None => return Err(invalid()),
+        };
+
+        // The promotion, with or without '='
+        let mut promotion = None;
+        if let Some(c) = chars.last().copied() {
+            if matches!(c.to_ascii_uppercase(), 'N' | 'B' | 'R' | 'Q' | 'K')
+                && chars.len() >= 3
+                && (chars[chars.len() - 2] == '=' || chars[chars.len() - 2].is_ascii_digit())
+            {
+                promotion = Some(Piece::from_fen(c).map_err(|_| invalid())?);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        // The target square
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let target: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let target = Coordinate::from_fen(&target).map_err(|_| invalid())?;
+
+        // Optional capture or long algebraic separators
+        if matches!(chars.last(), Some('x' | ':' | '-')) {
+            chars.pop();
+        }
+
+        // Optional disambiguation by file and / or rank
+        let (mut from_file, mut from_rank) = (None, None);
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => {
+                    from_file = Some(c as u8 - b'a')
+                }
+                '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+                _ => return Err(invalid()),
+            }
+        }
+
+        // Without a piece letter it is a pawn move, unless the move
+        // is given in long algebraic notation with a complete origin square
+        let piece = match (piece, from_file, from_rank) {
+            (Some(piece), _, _) => Some(piece),
+            (None, Some(_), Some(_)) => None,
+            (None, _, _) => Some(Piece::Pawn),
+        };
+
+        let candidates: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                mv.to == target
+                    && mv.promotion == promotion
+                    && piece.is_none_or(|piece| {
+                        self.board.get(mv.from).is_some_and(|p| p.piece == piece)
+                    })
+                    && from_file.is_none_or(|file| mv.from.file as u8 == file)
+                    && from_rank.is_none_or(|rank| mv.from.rank as u8 == rank)
+            })
+            .collect();
+
+        match candidates.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(FromSANError::IllegalMove(san.to_string())),
+            _ => Err(FromSANError::AmbiguousMove {
+                san: san.to_string(),
+                candidates: candidates.iter().map(|mv| mv.to_san(self)).collect(),
+            }),
+        }
+    }
+}
+
+/// Returns the lowercase letter of the file of `coordinate`
+fn file_char(coordinate: Coordinate) -> char {
+    (b'a' + coordinate.file as u8) as char
+}
+
+/// Returns the digit of the rank of `coordinate`
+fn rank_char(coordinate: Coordinate) -> char {
+    (b'1' + coordinate.rank as u8) as char
+}
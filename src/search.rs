@@ -0,0 +1,893 @@
+//! The search for the best move in a position
+//!
+//! This is an iterative deepening alpha-beta search with a
+//! quiescence search, a transposition table, null move pruning
+//! and late move reductions. Syzygy tablebases are used at the root
+//! and for cutoffs in the tree if they are available, generated
+//! endgame tables give exact mate scores.
+//!
+//! With more than one thread, helper threads search the same position
+//! (Lazy SMP). They only share the transposition table with the main
+//! thread and skip some depths and shuffle quiet moves, so they explore
+//! different parts of the tree.
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    color::Color,
+    coordinate::Coordinate,
+    endgame::{Endgames, DTM},
+    eval::{evaluate, PIECE_VALUES},
+    game::GameState,
+    moves::Move,
+    piece::Piece,
+    syzygy::{Tablebase, WDL},
+};
+
+mod time;
+mod tt;
+pub use time::*;
+pub use tt::*;
+
+/// A score larger than any reachable score
+pub const INFINITY: i32 = 32000;
+
+/// The score of delivering mate right now
+pub const MATE: i32 = 31000;
+
+/// Scores beyond this value announce a forced mate
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+/// The maximum depth the search can reach
+pub const MAX_PLY: usize = 128;
+
+/// The score of a tablebase win right now, below any mate score
+pub const TB_WIN: i32 = MATE_BOUND - MAX_PLY as i32;
+
+/// Scores beyond this value announce a tablebase win
+pub const TB_WIN_BOUND: i32 = TB_WIN - MAX_PLY as i32;
+
+/// The largest number of search threads
+pub const MAX_THREADS: usize = 256;
+
+/// Helper threads skip depths in blocks of this size...
+const SKIP_SIZE: [u8; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+/// ...starting at this offset, indexed by the helper
+const SKIP_PHASE: [u8; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+/// The limits a search has to obey. The search stops once any limit is reached
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// The maximum depth to search to
+    pub depth: Option<u8>,
+    /// The maximum number of nodes to search
+    pub nodes: Option<u64>,
+    /// The exact time to search for
+    pub movetime: Option<Duration>,
+    /// The clock of the side to move, the time to search for is derived from it
+    pub clock: Option<Clock>,
+    /// The time lost to communication with the GUI, it is kept in reserve
+    pub move_overhead: Duration,
+    /// Only these moves are searched at the root, all if empty
+    pub search_moves: Vec<Move>,
+    /// The number of best lines to search for, `0` is treated as `1`
+    pub multi_pv: usize,
+}
+
+/// A principal variation and its score
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PVLine {
+    /// The score of the line from the point of view of the side to move
+    pub score: i32,
+    /// The moves of the line, starting with the root move
+    pub pv: Vec<Move>,
+}
+
+/// The result of a search
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    /// The best move found, [None] if there are no legal moves
+    pub best_move: Option<Move>,
+    /// The score of the best move from the point of view of the side to move
+    pub score: i32,
+    /// The depth of the last completed iteration
+    pub depth: u8,
+    /// The nodes searched in total
+    pub nodes: u64,
+    /// The principal variation, starting with the best move
+    pub pv: Vec<Move>,
+    /// The best lines with different root moves, best first.
+    /// The first line is the principal variation
+    pub lines: Vec<PVLine>,
+    /// The number of successful tablebase probes
+    pub tb_hits: u64,
+}
+
+/// The state of the search that is kept between searches
+pub struct Searcher {
+    tt: Arc<TranspositionTable>,
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    history: Vec<u64>,
+    pv: [[Option<Move>; MAX_PLY]; MAX_PLY],
+    pv_length: [usize; MAX_PLY],
+    nodes: u64,
+    limits: SearchLimits,
+    time: TimeManager,
+    stopped: bool,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    pondering: bool,
+    tablebase: Option<Arc<Tablebase>>,
+    endgames: Option<Arc<Endgames>>,
+    root_moves: Vec<Move>,
+    /// The root moves of the better lines of the current iteration
+    excluded: Vec<Move>,
+    tb_hits: u64,
+    /// The index of the thread, `0` for the main thread
+    thread_id: usize,
+    /// The searched nodes, published for the main thread
+    shared_nodes: Arc<AtomicU64>,
+    helpers: Vec<Searcher>,
+    /// The published node counts of the helpers
+    helper_nodes: Vec<Arc<AtomicU64>>,
+    /// Stops the helpers once the main thread is done
+    helper_stop: Arc<AtomicBool>,
+}
+
+impl Searcher {
+    /// Creates a new searcher with a transposition table of `hash_mb` megabytes
+    /// # Arguments
+    /// * `hash_mb` - The size of the transposition table in megabytes
+    pub fn new(hash_mb: usize) -> Self {
+        Self::with_table(Arc::new(TranspositionTable::new(hash_mb)))
+    }
+
+    /// Creates a new searcher that uses `tt`
+    fn with_table(tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            tt,
+            killers: [[None; 2]; MAX_PLY],
+            history: Vec::new(),
+            pv: [[None; MAX_PLY]; MAX_PLY],
+            pv_length: [0; MAX_PLY],
+            nodes: 0,
+            limits: SearchLimits::default(),
+            time: TimeManager::new(&SearchLimits::default()),
+            stopped: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: Arc::new(AtomicBool::new(false)),
+            pondering: false,
+            tablebase: None,
+            endgames: None,
+            root_moves: Vec::new(),
+            excluded: Vec::new(),
+            tb_hits: 0,
+            thread_id: 0,
+            shared_nodes: Arc::new(AtomicU64::new(0)),
+            helpers: Vec::new(),
+            helper_nodes: Vec::new(),
+            helper_stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Sets the number of threads to search with, including the main thread
+    /// # Arguments
+    /// * `threads` - The number of threads, between 1 and [MAX_THREADS]
+    pub fn set_threads(&mut self, threads: usize) {
+        self.helpers = (1..threads.clamp(1, MAX_THREADS))
+            .map(|thread_id| Self {
+                stop: self.helper_stop.clone(),
+                tablebase: self.tablebase.clone(),
+                endgames: self.endgames.clone(),
+                thread_id,
+                ..Self::with_table(self.tt.clone())
+            })
+            .collect();
+        self.helper_nodes = self
+            .helpers
+            .iter()
+            .map(|helper| helper.shared_nodes.clone())
+            .collect();
+    }
+
+    /// Returns the number of threads the search uses
+    pub fn threads(&self) -> usize {
+        self.helpers.len() + 1
+    }
+
+    /// Sets the endgame tablebases to use, [None] to not use any
+    /// # Arguments
+    /// * `tablebase` - The tablebases
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<Tablebase>>) {
+        for helper in &mut self.helpers {
+            helper.set_tablebase(tablebase.clone());
+        }
+        self.tablebase = tablebase;
+    }
+
+    /// Sets the generated endgame tables to use, [None] to not use any
+    /// # Arguments
+    /// * `endgames` - The endgame tables
+    pub fn set_endgames(&mut self, endgames: Option<Arc<Endgames>>) {
+        for helper in &mut self.helpers {
+            helper.set_endgames(endgames.clone());
+        }
+        self.endgames = endgames;
+    }
+
+    /// Returns the flag that stops a running search once it is set.
+    ///
+    /// The flag is not reset by the search, this is up to the caller
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Returns the flag that marks the search as pondering. The time limits
+    /// only apply once it is cleared, they are measured from that moment on
+    pub fn ponder_flag(&self) -> Arc<AtomicBool> {
+        self.ponder.clone()
+    }
+
+    /// Forgets everything learned in previous searches, e.g. for a new game
+    pub fn clear(&mut self) {
+        self.tt.clear();
+        self.killers = [[None; 2]; MAX_PLY];
+        for helper in &mut self.helpers {
+            helper.killers = [[None; 2]; MAX_PLY];
+        }
+    }
+
+    /// Searches `state` for the best move
+    /// # Arguments
+    /// * `state` - The position to search
+    /// * `history` - The hashes of the positions that led to `state`, for detecting repetitions
+    /// * `limits` - The limits of the search
+    pub fn search(
+        &mut self,
+        state: &GameState,
+        history: &[u64],
+        limits: &SearchLimits,
+    ) -> SearchResult {
+        self.search_with_info(state, history, limits, &mut |_| {})
+    }
+
+    /// Searches `state` for the best `lines` lines with different root moves
+    /// # Arguments
+    /// * `state` - The position to search
+    /// * `history` - The hashes of the positions that led to `state`, for detecting repetitions
+    /// * `limits` - The limits of the search, its [SearchLimits::multi_pv] is replaced
+    /// * `lines` - The number of lines to search for
+    /// # Returns
+    /// The lines ranked best first, fewer if there are not enough legal moves
+    pub fn analyze(
+        &mut self,
+        state: &GameState,
+        history: &[u64],
+        limits: &SearchLimits,
+        lines: usize,
+    ) -> Vec<PVLine> {
+        let limits = SearchLimits {
+            multi_pv: lines,
+            ..limits.clone()
+        };
+
+        self.search(state, history, &limits).lines
+    }
+
+    /// Searches `state` for the best move, reporting the result of every completed iteration
+    /// # Arguments
+    /// * `state` - The position to search
+    /// * `history` - The hashes of the positions that led to `state`, for detecting repetitions
+    /// * `limits` - The limits of the search
+    /// * `info` - Called with the intermediate result after each iteration
+    pub fn search_with_info(
+        &mut self,
+        state: &GameState,
+        history: &[u64],
+        limits: &SearchLimits,
+        info: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        if self.helpers.is_empty() {
+            return self.iterate(state, history, limits, info);
+        }
+
+        // The helpers run until the main thread is done
+        let helper_limits = SearchLimits {
+            depth: limits.depth,
+            search_moves: limits.search_moves.clone(),
+            multi_pv: limits.multi_pv,
+            ..Default::default()
+        };
+        let mut helpers = std::mem::take(&mut self.helpers);
+        self.helper_stop.store(false, Ordering::Relaxed);
+
+        let (mut result, helper_results) = std::thread::scope(|scope| {
+            let handles: Vec<_> = helpers
+                .iter_mut()
+                .map(|helper| {
+                    let helper_limits = &helper_limits;
+                    scope.spawn(move || helper.iterate(state, history, helper_limits, &mut |_| {}))
+                })
+                .collect();
+
+            let result = self.iterate(state, history, limits, info);
+            self.helper_stop.store(true, Ordering::Relaxed);
+
+            let helper_results: Vec<SearchResult> = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("[DEV] Helper thread panicked"))
+                .collect();
+            (result, helper_results)
+        });
+        self.helpers = helpers;
+
+        let nodes = result.nodes + helper_results.iter().map(|r| r.nodes).sum::<u64>();
+        let tb_hits = result.tb_hits + helper_results.iter().map(|r| r.tb_hits).sum::<u64>();
+
+        // Prefer the deepest completed iteration, then the best score
+        for helper_result in helper_results {
+            if helper_result.best_move.is_some()
+                && (helper_result.depth, helper_result.score) > (result.depth, result.score)
+            {
+                result = helper_result;
+            }
+        }
+
+        result.nodes = nodes;
+        result.tb_hits = tb_hits;
+        result
+    }
+
+    /// Runs the iterative deepening loop of a single thread
+    fn iterate(
+        &mut self,
+        state: &GameState,
+        history: &[u64],
+        limits: &SearchLimits,
+        info: &mut dyn FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.history = history.to_vec();
+        self.nodes = 0;
+        self.tb_hits = 0;
+        self.limits = limits.clone();
+        self.time = TimeManager::new(limits);
+        self.stopped = false;
+        self.pondering = self.ponder.load(Ordering::Relaxed);
+
+        self.root_moves = self.root_moves(state, limits);
+
+        let mut result = SearchResult {
+            best_move: self.root_moves.first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            lines: Vec::new(),
+            tb_hits: 0,
+        };
+
+        if result.best_move.is_none() {
+            result.score = if state.is_check() { -MATE } else { 0 };
+            return result;
+        }
+
+        let max_depth = limits
+            .depth
+            .unwrap_or(MAX_PLY as u8 - 1)
+            .min(MAX_PLY as u8 - 1);
+
+        for depth in 1..=max_depth {
+            if self.thread_id > 0 && depth < max_depth {
+                let i = (self.thread_id - 1) % SKIP_SIZE.len();
+                if !((depth + SKIP_PHASE[i]) / SKIP_SIZE[i]).is_multiple_of(2) {
+                    continue;
+                }
+            }
+
+            // Each line excludes the root moves of the better lines
+            let mut lines = Vec::new();
+            self.excluded.clear();
+            while lines.len() < limits.multi_pv.clamp(1, self.root_moves.len()) {
+                let score = self.negamax(state, depth as i32, -INFINITY, INFINITY, 0, true);
+                if self.stopped {
+                    break;
+                }
+
+                let pv: Vec<Move> = self.pv[0][..self.pv_length[0]]
+                    .iter()
+                    .map_while(|mv| *mv)
+                    .collect();
+                let Some(root_move) = pv.first() else {
+                    break;
+                };
+                self.excluded.push(*root_move);
+                lines.push(PVLine { score, pv });
+            }
+            self.excluded.clear();
+
+            // An unfinished iteration cannot be trusted
+            if self.stopped || lines.is_empty() {
+                break;
+            }
+
+            lines.sort_by_key(|line| std::cmp::Reverse(line.score));
+            let score = lines[0].score;
+            result.score = score;
+            result.depth = depth;
+            result.pv = lines[0].pv.clone();
+            result.best_move = result.pv.first().copied();
+            result.lines = lines;
+            result.nodes = self.total_nodes();
+            result.tb_hits = self.tb_hits;
+            info(&result);
+
+            log::debug!(
+                "depth {depth} score {score} nodes {} pv {}",
+                self.nodes,
+                result
+                    .pv
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            );
+
+            // A found mate will not get any shorter
+            if score.abs() >= MATE_BOUND && (MATE - score.abs()) <= depth as i32 {
+                break;
+            }
+
+            self.time.update(result.best_move, score);
+            if !self.pondering() && self.time.soft_limit_reached() {
+                break;
+            }
+        }
+
+        self.shared_nodes.store(self.nodes, Ordering::Relaxed);
+        result.nodes = self.nodes;
+        result.tb_hits = self.tb_hits;
+        result
+    }
+
+    /// Returns the nodes searched by this thread and its helpers so far
+    fn total_nodes(&self) -> u64 {
+        self.nodes
+            + self
+                .helper_nodes
+                .iter()
+                .map(|nodes| nodes.load(Ordering::Relaxed))
+                .sum::<u64>()
+    }
+
+    /// Collects the moves to search at the root. If the position is in the
+    /// tablebases, only the moves that keep the best result are kept
+    fn root_moves(&mut self, state: &GameState, limits: &SearchLimits) -> Vec<Move> {
+        let mut moves = state.legal_moves();
+
+        if !limits.search_moves.is_empty() {
+            let allowed: Vec<Move> = moves
+                .iter()
+                .filter(|mv| limits.search_moves.contains(mv))
+                .copied()
+                .collect();
+            if !allowed.is_empty() {
+                moves = allowed;
+            }
+        }
+
+        let Some(tablebase) = &self.tablebase else {
+            return moves;
+        };
+
+        // Repetitions since the last zeroing move make the 50 move rule matter
+        let recent: Vec<u64> = self
+            .history
+            .iter()
+            .rev()
+            .take(state.halfmove_clock as usize)
+            .copied()
+            .collect();
+        let repeated = recent
+            .iter()
+            .enumerate()
+            .any(|(i, hash)| *hash == state.hash() || recent[i + 1..].contains(hash));
+
+        if let Some(ranked) = tablebase.rank_root_moves(state, repeated) {
+            self.tb_hits += 1;
+
+            let best = ranked
+                .iter()
+                .filter(|m| moves.contains(&m.mv))
+                .map(|m| m.rank)
+                .max();
+            moves.retain(|mv| ranked.iter().any(|m| m.mv == *mv && Some(m.rank) == best));
+        }
+
+        moves
+    }
+
+    /// Checks whether the limits have been reached
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.stopped = true;
+        }
+
+        if self.nodes.is_multiple_of(2048) {
+            self.shared_nodes.store(self.nodes, Ordering::Relaxed);
+
+            // The node limit counts the nodes of all threads
+            if self.stop.load(Ordering::Relaxed)
+                || (!self.pondering() && self.time.hard_limit_reached())
+                || self
+                    .limits
+                    .nodes
+                    .is_some_and(|nodes| self.total_nodes() >= nodes)
+            {
+                self.stopped = true;
+            }
+        }
+
+        self.stopped
+    }
+
+    /// Checks whether the search is still pondering, the clock starts once it stops
+    fn pondering(&mut self) -> bool {
+        if self.pondering && !self.ponder.load(Ordering::Relaxed) {
+            self.pondering = false;
+            self.time.restart();
+        }
+
+        self.pondering
+    }
+
+    /// Checks whether `state` repeats an earlier position that can still be reached
+    fn is_repetition(&self, state: &GameState) -> bool {
+        self.history
+            .iter()
+            .rev()
+            .take(state.halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|hash| *hash == state.hash())
+    }
+
+    /// The main alpha-beta search
+    fn negamax(
+        &mut self,
+        state: &GameState,
+        mut depth: i32,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+        allow_null: bool,
+    ) -> i32 {
+        self.pv_length[ply] = ply;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        let is_pv = beta - alpha > 1;
+
+        if ply > 0 {
+            if let Some(outcome) = state.variant_outcome() {
+                return variant_score(state, outcome.winner(), ply);
+            }
+
+            if state.halfmove_clock >= 100 || self.is_repetition(state) {
+                return 0;
+            }
+
+            if ply >= MAX_PLY - 1 {
+                return evaluate(state);
+            }
+
+            if let Some(dtm) = self.endgames.as_ref().and_then(|e| e.probe(state)) {
+                self.tb_hits += 1;
+                return match dtm {
+                    DTM::Win(plies) => MATE - ply as i32 - plies as i32,
+                    DTM::Loss(plies) => -MATE + ply as i32 + plies as i32,
+                    DTM::Draw => 0,
+                };
+            }
+
+            // After captures and pawn moves the tablebases know the exact result
+            if state.halfmove_clock == 0 {
+                let wdl = self.tablebase.as_ref().and_then(|tb| tb.probe_wdl(state));
+                if let Some(wdl) = wdl {
+                    self.tb_hits += 1;
+                    return match wdl {
+                        WDL::Win => TB_WIN - ply as i32,
+                        WDL::Loss => -TB_WIN + ply as i32,
+                        _ => 0,
+                    };
+                }
+            }
+        }
+
+        let in_check = state.is_check();
+        if in_check {
+            depth += 1;
+        }
+
+        if depth <= 0 {
+            return self.quiescence(state, alpha, beta, ply);
+        }
+
+        self.nodes += 1;
+
+        let entry = self.tt.probe(state.hash());
+        if let Some(entry) = entry {
+            if ply > 0 && !is_pv && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        // Null move pruning: if passing still fails high, a real move will too
+        if allow_null && !is_pv && !in_check && depth >= 3 && has_pieces(state) {
+            let static_eval = evaluate(state);
+            if static_eval >= beta {
+                self.history.push(state.hash());
+                let score = -self.negamax(
+                    &state.make_null_move(),
+                    depth - 3,
+                    -beta,
+                    -beta + 1,
+                    ply + 1,
+                    false,
+                );
+                self.history.pop();
+
+                if self.stopped {
+                    return 0;
+                }
+                if score >= beta && score < MATE_BOUND {
+                    return beta;
+                }
+            }
+        }
+
+        let mut moves = state.legal_moves();
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+        if ply == 0 {
+            moves.retain(|mv| self.root_moves.contains(mv) && !self.excluded.contains(mv));
+        }
+
+        let tt_move = entry.and_then(|entry| entry.best_move);
+        self.order_moves(state, &mut moves, tt_move, ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        self.history.push(state.hash());
+
+        for (i, mv) in moves.iter().enumerate() {
+            let next = state.make_move(*mv);
+            let quiet = !state.is_capture(*mv) && mv.promotion.is_none();
+
+            let score = if i == 0 {
+                -self.negamax(&next, depth - 1, -beta, -alpha, ply + 1, true)
+            } else {
+                // Search late quiet moves with reduced depth first
+                let reduction = if depth >= 3 && i >= 4 && quiet && !in_check {
+                    1 + (i >= 12) as i32
+                } else {
+                    0
+                };
+
+                let mut score = -self.negamax(
+                    &next,
+                    depth - 1 - reduction,
+                    -alpha - 1,
+                    -alpha,
+                    ply + 1,
+                    true,
+                );
+
+                if score > alpha && reduction > 0 {
+                    score = -self.negamax(&next, depth - 1, -alpha - 1, -alpha, ply + 1, true);
+                }
+                if score > alpha && score < beta {
+                    score = -self.negamax(&next, depth - 1, -beta, -alpha, ply + 1, true);
+                }
+
+                score
+            };
+
+            if self.stopped {
+                self.history.pop();
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(*mv);
+
+                if score > alpha {
+                    alpha = score;
+
+                    self.pv[ply][ply] = Some(*mv);
+                    for next_ply in ply + 1..self.pv_length[ply + 1] {
+                        self.pv[ply][next_ply] = self.pv[ply + 1][next_ply];
+                    }
+                    self.pv_length[ply] = self.pv_length[ply + 1].max(ply + 1);
+                }
+            }
+
+            if alpha >= beta {
+                if quiet && self.killers[ply][0] != Some(*mv) {
+                    self.killers[ply][1] = self.killers[ply][0];
+                    self.killers[ply][0] = Some(*mv);
+                }
+                break;
+            }
+        }
+
+        self.history.pop();
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        self.tt.store(Entry {
+            hash: state.hash(),
+            best_move,
+            score: score_to_tt(best_score, ply),
+            depth,
+            bound,
+        });
+
+        best_score
+    }
+
+    /// Searches captures and promotions until the position is quiet
+    fn quiescence(&mut self, state: &GameState, mut alpha: i32, beta: i32, ply: usize) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if let Some(outcome) = state.variant_outcome() {
+            return variant_score(state, outcome.winner(), ply);
+        }
+
+        let stand_pat = evaluate(state);
+        if stand_pat >= beta || ply >= MAX_PLY - 1 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves: Vec<Move> = state
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| state.is_capture(*mv) || mv.promotion == Some(Piece::Queen))
+            .collect();
+        self.order_moves(state, &mut moves, None, ply);
+
+        let mut best_score = stand_pat;
+
+        for mv in moves {
+            let score = -self.quiescence(&state.make_move(mv), -beta, -alpha, ply + 1);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                alpha = alpha.max(score);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+
+    /// Sorts `moves` so the most promising ones are searched first
+    fn order_moves(
+        &self,
+        state: &GameState,
+        moves: &mut [Move],
+        tt_move: Option<Move>,
+        ply: usize,
+    ) {
+        moves.sort_by_cached_key(|mv| {
+            if Some(*mv) == tt_move {
+                return i32::MIN;
+            }
+
+            let mut score = 0;
+
+            if state.is_capture(*mv) {
+                // Most valuable victim, least valuable attacker
+                let victim = state.board.get(mv.to).map_or(Piece::Pawn, |p| p.piece);
+                let attacker = state.board.get(mv.from).map_or(Piece::Pawn, |p| p.piece);
+                score += 100_000 + PIECE_VALUES[victim as usize] * 10
+                    - PIECE_VALUES[attacker as usize] / 10;
+            } else if self.killers[ply].contains(&Some(*mv)) {
+                score += 50_000;
+            } else if self.thread_id > 0 {
+                // Helpers try quiet moves in a different order than the main thread
+                let square = |c: Coordinate| c.rank as u64 * 8 + c.file as u64;
+                let key = (square(mv.from) << 6 | square(mv.to)) ^ self.thread_id as u64;
+                score += (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 58) as i32;
+            }
+
+            if let Some(promotion) = mv.promotion {
+                score += 200_000 + PIECE_VALUES[promotion as usize];
+            }
+
+            -score
+        });
+    }
+}
+
+/// Checks whether the side to move has pieces besides pawns and the king,
+/// as null moves are unsafe in pawn endgames because of zugzwang
+fn has_pieces(state: &GameState) -> bool {
+    state
+        .board
+        .get_occupied_fields_fm()
+        .iter()
+        .any(|(_, piece)| {
+            piece.color == state.side_to_move && !matches!(piece.piece, Piece::Pawn | Piece::King)
+        })
+}
+
+/// Scores a game decided by a rule of the variant like a mate `ply` plies from the root,
+/// or as a draw if there is no `winner`
+fn variant_score(state: &GameState, winner: Option<Color>, ply: usize) -> i32 {
+    match winner {
+        Some(winner) if winner == state.side_to_move => MATE - ply as i32,
+        Some(_) => -MATE + ply as i32,
+        None => 0,
+    }
+}
+
+/// Converts a mate or tablebase score relative to the root into one relative to the current position
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= TB_WIN_BOUND {
+        score + ply as i32
+    } else if score <= -TB_WIN_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Converts a mate or tablebase score relative to the stored position into one relative to the root
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= TB_WIN_BOUND {
+        score - ply as i32
+    } else if score <= -TB_WIN_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
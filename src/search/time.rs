@@ -0,0 +1,136 @@
+//! Budgeting the thinking time of a search
+//!
+//! Under a time control the search gets a soft limit, after which no new
+//! iteration is started, and a hard limit, at which it is stopped right away.
+//! The soft limit is stretched while the best move keeps changing or the
+//! score drops, but never beyond the hard limit.
+use std::time::{Duration, Instant};
+
+use crate::moves::Move;
+
+use super::SearchLimits;
+
+/// The number of moves the remaining time is spread over in sudden death
+const SUDDEN_DEATH_MOVES: u32 = 30;
+
+/// The soft limit is never stretched more than this
+const MAX_SCALE: f64 = 3.0;
+
+/// The clock of the side to move
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Clock {
+    /// The time left on the clock
+    pub time: Duration,
+    /// The time added after each move
+    pub increment: Duration,
+    /// The moves until the next time control, [None] for sudden death
+    pub moves_to_go: Option<u32>,
+}
+
+/// Decides when a search has to stop
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    start: Instant,
+    /// No iteration is started after this time, before scaling
+    soft: Option<Duration>,
+    /// The search stops at this time
+    hard: Option<Duration>,
+    /// The factor the soft limit is scaled by
+    scale: f64,
+    /// How often the best move changed recently, older changes count less
+    instability: f64,
+    best_move: Option<Move>,
+    score: Option<i32>,
+}
+
+impl TimeManager {
+    /// Creates a time manager for a search with `limits`, starting now
+    /// # Arguments
+    /// * `limits` - The limits of the search
+    pub fn new(limits: &SearchLimits) -> Self {
+        let (soft, hard) = match (limits.movetime, limits.clock) {
+            (Some(movetime), _) => (None, Some(Self::after_overhead(movetime, limits))),
+            (None, Some(clock)) => {
+                let left = Self::after_overhead(clock.time, limits);
+                let moves = clock.moves_to_go.unwrap_or(SUDDEN_DEATH_MOVES).max(1);
+
+                // With the last move before the time control most of the time can be used
+                let max = if moves == 1 {
+                    left.mul_f64(0.9)
+                } else {
+                    left.mul_f64(0.75)
+                };
+                let share = left / moves + clock.increment.mul_f64(0.75);
+
+                (Some(share.min(max)), Some((share * 4).min(max)))
+            }
+            (None, None) => (None, None),
+        };
+
+        Self {
+            start: Instant::now(),
+            soft,
+            hard,
+            scale: 1.0,
+            instability: 0.0,
+            best_move: None,
+            score: None,
+        }
+    }
+
+    /// Subtracts the move overhead from `time`, leaving at least a millisecond
+    fn after_overhead(time: Duration, limits: &SearchLimits) -> Duration {
+        time.saturating_sub(limits.move_overhead)
+            .max(Duration::from_millis(1))
+    }
+
+    /// Starts measuring the time again, e.g. when a pondered move was played
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// Returns the time since the start
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Returns the current soft limit, [None] if there is none
+    pub fn soft_limit(&self) -> Option<Duration> {
+        let soft = self.soft?.mul_f64(self.scale);
+
+        Some(self.hard.map_or(soft, |hard| soft.min(hard)))
+    }
+
+    /// Returns the hard limit, [None] if there is none
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard
+    }
+
+    /// Adjusts the soft limit to the result of a completed iteration
+    /// # Arguments
+    /// * `best_move` - The best move of the iteration
+    /// * `score` - The score of the iteration
+    pub fn update(&mut self, best_move: Option<Move>, score: i32) {
+        let changed = self.best_move.is_some() && self.best_move != best_move;
+        self.instability = self.instability * 0.5 + if changed { 1.0 } else { 0.0 };
+
+        // A falling score means there is trouble ahead
+        let drop = self
+            .score
+            .map_or(0, |previous| (previous - score).clamp(0, 100));
+
+        self.scale = ((1.0 + self.instability) * (1.0 + drop as f64 / 100.0)).min(MAX_SCALE);
+        self.best_move = best_move;
+        self.score = Some(score);
+    }
+
+    /// Checks whether there is not enough time left for another iteration
+    pub fn soft_limit_reached(&self) -> bool {
+        self.soft_limit().is_some_and(|soft| self.elapsed() >= soft)
+    }
+
+    /// Checks whether the search has to stop immediately
+    pub fn hard_limit_reached(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+}
@@ -0,0 +1,159 @@
+//! The transposition table
+//!
+//! The table is shared by all search threads without locking. Each slot
+//! stores the entry packed into one word next to the hash XORed with that
+//! word, so an entry torn by concurrent writes does not match its hash.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{coordinate::Coordinate, moves::Move, piece::Piece};
+
+/// The kind of bound a stored score represents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The real score is at least the stored one (fail high)
+    Lower,
+    /// The real score is at most the stored one (fail low)
+    Upper,
+}
+
+/// A single entry in the [TranspositionTable]
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    /// The full hash of the position, to detect index collisions
+    pub hash: u64,
+    /// The best move found for the position
+    pub best_move: Option<Move>,
+    /// The score, with mate scores relative to the position
+    pub score: i32,
+    /// The depth the position has been searched to
+    pub depth: i32,
+    /// The bound the score represents
+    pub bound: Bound,
+}
+
+/// A slot of the table, both words are `0` while it is empty
+#[derive(Debug, Default)]
+struct Slot {
+    /// The hash of the position XORed with the data
+    key: AtomicU64,
+    /// The packed entry
+    data: AtomicU64,
+}
+
+/// A hash table storing results of previously searched positions
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+impl Entry {
+    /// Packs everything except the hash into a word that is never `0`
+    fn pack(&self) -> u64 {
+        let mv = self.best_move.map_or(0, |mv| {
+            let square = |c: Coordinate| c.rank as u64 * 8 + c.file as u64;
+            // Drops keep the dropped piece in the promotion bits and start on their target
+            let promotion = mv.promotion.or(mv.drop).map_or(0, |p| p as u64 + 1);
+
+            1 << 15 | square(mv.from) << 9 | square(mv.to) << 3 | promotion
+        });
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        let score = self.score.clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16 as u64;
+        let depth = self.depth.clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16 as u64;
+
+        mv | score << 16 | depth << 32 | bound << 48
+    }
+
+    /// Unpacks an entry of the position with `hash` from `data`
+    fn unpack(hash: u64, data: u64) -> Self {
+        let square = |bits: u64| {
+            Coordinate::from_u8s((bits & 7) as u8, ((bits >> 3) & 7) as u8)
+                .expect("[DEV] Internal File and Rank mishap")
+        };
+        let best_move = (data & 1 << 15 != 0).then(|| {
+            let from = square(data >> 9);
+            let to = square(data >> 3);
+            let piece = match data & 7 {
+                0 => None,
+                p => Some(Piece::ALL[p as usize - 1]),
+            };
+
+            match piece {
+                Some(piece) if from == to => Move::new_drop(piece, to),
+                promotion => Move {
+                    from,
+                    to,
+                    promotion,
+                    drop: None,
+                },
+            }
+        });
+
+        Self {
+            hash,
+            best_move,
+            score: (data >> 16) as u16 as i16 as i32,
+            depth: (data >> 32) as u16 as i16 as i32,
+            bound: match (data >> 48) & 3 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+        }
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a new table that uses roughly `megabytes` of memory
+    /// # Arguments
+    /// * `megabytes` - The size of the table
+    pub fn new(megabytes: usize) -> Self {
+        let count = (megabytes.max(1) * 1024 * 1024 / size_of::<Slot>()).max(1);
+
+        Self {
+            slots: (0..count).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    /// Removes all entries from the table
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Looks up the entry for `hash`
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed);
+
+        (data != 0 && key ^ data == hash).then(|| Entry::unpack(hash, data))
+    }
+
+    /// Stores `entry`, replacing entries of other positions or of lower depth
+    pub fn store(&self, entry: Entry) {
+        let slot = &self.slots[self.index(entry.hash)];
+
+        let replace = match self.probe(entry.hash) {
+            Some(existing) => existing.depth <= entry.depth,
+            None => true,
+        };
+
+        if replace {
+            let data = entry.pack();
+            slot.key.store(entry.hash ^ data, Ordering::Relaxed);
+            slot.data.store(data, Ordering::Relaxed);
+        }
+    }
+
+    /// Maps `hash` to an index into the slots
+    fn index(&self, hash: u64) -> usize {
+        ((hash as u128 * self.slots.len() as u128) >> 64) as usize
+    }
+}
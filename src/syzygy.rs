@@ -0,0 +1,488 @@
+//! Probing of Syzygy endgame tablebases
+//!
+//! Win / draw / loss (`.rtbw`) and distance to zeroing (`.rtbz`) files are
+//! looked up in the configured directories. Files are only read once a
+//! position with their material is probed for the first time.
+use std::{collections::HashMap, fmt::Display, path::PathBuf, sync::OnceLock};
+
+use crate::{
+    error::{Error, ErrorExt},
+    game::GameState,
+    moves::Move,
+    piece::Piece,
+    str,
+    variant::Variant,
+};
+
+mod table;
+pub use table::{Material, MaterialError, MAX_PIECES};
+use table::{Table, TableKind, TableValue};
+
+/// The win / draw / loss value of a position from the side to move's point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WDL {
+    /// The position is lost
+    Loss = -2,
+    /// The position is lost, but drawn by the 50 move rule
+    BlessedLoss = -1,
+    /// The position is drawn
+    Draw = 0,
+    /// The position is won, but drawn by the 50 move rule
+    CursedWin = 1,
+    /// The position is won
+    Win = 2,
+}
+
+/// A root move ranked by the tablebases
+#[derive(Clone, Copy, Debug)]
+pub struct RootMove {
+    /// The move
+    pub mv: Move,
+    /// The distance to zeroing in plies after playing the move, counted from the root.
+    /// Positive if the move wins, negative if it loses and `0` for a draw
+    pub dtz: i32,
+    /// The rank of the move, better moves have a higher rank
+    pub rank: i32,
+}
+
+/// The files of one material signature
+struct TableFiles {
+    wdl: Option<PathBuf>,
+    dtz: Option<PathBuf>,
+    wdl_table: OnceLock<Option<Table>>,
+    dtz_table: OnceLock<Option<Table>>,
+}
+
+/// A set of Syzygy tables
+pub struct Tablebase {
+    tables: HashMap<String, TableFiles>,
+    max_pieces: usize,
+}
+
+impl WDL {
+    fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            -2 => Some(Self::Loss),
+            -1 => Some(Self::BlessedLoss),
+            0 => Some(Self::Draw),
+            1 => Some(Self::CursedWin),
+            2 => Some(Self::Win),
+            _ => None,
+        }
+    }
+
+    /// Returns the value from the opponent's point of view
+    pub fn negate(self) -> Self {
+        Self::from_i32(-(self as i32)).expect("[DEV] WDL negation mishap")
+    }
+}
+
+impl Display for WDL {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Loss => write!(f, "loss"),
+            Self::BlessedLoss => write!(f, "blessed loss"),
+            Self::Draw => write!(f, "draw"),
+            Self::CursedWin => write!(f, "cursed win"),
+            Self::Win => write!(f, "win"),
+        }
+    }
+}
+
+/// An error that occurred while looking up or generating tables
+#[derive(Debug)]
+pub enum TablebaseError {
+    /// The position is not covered by the loaded tables
+    NotFound {
+        /// The number of material signatures with tables
+        tables: usize,
+        /// The largest number of pieces there are tables for
+        max_pieces: usize,
+    },
+    /// Tables cannot be generated for the material signature
+    UnsupportedMaterial {
+        /// The material signature
+        material: String,
+        /// The largest number of pieces tables can be generated for
+        max_pieces: usize,
+    },
+}
+
+impl Display for TablebaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { tables, max_pieces } => write!(
+                f,
+                "The position is not in the tablebases ({tables} tables with up to {max_pieces} pieces)"
+            ),
+            Self::UnsupportedMaterial {
+                material,
+                max_pieces,
+            } => write!(
+                f,
+                "Tables can only be generated for 3 to {max_pieces} pieces, kings included, not for '{material}'"
+            ),
+        }
+    }
+}
+
+/// The DTZ of a position whose best move zeroes the 50 move counter
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+impl Tablebase {
+    /// Creates an empty set of tables
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+            max_pieces: 0,
+        }
+    }
+
+    /// Creates a set of tables from the directories in `path`,
+    /// separated like the `PATH` environment variable
+    /// # Arguments
+    /// * `path` - The directories to search for table files
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let mut tablebase = Self::new();
+
+        for directory in std::env::split_paths(path) {
+            if directory.as_os_str().is_empty() {
+                continue;
+            }
+            tablebase.add_directory(directory)?;
+        }
+
+        Ok(tablebase)
+    }
+
+    /// Adds all table files in `directory`
+    /// # Arguments
+    /// * `directory` - The directory to search for table files
+    pub fn add_directory(&mut self, directory: PathBuf) -> Result<(), Error> {
+        let entries = std::fs::read_dir(&directory)
+            .ctx(str!("Reading tablebase directory {}", directory.display()))?;
+
+        for entry in entries {
+            let path = entry
+                .ctx(str!("Reading tablebase directory {}", directory.display()))?
+                .path();
+
+            let kind = match path.extension().and_then(|e| e.to_str()) {
+                Some("rtbw") => TableKind::Wdl,
+                Some("rtbz") => TableKind::Dtz,
+                _ => continue,
+            };
+            let Some(material) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|name| Material::parse(name).ok())
+                .filter(|material| material.piece_count() <= MAX_PIECES)
+            else {
+                continue;
+            };
+
+            let files = self
+                .tables
+                .entry(material.name())
+                .or_insert_with(|| TableFiles {
+                    wdl: None,
+                    dtz: None,
+                    wdl_table: OnceLock::new(),
+                    dtz_table: OnceLock::new(),
+                });
+            match kind {
+                TableKind::Wdl => files.wdl = Some(path),
+                TableKind::Dtz => files.dtz = Some(path),
+            }
+
+            if kind == TableKind::Wdl {
+                self.max_pieces = self.max_pieces.max(material.piece_count());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the largest number of pieces there are WDL tables for
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Returns the number of material signatures with tables
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Checks whether `state` can be probed at all, the tables only cover standard chess
+    fn probeable(&self, state: &GameState) -> bool {
+        state.variant == Variant::Standard
+            && state.castling.bits() == 0
+            && Material::of(state).piece_count() <= self.max_pieces
+    }
+
+    /// Probes the WDL value of `state`
+    /// # Returns
+    /// [None] if there is no table for the position or it has castling rights
+    pub fn probe_wdl(&self, state: &GameState) -> Option<WDL> {
+        if !self.probeable(state) {
+            return None;
+        }
+
+        WDL::from_i32(self.search(state, false)?.0)
+    }
+
+    /// Probes the distance to zeroing the 50 move counter of `state` in plies
+    /// # Returns
+    /// The DTZ, positive if the side to move wins and negative if it loses.
+    /// Values beyond 100 mean the result is reached too late for the 50 move rule.
+    /// [None] if there is no table for the position or it has castling rights
+    pub fn probe_dtz(&self, state: &GameState) -> Option<i32> {
+        if !self.probeable(state) {
+            return None;
+        }
+
+        self.dtz(state)
+    }
+
+    /// Ranks the legal moves of `state`, best first. Without DTZ tables
+    /// the moves are only ranked by their WDL value
+    /// # Arguments
+    /// * `state` - The root position
+    /// * `repeated` - Whether a position has repeated since the last zeroing move
+    /// # Returns
+    /// [None] if the position or any position after a move cannot be probed
+    pub fn rank_root_moves(&self, state: &GameState, repeated: bool) -> Option<Vec<RootMove>> {
+        if !self.probeable(state) {
+            return None;
+        }
+
+        let mut moves = self
+            .rank_by_dtz(state, repeated)
+            .or_else(|| self.rank_by_wdl(state))?;
+
+        moves.sort_by_key(|m| std::cmp::Reverse(m.rank));
+        Some(moves)
+    }
+
+    /// Ranks the root moves by their DTZ
+    fn rank_by_dtz(&self, state: &GameState, repeated: bool) -> Option<Vec<RootMove>> {
+        let halfmove_clock = state.halfmove_clock as i32;
+        let mut moves = Vec::new();
+
+        for mv in state.legal_moves() {
+            let next = state.make_move(mv);
+
+            let mut dtz = if next.halfmove_clock == 0 {
+                dtz_before_zeroing(-self.search(&next, false)?.0)
+            } else {
+                let dtz = -self.dtz(&next)?;
+                dtz + dtz.signum()
+            };
+
+            // A mating move has a DTZ of 1
+            if dtz == 2 && next.is_check() && next.legal_moves().is_empty() {
+                dtz = 1;
+            }
+
+            // Certain wins are ranked equally, unless the 50 move rule comes close
+            let rank = if dtz > 0 {
+                if dtz + halfmove_clock <= 99 && !repeated {
+                    1000
+                } else {
+                    1000 - (dtz + halfmove_clock)
+                }
+            } else if dtz < 0 {
+                if -dtz * 2 + halfmove_clock < 100 {
+                    -1000
+                } else {
+                    -1000 + (-dtz + halfmove_clock)
+                }
+            } else {
+                0
+            };
+
+            moves.push(RootMove { mv, dtz, rank });
+        }
+
+        Some(moves)
+    }
+
+    /// Ranks the root moves by their WDL value
+    fn rank_by_wdl(&self, state: &GameState) -> Option<Vec<RootMove>> {
+        state
+            .legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let wdl = -self.search(&state.make_move(mv), false)?.0;
+                let rank = match wdl {
+                    2 => 1000,
+                    1 => 899,
+                    -1 => -899,
+                    -2 => -1000,
+                    _ => 0,
+                };
+
+                Some(RootMove {
+                    mv,
+                    dtz: dtz_before_zeroing(wdl),
+                    rank,
+                })
+            })
+            .collect()
+    }
+
+    /// Looks up `state` in its table
+    fn probe_table(&self, state: &GameState, kind: TableKind, wdl: i32) -> Option<TableValue> {
+        let material = Material::of(state);
+        if material.piece_count() == 2 {
+            return Some(TableValue::Value(0));
+        }
+
+        // Tables are named with the stronger side first
+        let (files, black_stronger) = match self.tables.get(&material.name()) {
+            Some(files) => (files, false),
+            None => (self.tables.get(&material.flipped().name())?, true),
+        };
+
+        let (path, table) = match kind {
+            TableKind::Wdl => (&files.wdl, &files.wdl_table),
+            TableKind::Dtz => (&files.dtz, &files.dtz_table),
+        };
+        let table = table.get_or_init(|| {
+            let path = path.as_ref()?;
+            let data = match std::fs::read(path) {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("Failed to read {}: {e}", path.display());
+                    return None;
+                }
+            };
+
+            let name = if black_stronger {
+                material.flipped()
+            } else {
+                material
+            };
+            let table = Table::parse(kind, &name, data);
+            if table.is_none() {
+                log::warn!("Corrupted table {}", path.display());
+            }
+            table
+        });
+
+        table.as_ref()?.probe(state, black_stronger, wdl)
+    }
+
+    /// Probes the WDL table, resolving the "don't care" values stored for
+    /// positions where a capture (or for DTZ a pawn move) is best
+    /// # Returns
+    /// The WDL value and whether the best move zeroes the 50 move counter
+    fn search(&self, state: &GameState, check_zeroing: bool) -> Option<(i32, bool)> {
+        let moves = state.legal_moves();
+        let mut best = -2;
+        let mut searched = 0;
+
+        for mv in &moves {
+            let pawn_move = state
+                .board
+                .get(mv.from)
+                .is_some_and(|p| p.piece == Piece::Pawn);
+            let zeroing = state.is_capture(*mv) || (check_zeroing && pawn_move);
+            if !zeroing {
+                continue;
+            }
+            searched += 1;
+
+            let value = -self.search(&state.make_move(*mv), false)?.0;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        let exhausted = searched > 0 && searched == moves.len();
+        let value = if exhausted {
+            best
+        } else {
+            match self.probe_table(state, TableKind::Wdl, 0)? {
+                TableValue::Value(value) => value,
+                TableValue::ChangeSideToMove => return None,
+            }
+        };
+
+        if best >= value {
+            Some((best, best > 0 || exhausted))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    /// Probes the DTZ of `state`, see [Self::probe_dtz]
+    fn dtz(&self, state: &GameState) -> Option<i32> {
+        let (wdl, zeroing) = self.search(state, true)?;
+
+        if wdl == 0 {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.probe_table(state, TableKind::Dtz, wdl)? {
+            TableValue::Value(dtz) => {
+                let cursed = (wdl == 1 || wdl == -1) as i32;
+                return Some((dtz + 100 * cursed) * wdl.signum());
+            }
+            TableValue::ChangeSideToMove => {}
+        }
+
+        // The table stores the other side to move, take the best move instead
+        let mut min_dtz = i32::MAX;
+
+        for mv in state.legal_moves() {
+            let zeroing = state.is_capture(mv)
+                || state
+                    .board
+                    .get(mv.from)
+                    .is_some_and(|p| p.piece == Piece::Pawn);
+            let next = state.make_move(mv);
+
+            let mut dtz = if zeroing {
+                -dtz_before_zeroing(self.search(&next, false)?.0)
+            } else {
+                -self.dtz(&next)?
+            };
+
+            if dtz == 1 && next.is_check() && next.legal_moves().is_empty() {
+                min_dtz = 1;
+            }
+
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+
+        // Without legal moves the position is mate
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+}
+
+impl Default for Tablebase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,982 @@
+//! Decoding of single Syzygy table files
+//!
+//! A table file stores one value per position of its material signature.
+//! Positions are mapped to an index using the symmetries of the board and
+//! the values are compressed using recursive pairing and canonical Huffman codes.
+use std::{fmt::Display, sync::OnceLock};
+
+use crate::{color::Color, game::GameState, piece::Piece};
+
+/// The maximum number of pieces a table can hold
+pub const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+/// The DTZ table stores the positions for black to move
+const FLAG_STM: u8 = 1;
+/// The DTZ values are stored through a value map
+const FLAG_MAPPED: u8 = 2;
+/// Winning DTZ values are stored in plies instead of moves
+const FLAG_WIN_PLIES: u8 = 4;
+/// Losing DTZ values are stored in plies instead of moves
+const FLAG_LOSS_PLIES: u8 = 8;
+/// The value map stores 16 bit values
+const FLAG_WIDE: u8 = 16;
+/// All positions share the same value
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// The kind of information a table file holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableKind {
+    /// Win / draw / loss information (`.rtbw`)
+    Wdl,
+    /// Distance to zeroing the 50 move counter (`.rtbz`)
+    Dtz,
+}
+
+/// The result of looking up a position in a table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableValue {
+    /// The stored value
+    Value(i32),
+    /// The DTZ table only stores the other side to move
+    ChangeSideToMove,
+}
+
+/// The piece counts of a material signature like `KRPvKP`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Material {
+    /// The piece counts per side, indexed by [piece_code] - 1
+    pub counts: [[u8; 6]; 2],
+}
+
+/// An error that occurred while parsing a material signature
+#[derive(Debug)]
+pub enum MaterialError {
+    /// The signature is not like `KRPvKP` with one king per side
+    InvalidSignature(String),
+    /// No legal position has the pieces of the signature
+    Impossible(String),
+    /// No random position with the pieces of the signature meets the constraints
+    Unsatisfiable(String),
+}
+
+impl Display for MaterialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSignature(signature) => {
+                write!(f, "Invalid material signature '{signature}'")
+            }
+            Self::Impossible(signature) => {
+                write!(f, "No legal position has the material '{signature}'")
+            }
+            Self::Unsatisfiable(signature) => {
+                write!(f, "No position with '{signature}' meets the constraints")
+            }
+        }
+    }
+}
+
+impl Material {
+    /// The piece letters in the order they appear in file names
+    const ORDER: [(char, usize); 6] = [('K', 5), ('Q', 4), ('R', 3), ('B', 2), ('N', 1), ('P', 0)];
+
+    /// Parses a signature like `KRPvKP`, the first side is treated as white.
+    /// The number of pieces is not limited to [MAX_PIECES]
+    pub fn parse(name: &str) -> Result<Self, MaterialError> {
+        let invalid = || MaterialError::InvalidSignature(name.to_string());
+        let (first, second) = name.split_once('v').ok_or_else(invalid)?;
+        let mut counts = [[0; 6]; 2];
+
+        for (side, pieces) in [first, second].iter().enumerate() {
+            for c in pieces.chars() {
+                let (_, index) = Self::ORDER
+                    .iter()
+                    .find(|(letter, _)| *letter == c)
+                    .ok_or_else(invalid)?;
+                counts[side][*index] += 1;
+            }
+            if counts[side][5] != 1 {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self { counts })
+    }
+
+    /// Returns the material of the pieces in `state`
+    pub fn of(state: &GameState) -> Self {
+        let mut counts = [[0; 6]; 2];
+
+        for (_, piece) in state.board.get_occupied_fields_fm() {
+            counts[piece.color as usize][piece_code(piece.piece) as usize - 1] += 1;
+        }
+
+        Self { counts }
+    }
+
+    /// Returns the number of `piece`s of `color`
+    pub fn count(&self, color: Color, piece: Piece) -> u8 {
+        self.counts[color as usize][piece_code(piece) as usize - 1]
+    }
+
+    /// Returns the material with one `piece` of `color` more
+    pub fn with(&self, color: Color, piece: Piece) -> Self {
+        let mut material = *self;
+        material.counts[color as usize][piece_code(piece) as usize - 1] += 1;
+        material
+    }
+
+    /// Returns the material with one `piece` of `color` less
+    pub fn without(&self, color: Color, piece: Piece) -> Self {
+        let mut material = *self;
+        material.counts[color as usize][piece_code(piece) as usize - 1] -= 1;
+        material
+    }
+
+    /// Swaps the sides
+    pub fn flipped(&self) -> Self {
+        Self {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    /// Returns the total number of pieces
+    pub fn piece_count(&self) -> usize {
+        self.counts.iter().flatten().map(|c| *c as usize).sum()
+    }
+
+    /// Returns the signature, e.g. `KRvK`
+    pub fn name(&self) -> String {
+        let side = |counts: &[u8; 6]| {
+            Self::ORDER
+                .iter()
+                .map(|(letter, index)| letter.to_string().repeat(counts[*index] as usize))
+                .collect::<String>()
+        };
+
+        format!("{}v{}", side(&self.counts[0]), side(&self.counts[1]))
+    }
+}
+
+/// The Syzygy code of a piece type, 1 for pawns up to 6 for kings
+fn piece_code(piece: Piece) -> u8 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight => 2,
+        Piece::Bishop => 3,
+        Piece::Rook => 4,
+        Piece::Queen => 5,
+        Piece::King => 6,
+    }
+}
+
+/// The index tables shared by all table files
+struct Indices {
+    /// Maps a pawn square to the number of squares left for other pawns
+    map_pawns: [u64; 64],
+    /// Maps the squares below the a1-h8 diagonal to 0..28
+    map_b1h1h7: [u64; 64],
+    /// Maps the a1-d1-d4 triangle to 0..10, the diagonal last
+    map_a1d1d4: [usize; 64],
+    /// Maps the legal placements of two kings to 0..462
+    map_kk: [[u64; 64]; 10],
+    /// `binomial[k][n]` is the number of ways to choose `k` of `n`
+    binomial: [[u64; 64]; MAX_PIECES],
+    /// The start index of a leading pawn square for a number of leading pawns
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    /// The number of indices of the leading pawns per file
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+/// The position of a square relative to the a1-h8 diagonal, negative below it
+fn off_diagonal(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+/// Flips a square to the other side of the a1-h8 diagonal
+fn flip_diagonal(square: usize) -> usize {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+fn indices() -> &'static Indices {
+    static INDICES: OnceLock<Indices> = OnceLock::new();
+
+    INDICES.get_or_init(|| {
+        let mut indices = Indices {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                indices.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for square in 0..28 {
+            if off_diagonal(square) < 0 && square % 8 <= 3 {
+                indices.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && square % 8 <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            indices.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10 {
+            for first in 0..28 {
+                // Unmapped squares are 0 as well, b1 is the real 0
+                if indices.map_a1d1d4[first] != idx || (idx == 0 && first != 1) {
+                    continue;
+                }
+
+                for second in 0..64 {
+                    let file_distance = (first % 8).abs_diff(second % 8);
+                    let rank_distance = (first / 8).abs_diff(second / 8);
+
+                    // Skip adjacent kings and the second king above the diagonal
+                    let adjacent = file_distance <= 1 && rank_distance <= 1;
+                    if adjacent || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                        continue;
+                    } else if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        indices.map_kk[idx][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            indices.map_kk[idx][second] = code;
+            code += 1;
+        }
+
+        indices.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                indices.binomial[k][n] = if k > 0 {
+                    indices.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n { indices.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 47;
+        for lead_pawns in 1..MAX_PIECES - 1 {
+            for file in 0..4 {
+                let mut idx = 0;
+
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+
+                    if lead_pawns == 1 {
+                        indices.map_pawns[square] = available;
+                        indices.map_pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    indices.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += indices.binomial[lead_pawns - 1][indices.map_pawns[square] as usize];
+                }
+
+                indices.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+
+        indices
+    })
+}
+
+/// The decoding information for one part of a table
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    flags: u8,
+    min_sym_len: u8,
+    block_size: u64,
+    span: u64,
+    num_blocks: u64,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: u64,
+    sparse_index: usize,
+    sparse_index_size: u64,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u32>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [u16; 4],
+}
+
+/// A loaded table file
+pub struct Table {
+    kind: TableKind,
+    data: Vec<u8>,
+    symmetric: bool,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    piece_count: usize,
+    pawn_count: [usize; 2],
+    /// Indexed by side to move (WDL only) and leading pawn file (pawns only)
+    pairs: Vec<Vec<PairsData>>,
+    map: usize,
+}
+
+impl Table {
+    /// Parses the contents of a table file
+    /// # Arguments
+    /// * `kind` - Whether this is a WDL or DTZ file
+    /// * `material` - The material signature from the file name
+    /// * `data` - The contents of the file
+    /// # Returns
+    /// [None] if the file is corrupted or does not match `material`
+    pub fn parse(kind: TableKind, material: &Material, data: Vec<u8>) -> Option<Self> {
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if data.get(0..4)? != magic {
+            return None;
+        }
+
+        let [white, black] = material.counts;
+        let has_pawns = white[0] + black[0] > 0;
+
+        // The leading color is the one with fewer pawns, if it has any
+        let white_leads = black[0] == 0 || (white[0] > 0 && black[0] >= white[0]);
+        let pawn_count = if white_leads {
+            [white[0] as usize, black[0] as usize]
+        } else {
+            [black[0] as usize, white[0] as usize]
+        };
+
+        let mut table = Self {
+            kind,
+            symmetric: white == black,
+            has_pawns,
+            has_unique_pieces: [white, black].iter().any(|c| c[..5].contains(&1)),
+            piece_count: material.piece_count(),
+            pawn_count,
+            pairs: Vec::new(),
+            map: 0,
+            data,
+        };
+
+        let flags = *table.data.get(4)?;
+        if (flags & 2 != 0) != has_pawns || (flags & 1 != 0) == table.symmetric {
+            return None;
+        }
+
+        table.parse_header()?;
+        Some(table)
+    }
+
+    /// Reads the header and sets up the [PairsData] of all parts
+    fn parse_header(&mut self) -> Option<()> {
+        let sides = if self.kind == TableKind::Wdl && !self.symmetric {
+            2
+        } else {
+            1
+        };
+        let files = if self.has_pawns { 4 } else { 1 };
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+
+        let mut pairs = vec![vec![PairsData::default(); files]; sides];
+        let mut pos = 5;
+
+        for file in 0..files {
+            let first = *self.data.get(pos)?;
+            let second = if both_pawns {
+                *self.data.get(pos + 1)?
+            } else {
+                0xFF
+            };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            pos += 1 + both_pawns as usize;
+
+            for k in 0..self.piece_count {
+                let byte = *self.data.get(pos)?;
+                for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                    side_pairs[file].pieces[k] = if side == 1 { byte >> 4 } else { byte & 0xF };
+                }
+                pos += 1;
+            }
+
+            for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                self.set_groups(&mut side_pairs[file], order[side], file);
+            }
+        }
+
+        pos += pos & 1;
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                pos = self.set_sizes(&mut side_pairs[file], pos)?;
+            }
+        }
+
+        if self.kind == TableKind::Dtz {
+            self.map = pos;
+
+            for d in pairs[0].iter_mut() {
+                if d.flags & FLAG_MAPPED == 0 {
+                    continue;
+                }
+
+                if d.flags & FLAG_WIDE != 0 {
+                    pos += pos & 1;
+                    for map_idx in d.map_idx.iter_mut() {
+                        *map_idx = ((pos - self.map) / 2 + 1) as u16;
+                        pos += 2 * self.u16_le(pos)? as usize + 2;
+                    }
+                } else {
+                    for map_idx in d.map_idx.iter_mut() {
+                        *map_idx = (pos - self.map + 1) as u16;
+                        pos += *self.data.get(pos)? as usize + 1;
+                    }
+                }
+            }
+
+            pos += pos & 1;
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                let d = &mut side_pairs[file];
+                d.sparse_index = pos;
+                pos += d.sparse_index_size as usize * 6;
+            }
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                let d = &mut side_pairs[file];
+                d.block_length = pos;
+                pos += d.block_length_size as usize * 2;
+            }
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                let d = &mut side_pairs[file];
+                pos = (pos + 0x3F) & !0x3F;
+                d.data = pos;
+                pos += (d.num_blocks * d.block_size) as usize;
+
+                if d.num_blocks > 0 && pos > self.data.len() {
+                    return None;
+                }
+            }
+        }
+
+        self.pairs = pairs;
+        Some(())
+    }
+
+    /// Splits the pieces into groups that are encoded together and
+    /// computes the factors of their indices
+    fn set_groups(&self, d: &mut PairsData, order: [u8; 2], file: usize) {
+        let indices = indices();
+
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns {
+            0
+        } else if self.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        d.group_len[0] = 1;
+
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - d.group_len[0] - if both_pawns { d.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    indices.lead_pawns_size[d.group_len[0]][file]
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= indices.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                d.group_idx[next] = idx;
+                idx *= indices.binomial[d.group_len[next]][free_squares];
+                free_squares -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+
+        d.group_idx[n] = idx;
+    }
+
+    /// Reads the sizes and the Huffman code of one part
+    fn set_sizes(&self, d: &mut PairsData, mut pos: usize) -> Option<usize> {
+        d.flags = *self.data.get(pos)?;
+        pos += 1;
+
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            // The single value is stored in place of the symbol length
+            d.min_sym_len = *self.data.get(pos)?;
+            return Some(pos + 1);
+        }
+
+        let groups = d.group_len.iter().position(|len| *len == 0)?;
+        let size = d.group_idx[groups];
+
+        d.block_size = 1u64.checked_shl(*self.data.get(pos)? as u32)?;
+        d.span = 1u64.checked_shl(*self.data.get(pos + 1)? as u32)?;
+        d.sparse_index_size = size.div_ceil(d.span);
+        let padding = *self.data.get(pos + 2)? as u64;
+        d.num_blocks = self.u32_le(pos + 3)? as u64;
+        d.block_length_size = d.num_blocks + padding;
+        let max_sym_len = *self.data.get(pos + 7)?;
+        d.min_sym_len = *self.data.get(pos + 8)?;
+        d.lowest_sym = pos + 9;
+        pos += 9;
+
+        if max_sym_len < d.min_sym_len {
+            return None;
+        }
+
+        let lengths = (max_sym_len - d.min_sym_len) as usize + 1;
+        d.base64 = vec![0; lengths];
+
+        // Longer codes have lower values, the lowest code of every length
+        // padded to 64 bits allows to find the length of a code
+        for i in (0..lengths - 1).rev() {
+            let lowest = self.u16_le(d.lowest_sym + 2 * i)? as u64;
+            let next_lowest = self.u16_le(d.lowest_sym + 2 * (i + 1))? as u64;
+            d.base64[i] = d.base64[i + 1]
+                .wrapping_add(lowest)
+                .wrapping_sub(next_lowest)
+                / 2;
+        }
+        for (i, base) in d.base64.iter_mut().enumerate() {
+            *base = base
+                .checked_shl(64 - i as u32 - d.min_sym_len as u32)
+                .unwrap_or(0);
+        }
+
+        pos += lengths * 2;
+        let symbols = self.u16_le(pos)? as usize;
+        pos += 2;
+        d.btree = pos;
+
+        if pos + symbols * 3 > self.data.len() {
+            return None;
+        }
+
+        // Every symbol expands into a pair of symbols or a single value
+        d.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                d.symlen[symbol] = self.set_symlen(d, symbol, &mut visited)?;
+            }
+        }
+
+        Some(pos + symbols * 3 + (symbols & 1))
+    }
+
+    /// Computes the number of values minus one a symbol expands to
+    fn set_symlen(&self, d: &mut PairsData, symbol: usize, visited: &mut [bool]) -> Option<u32> {
+        visited[symbol] = true;
+
+        let (left, right) = self.btree(d, symbol as u16);
+        if right == 0xFFF {
+            return Some(0);
+        }
+
+        let (left, right) = (left as usize, right as usize);
+        if left >= visited.len() || right >= visited.len() {
+            return None;
+        }
+
+        if !visited[left] {
+            d.symlen[left] = self.set_symlen(d, left, visited)?;
+        }
+        if !visited[right] {
+            d.symlen[right] = self.set_symlen(d, right, visited)?;
+        }
+
+        Some(d.symlen[left] + d.symlen[right] + 1)
+    }
+
+    /// Returns the left and right symbol a symbol expands to
+    fn btree(&self, d: &PairsData, symbol: u16) -> (u16, u16) {
+        let pos = d.btree + 3 * symbol as usize;
+        let byte = |i: usize| self.data.get(pos + i).copied().unwrap_or(0) as u16;
+
+        (
+            ((byte(1) & 0xF) << 8) | byte(0),
+            (byte(2) << 4) | (byte(1) >> 4),
+        )
+    }
+
+    /// Decompresses the value stored at `idx`
+    fn decompress(&self, d: &PairsData, idx: u64) -> Option<u16> {
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(d.min_sym_len as u16);
+        }
+
+        // The sparse index points close to the block holding `idx`
+        let k = idx / d.span;
+        if k >= d.sparse_index_size {
+            return None;
+        }
+        let entry = d.sparse_index + 6 * k as usize;
+        let mut block = self.u32_le(entry)? as u64;
+        let mut offset = self.u16_le(entry + 4)? as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+
+        let block_length = |block: u64| -> Option<i64> {
+            if block >= d.block_length_size {
+                return None;
+            }
+            Some(self.u16_le(d.block_length + 2 * block as usize)? as i64)
+        };
+
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // Find the symbol holding the value in the Huffman coded block
+        let mut pos = d.data + (block * d.block_size) as usize;
+        let mut buffer = self.u64_be(pos);
+        let mut buffer_size = 64;
+        pos += 8;
+
+        let mut symbol;
+        loop {
+            let mut len = 0;
+            while len < d.base64.len() - 1 && buffer < d.base64[len] {
+                len += 1;
+            }
+
+            let shift = 64 - len as u32 - d.min_sym_len as u32;
+            symbol = ((buffer - d.base64[len]) >> shift) as u16;
+            symbol = symbol.wrapping_add(self.u16_le(d.lowest_sym + 2 * len)?);
+
+            let expands = *d.symlen.get(symbol as usize)? as i64 + 1;
+            if offset < expands {
+                break;
+            }
+
+            offset -= expands;
+            let len = len as u32 + d.min_sym_len as u32;
+            buffer = buffer.checked_shl(len).unwrap_or(0);
+            buffer_size -= len as i32;
+
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= (self.u32_be(pos) as u64) << (64 - buffer_size);
+                pos += 4;
+            }
+        }
+
+        // Expand the symbol until the value is reached
+        while *d.symlen.get(symbol as usize)? != 0 {
+            let (left, right) = self.btree(d, symbol);
+            let left_expands = *d.symlen.get(left as usize)? as i64 + 1;
+
+            if offset < left_expands {
+                symbol = left;
+            } else {
+                offset -= left_expands;
+                symbol = right;
+            }
+        }
+
+        Some(self.btree(d, symbol).0)
+    }
+
+    /// Looks up `state` in the table
+    /// # Arguments
+    /// * `state` - The position to look up
+    /// * `black_stronger` - Whether black has the pieces of the first side of the table name
+    /// * `wdl` - The WDL value of the position, used to interpret DTZ values
+    pub fn probe(&self, state: &GameState, black_stronger: bool, wdl: i32) -> Option<TableValue> {
+        let indices = indices();
+
+        // Tables of symmetric material only store white to move
+        let symmetric_black_to_move = self.symmetric && state.side_to_move == Color::Black;
+        let flip = symmetric_black_to_move || black_stronger;
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ state.side_to_move as usize;
+
+        let occupied: Vec<(usize, u8)> = state
+            .board
+            .get_occupied_fields_fm()
+            .iter()
+            .map(|(coordinate, piece)| {
+                let square = coordinate.rank as usize * 8 + coordinate.file as usize;
+                let code = piece_code(piece.piece) + 8 * (piece.color == Color::Black) as u8;
+                (square, code)
+            })
+            .collect();
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+
+        if self.has_pawns {
+            // The leading pawn is the one closest to the edge and on the lowest rank
+            let lead = self.pairs[0][0].pieces[0] ^ flip_color;
+            for (square, code) in &occupied {
+                if *code == lead {
+                    squares[size] = square ^ flip_squares;
+                    pieces[size] = code ^ flip_color;
+                    size += 1;
+                }
+            }
+            lead_pawns = size;
+
+            let best = (0..lead_pawns)
+                .max_by_key(|i| (indices.map_pawns[squares[*i]], std::cmp::Reverse(*i)))?;
+            squares.swap(0, best);
+
+            file = squares[0] % 8;
+            if file > 3 {
+                file = 7 - file;
+            }
+        }
+
+        let pairs = &self.pairs[stm % self.pairs.len()];
+        let d = &pairs[if self.has_pawns { file } else { 0 }];
+
+        let stores_both_sides = self.symmetric && !self.has_pawns;
+        if self.kind == TableKind::Dtz && (d.flags & FLAG_STM) as usize != stm && !stores_both_sides
+        {
+            return Some(TableValue::ChangeSideToMove);
+        }
+
+        let lead = if self.has_pawns {
+            Some(self.pairs[0][0].pieces[0] ^ flip_color)
+        } else {
+            None
+        };
+        for (square, code) in &occupied {
+            if Some(*code) == lead {
+                continue;
+            }
+            squares[size] = square ^ flip_squares;
+            pieces[size] = code ^ flip_color;
+            size += 1;
+        }
+
+        if size != self.piece_count {
+            return None;
+        }
+
+        // Order the pieces like the table does
+        for i in lead_pawns..size - 1 {
+            for j in i + 1..size {
+                if d.pieces[i] == pieces[j] {
+                    pieces.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        // Mirror the leading piece into the files a to d
+        if squares[0] % 8 > 3 {
+            for square in squares.iter_mut().take(size) {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx;
+
+        if self.has_pawns {
+            idx = indices.lead_pawn_idx[lead_pawns][squares[0]];
+
+            squares[1..lead_pawns].sort_by_key(|square| indices.map_pawns[*square]);
+            for (i, square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += indices.binomial[i][indices.map_pawns[*square] as usize];
+            }
+        } else {
+            // Mirror the leading piece into the ranks 1 to 4
+            if squares[0] / 8 > 3 {
+                for square in squares.iter_mut().take(size) {
+                    *square ^= 56;
+                }
+            }
+
+            // Mirror the first leading piece off the diagonal below it
+            for i in 0..d.group_len[0] {
+                match off_diagonal(squares[i]) {
+                    0 => continue,
+                    offset if offset > 0 => {
+                        for square in squares.iter_mut().take(size).skip(i) {
+                            *square = flip_diagonal(*square);
+                        }
+                    }
+                    _ => {}
+                }
+                break;
+            }
+
+            if self.has_unique_pieces {
+                let adjust1 = (squares[1] > squares[0]) as usize;
+                let adjust2 =
+                    (squares[2] > squares[0]) as usize + (squares[2] > squares[1]) as usize;
+                let rank = |square: usize| (square / 8) as u64;
+
+                idx = if off_diagonal(squares[0]) != 0 {
+                    (indices.map_a1d1d4[squares[0]] as u64 * 63 + (squares[1] - adjust1) as u64)
+                        * 62
+                        + (squares[2] - adjust2) as u64
+                } else if off_diagonal(squares[1]) != 0 {
+                    (6 * 63 + rank(squares[0]) * 28 + indices.map_b1h1h7[squares[1]]) * 62
+                        + (squares[2] - adjust2) as u64
+                } else if off_diagonal(squares[2]) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + rank(squares[0]) * 7 * 28
+                        + (rank(squares[1]) - adjust1 as u64) * 28
+                        + indices.map_b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + rank(squares[0]) * 7 * 6
+                        + (rank(squares[1]) - adjust1 as u64) * 6
+                        + (rank(squares[2]) - adjust2 as u64)
+                };
+            } else {
+                idx = indices.map_kk[indices.map_a1d1d4[squares[0]]][squares[1]];
+            }
+        }
+
+        idx *= d.group_idx[0];
+
+        // Encode the remaining groups, each sorted by square
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            squares[start..start + len].sort();
+
+            let mut n = 0;
+            for i in 0..len {
+                let square = squares[start + i];
+                let adjust = squares[..start].iter().filter(|s| square > **s).count();
+                let rank_offset = if remaining_pawns { 8 } else { 0 };
+                n += indices.binomial[i + 1][square.checked_sub(adjust + rank_offset)?];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        let value = self.decompress(d, idx)? as i32;
+
+        Some(TableValue::Value(match self.kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz => self.map_dtz(d, value, wdl)?,
+        }))
+    }
+
+    /// Converts a stored DTZ value into plies
+    fn map_dtz(&self, d: &PairsData, mut value: i32, wdl: i32) -> Option<i32> {
+        // The maps are ordered win, loss, cursed win, blessed loss
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let flags = d.flags;
+
+        if flags & FLAG_MAPPED != 0 {
+            let idx = d.map_idx[WDL_MAP[(wdl + 2) as usize]] as usize + value as usize;
+            value = if flags & FLAG_WIDE != 0 {
+                self.u16_le(self.map + 2 * idx)? as i32
+            } else {
+                *self.data.get(self.map + idx)? as i32
+            };
+        }
+
+        let in_moves = (wdl == 2 && flags & FLAG_WIN_PLIES == 0)
+            || (wdl == -2 && flags & FLAG_LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1;
+        if in_moves {
+            value *= 2;
+        }
+
+        Some(value + 1)
+    }
+
+    fn u16_le(&self, pos: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(
+            self.data.get(pos..pos + 2)?.try_into().ok()?,
+        ))
+    }
+
+    fn u32_le(&self, pos: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(
+            self.data.get(pos..pos + 4)?.try_into().ok()?,
+        ))
+    }
+
+    /// Reads big endian bytes for the Huffman decoder, past the end reads as zeros
+    fn u32_be(&self, pos: usize) -> u32 {
+        (0..4).fold(0, |value, i| {
+            (value << 8) | self.data.get(pos + i).copied().unwrap_or(0) as u32
+        })
+    }
+
+    fn u64_be(&self, pos: usize) -> u64 {
+        ((self.u32_be(pos) as u64) << 32) | self.u32_be(pos + 4) as u64
+    }
+}
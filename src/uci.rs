@@ -0,0 +1,664 @@
+//! The Universal Chess Interface (UCI) protocol
+//!
+//! The engine reads commands from the GUI line by line and answers
+//! on stdout. Searches run in a background thread so `stop` and
+//! `isready` are answered while the engine is thinking.
+use std::{
+    fmt::Display,
+    io::BufRead,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    color::Color,
+    endgame::Endgames,
+    error::{Error, ErrorExt, ErrorType},
+    fen::{FENMode, FromFENError, FromFENString, FEN_START},
+    game::GameState,
+    mate::{MateResult, MateSearch},
+    moves::Move,
+    polyglot::{BookSelection, PolyglotBook},
+    random::Random,
+    search::{Clock, SearchLimits, SearchResult, Searcher, MATE, MATE_BOUND, MAX_THREADS},
+    str,
+    syzygy::Tablebase,
+    variant::Variant,
+};
+
+/// The default size of the transposition table in megabytes
+pub const DEFAULT_HASH: usize = 16;
+
+/// The largest transposition table that can be configured in megabytes
+pub const MAX_HASH: usize = 65536;
+
+/// The largest number of lines that can be searched for with `MultiPV`
+pub const MAX_MULTI_PV: usize = 256;
+
+/// The default time kept in reserve for communication with the GUI in milliseconds
+pub const DEFAULT_MOVE_OVERHEAD: u64 = 10;
+
+/// The largest move overhead that can be configured in milliseconds
+pub const MAX_MOVE_OVERHEAD: u64 = 5000;
+
+/// An error in a command received from the GUI
+#[derive(Debug)]
+pub enum ProtocolError {
+    /// The command is not part of the protocol
+    UnknownCommand(String),
+    /// `position` is followed by neither `startpos` nor `fen`
+    ExpectedPosition,
+    /// The position after `position fen` is invalid
+    InvalidFEN(FromFENError),
+    /// A move after `moves` is not legal in the position
+    IllegalMove(String),
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "Unknown command '{command}'"),
+            Self::ExpectedPosition => write!(f, "Expected 'startpos' or 'fen'"),
+            Self::InvalidFEN(e) => write!(f, "Invalid FEN: {e}"),
+            Self::IllegalMove(mv) => write!(f, "Illegal move '{mv}'"),
+        }
+    }
+}
+
+/// An error in a `setoption` command
+#[derive(Debug)]
+pub enum OptionError {
+    /// The engine has no option of that name
+    UnknownOption(String),
+    /// The value is of the wrong type or out of range for the option
+    InvalidValue {
+        /// The name of the option
+        name: &'static str,
+        /// The value the GUI sent
+        value: String,
+    },
+}
+
+impl Display for OptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownOption(name) => write!(f, "Unknown option '{name}'"),
+            Self::InvalidValue { name, value } => {
+                write!(f, "Invalid value '{value}' for option {name}")
+            }
+        }
+    }
+}
+
+/// The options the GUI can change with `setoption`
+#[derive(Clone, Debug)]
+pub struct EngineOptions {
+    /// The size of the transposition table in megabytes (`Hash`)
+    pub hash: usize,
+    /// The number of search threads (`Threads`)
+    pub threads: usize,
+    /// The number of best lines to search for (`MultiPV`)
+    pub multi_pv: usize,
+    /// The time kept in reserve for communication with the GUI in milliseconds (`Move Overhead`)
+    pub move_overhead: u64,
+    /// Whether to play moves from the opening book (`OwnBook`)
+    pub own_book: bool,
+    /// The path to the Polyglot opening book (`BookFile`)
+    pub book_file: String,
+    /// Whether to always play the best book move instead of a weighted random one (`BestBookMove`)
+    pub best_book_move: bool,
+    /// The directories with Syzygy tablebase files (`SyzygyPath`)
+    pub syzygy_path: String,
+    /// The directories with generated endgame tables (`EndgamePath`)
+    pub endgame_path: String,
+    /// Whether castling moves are sent as the king capturing its own rook (`UCI_Chess960`)
+    pub chess960: bool,
+    /// The rules positions are set up and played with (`UCI_Variant`)
+    pub variant: Variant,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            hash: DEFAULT_HASH,
+            threads: 1,
+            multi_pv: 1,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            own_book: false,
+            book_file: String::new(),
+            best_book_move: false,
+            syzygy_path: String::new(),
+            endgame_path: String::new(),
+            chess960: false,
+            variant: Variant::Standard,
+        }
+    }
+}
+
+/// A UCI engine session
+pub struct UCI {
+    state: GameState,
+    history: Vec<u64>,
+    options: EngineOptions,
+    searcher: Arc<Mutex<Searcher>>,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    search_thread: Option<JoinHandle<()>>,
+    book: Option<PolyglotBook>,
+    tablebase: Option<Arc<Tablebase>>,
+    endgames: Option<Arc<Endgames>>,
+    random: Random,
+}
+
+impl UCI {
+    /// Creates a new session in the starting position
+    pub fn new() -> Self {
+        let options = EngineOptions::default();
+        let searcher = Searcher::new(options.hash);
+
+        Self {
+            state: GameState::from_fen(FEN_START).expect("[DEV] Invalid starting position"),
+            history: Vec::new(),
+            stop: searcher.stop_flag(),
+            ponder: searcher.ponder_flag(),
+            searcher: Arc::new(Mutex::new(searcher)),
+            options,
+            search_thread: None,
+            book: None,
+            tablebase: None,
+            endgames: None,
+            random: Random::from_time(),
+        }
+    }
+
+    /// Handles commands from `input` until `quit` is received or the input ends
+    /// # Arguments
+    /// * `input` - The stream of commands from the GUI
+    pub fn run<R: BufRead>(&mut self, input: R) -> Result<(), Error> {
+        for line in input.lines() {
+            let line = line.ctx(str!("Reading UCI command"))?;
+
+            if !self.handle(&line) {
+                break;
+            }
+        }
+
+        self.stop();
+        Ok(())
+    }
+
+    /// Handles a single command
+    /// # Returns
+    /// `false` if the engine should quit
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match tokens.first().copied() {
+            Some("uci") => {
+                println!("id name Knightmare {}", env!("CARGO_PKG_VERSION"));
+                println!("id author The Knightmare developers");
+                println!("option name Hash type spin default {DEFAULT_HASH} min 1 max {MAX_HASH}");
+                println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
+                println!("option name MultiPV type spin default 1 min 1 max {MAX_MULTI_PV}");
+                println!(
+                    "option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD} min 0 max {MAX_MOVE_OVERHEAD}"
+                );
+                println!("option name Ponder type check default false");
+                println!("option name OwnBook type check default false");
+                println!("option name BookFile type string default <empty>");
+                println!("option name BestBookMove type check default false");
+                println!("option name SyzygyPath type string default <empty>");
+                println!("option name EndgamePath type string default <empty>");
+                println!("option name UCI_Chess960 type check default false");
+                let variants: Vec<String> = Variant::ALL
+                    .iter()
+                    .map(|variant| format!("var {}", variant.uci_name()))
+                    .collect();
+                println!(
+                    "option name UCI_Variant type combo default {} {}",
+                    Variant::Standard.uci_name(),
+                    variants.join(" ")
+                );
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("setoption") => report(self.set_option(&tokens[1..])),
+            Some("ucinewgame") => {
+                self.stop();
+                self.searcher().clear();
+            }
+            Some("position") => report(self.position(&tokens[1..])),
+            Some("go") => self.go(&tokens[1..]),
+            Some("stop") => self.stop(),
+            Some("ponderhit") => self.ponder.store(false, Ordering::Relaxed),
+            Some("quit") => return false,
+            Some(command) => report(Err(ProtocolError::UnknownCommand(command.to_string()))),
+            None => {}
+        }
+
+        true
+    }
+
+    /// Locks the searcher
+    fn searcher(&self) -> std::sync::MutexGuard<'_, Searcher> {
+        self.searcher.lock().expect("[DEV] Search thread panicked")
+    }
+
+    /// Stops a running search and waits for it to report its best move
+    fn stop(&mut self) {
+        if let Some(thread) = self.search_thread.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            thread.join().expect("[DEV] Search thread panicked");
+        }
+    }
+
+    /// Handles `setoption name <name> [value <value>]`
+    fn set_option(&mut self, tokens: &[&str]) -> Result<(), OptionError> {
+        let value_index = tokens.iter().position(|t| *t == "value");
+        let name = tokens[..value_index.unwrap_or(tokens.len())]
+            .iter()
+            .skip_while(|t| **t == "name")
+            .copied()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let value = value_index
+            .map(|i| tokens[i + 1..].join(" "))
+            .unwrap_or_default();
+
+        let invalid = |name| OptionError::InvalidValue {
+            name,
+            value: value.clone(),
+        };
+
+        self.stop();
+
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(hash) if (1..=MAX_HASH).contains(&hash) => {
+                    let mut searcher = Searcher::new(hash);
+                    searcher.set_tablebase(self.tablebase.clone());
+                    searcher.set_endgames(self.endgames.clone());
+                    searcher.set_threads(self.options.threads);
+                    self.options.hash = hash;
+                    self.stop = searcher.stop_flag();
+                    self.ponder = searcher.ponder_flag();
+                    self.searcher = Arc::new(Mutex::new(searcher));
+                }
+                _ => return Err(invalid("Hash")),
+            },
+            "threads" => match value.parse::<usize>() {
+                Ok(threads) if (1..=MAX_THREADS).contains(&threads) => {
+                    self.options.threads = threads;
+                    self.searcher().set_threads(threads);
+                }
+                _ => return Err(invalid("Threads")),
+            },
+            "multipv" => match value.parse::<usize>() {
+                Ok(multi_pv) if (1..=MAX_MULTI_PV).contains(&multi_pv) => {
+                    self.options.multi_pv = multi_pv
+                }
+                _ => return Err(invalid("MultiPV")),
+            },
+            "move overhead" => match value.parse::<u64>() {
+                Ok(overhead) if overhead <= MAX_MOVE_OVERHEAD => {
+                    self.options.move_overhead = overhead
+                }
+                _ => return Err(invalid("Move Overhead")),
+            },
+            // Pondering is controlled by the GUI, the option only announces support
+            "ponder" => {}
+            "ownbook" => match value.parse::<bool>() {
+                Ok(own_book) => {
+                    self.options.own_book = own_book;
+                    self.load_book();
+                }
+                Err(_) => return Err(invalid("OwnBook")),
+            },
+            "bookfile" => {
+                self.options.book_file = match value.as_str() {
+                    "<empty>" => String::new(),
+                    _ => value,
+                };
+                self.book = None;
+                self.load_book();
+            }
+            "bestbookmove" => match value.parse::<bool>() {
+                Ok(best) => self.options.best_book_move = best,
+                Err(_) => return Err(invalid("BestBookMove")),
+            },
+            "syzygypath" => {
+                self.options.syzygy_path = match value.as_str() {
+                    "<empty>" => String::new(),
+                    _ => value,
+                };
+                self.load_tablebase();
+            }
+            "endgamepath" => {
+                self.options.endgame_path = match value.as_str() {
+                    "<empty>" => String::new(),
+                    _ => value,
+                };
+                self.load_endgames();
+            }
+            "uci_chess960" => match value.parse::<bool>() {
+                Ok(chess960) => self.options.chess960 = chess960,
+                Err(_) => return Err(invalid("UCI_Chess960")),
+            },
+            "uci_variant" => match Variant::from_uci_name(&value) {
+                Some(variant) => self.options.variant = variant,
+                None => return Err(invalid("UCI_Variant")),
+            },
+            _ => return Err(OptionError::UnknownOption(name)),
+        }
+
+        Ok(())
+    }
+
+    /// Loads the opening book if it is enabled and not loaded yet
+    fn load_book(&mut self) {
+        if !self.options.own_book || self.options.book_file.is_empty() || self.book.is_some() {
+            return;
+        }
+
+        match PolyglotBook::open(Path::new(&self.options.book_file)) {
+            Ok(book) => {
+                println!(
+                    "info string Loaded {} book entries from {}",
+                    book.entries().len(),
+                    self.options.book_file
+                );
+                self.book = Some(book);
+            }
+            Err(e) => println!("info string {}", e.to_string().replace('\n', " ")),
+        }
+    }
+
+    /// Loads the tablebases from the configured path and hands them to the searcher
+    fn load_tablebase(&mut self) {
+        self.tablebase = None;
+
+        if !self.options.syzygy_path.is_empty() {
+            match Tablebase::open(&self.options.syzygy_path) {
+                Ok(tablebase) => {
+                    println!(
+                        "info string Found {} tablebases with up to {} pieces",
+                        tablebase.table_count(),
+                        tablebase.max_pieces()
+                    );
+                    self.tablebase = Some(Arc::new(tablebase));
+                }
+                Err(e) => println!("info string {}", e.to_string().replace('\n', " ")),
+            }
+        }
+
+        let tablebase = self.tablebase.clone();
+        self.searcher().set_tablebase(tablebase);
+    }
+
+    /// Loads the generated endgame tables from the configured path and hands them to the searcher
+    fn load_endgames(&mut self) {
+        self.endgames = None;
+
+        if !self.options.endgame_path.is_empty() {
+            match Endgames::open(&self.options.endgame_path) {
+                Ok(endgames) => {
+                    println!(
+                        "info string Loaded {} endgame tables",
+                        endgames.tables().count()
+                    );
+                    self.endgames = Some(Arc::new(endgames));
+                }
+                Err(e) => println!("info string {}", e.to_string().replace('\n', " ")),
+            }
+        }
+
+        let endgames = self.endgames.clone();
+        self.searcher().set_endgames(endgames);
+    }
+
+    /// Handles `position [startpos | fen <fen>] [moves <moves>...]`
+    fn position(&mut self, tokens: &[&str]) -> Result<(), ProtocolError> {
+        let moves_index = tokens.iter().position(|t| *t == "moves");
+        let setup = &tokens[..moves_index.unwrap_or(tokens.len())];
+
+        let variant = self.options.variant;
+        let state = match setup.first().copied() {
+            Some("startpos") => GameState::from_variant_fen(variant.start_fen(), variant),
+            Some("fen") => {
+                GameState::from_variant_fen_with(&setup[1..].join(" "), variant, FENMode::Strict)
+            }
+            _ => return Err(ProtocolError::ExpectedPosition),
+        };
+        let mut state = state.map_err(ProtocolError::InvalidFEN)?;
+        // Chess960 positions are recognized by their castling rights, otherwise the option decides
+        state.chess960 |= self.options.chess960;
+
+        let mut history = Vec::new();
+        for token in moves_index.map_or(&[][..], |i| &tokens[i + 1..]) {
+            match Move::from_uci(token).filter(|mv| state.is_legal(*mv)) {
+                Some(mv) => {
+                    history.push(state.hash());
+                    state = state.make_move(mv);
+                }
+                None => return Err(ProtocolError::IllegalMove(token.to_string())),
+            }
+        }
+
+        self.state = state;
+        self.history = history;
+
+        Ok(())
+    }
+
+    /// Picks a move from the own book if it is enabled, the book only knows standard chess
+    fn book_move(&mut self) -> Option<Move> {
+        if !self.options.own_book || self.state.variant != Variant::Standard {
+            return None;
+        }
+
+        let selection = if self.options.best_book_move {
+            BookSelection::Best
+        } else {
+            BookSelection::Weighted
+        };
+        self.book
+            .as_ref()
+            .and_then(|book| book.pick(&self.state, selection, &mut self.random))
+    }
+
+    /// Handles `go` with its limits and starts the search
+    fn go(&mut self, tokens: &[&str]) {
+        self.stop();
+
+        let mut limits = SearchLimits {
+            move_overhead: Duration::from_millis(self.options.move_overhead),
+            multi_pv: self.options.multi_pv,
+            ..Default::default()
+        };
+        let mut infinite = false;
+        let mut ponder = false;
+        let mut mate = None;
+        let (mut time, mut increment, mut moves_to_go) = (None, 0, None);
+
+        let mut tokens = tokens.iter().peekable();
+        while let Some(token) = tokens.next() {
+            let mut value = || tokens.next().and_then(|v| v.parse::<u64>().ok());
+
+            match *token {
+                "depth" => limits.depth = value().map(|d| d.min(u8::MAX as u64) as u8),
+                "nodes" => limits.nodes = value(),
+                "movetime" => limits.movetime = value().map(Duration::from_millis),
+                "wtime" | "btime" => {
+                    let value = value();
+                    if (*token == "wtime") == (self.state.side_to_move == Color::White) {
+                        time = value;
+                    }
+                }
+                "winc" | "binc" => {
+                    let value = value();
+                    if (*token == "winc") == (self.state.side_to_move == Color::White) {
+                        increment = value.unwrap_or(0);
+                    }
+                }
+                "movestogo" => moves_to_go = value().map(|m| m.min(u32::MAX as u64) as u32),
+                "infinite" => infinite = true,
+                "ponder" => ponder = true,
+                "mate" => mate = value().map(|m| m.min(u32::MAX as u64) as u32),
+                "searchmoves" => {
+                    while let Some(mv) = tokens.peek().and_then(|t| Move::from_uci(t)) {
+                        limits.search_moves.push(mv);
+                        tokens.next();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        limits.clock = time.map(|time| Clock {
+            time: Duration::from_millis(time),
+            increment: Duration::from_millis(increment),
+            moves_to_go,
+        });
+
+        let state = self.state;
+        let history = self.history.clone();
+        let searcher = self.searcher.clone();
+        let stop = self.stop.clone();
+        let pondering = self.ponder.clone();
+        stop.store(false, Ordering::Relaxed);
+        pondering.store(ponder, Ordering::Relaxed);
+
+        if let Some(mv) = self.book_move() {
+            self.search_thread = Some(std::thread::spawn(move || {
+                println!("info string Book move");
+                wait_for_release(infinite, &pondering, &stop);
+                println!("bestmove {mv}");
+            }));
+            return;
+        }
+
+        if let Some(moves) = mate {
+            self.search_thread = Some(std::thread::spawn(move || go_mate(&state, moves, stop)));
+            return;
+        }
+
+        self.search_thread = Some(std::thread::spawn(move || {
+            let mut searcher = searcher.lock().expect("[DEV] Search thread panicked");
+            let start = Instant::now();
+
+            let result = searcher.search_with_info(&state, &history, &limits, &mut |result| {
+                print_info(result, start.elapsed())
+            });
+
+            wait_for_release(infinite, &pondering, &stop);
+
+            match (result.best_move, result.pv.get(1)) {
+                (Some(mv), Some(ponder)) => println!("bestmove {mv} ponder {ponder}"),
+                (Some(mv), None) => println!("bestmove {mv}"),
+                (None, _) => println!("bestmove 0000"),
+            }
+        }));
+    }
+}
+
+impl Default for UCI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports a failed command to the GUI as an `info string`
+fn report<E: Into<ErrorType>>(result: Result<(), E>) {
+    if let Err(e) = result {
+        println!("info string {}", e.into());
+    }
+}
+
+/// Blocks until a move may be reported: `go infinite` and `go ponder`
+/// must not report a move before `stop` or `ponderhit`
+/// # Arguments
+/// * `infinite` - Whether the search was started with `go infinite`
+/// * `pondering` - Set while the engine is pondering, cleared by `ponderhit`
+/// * `stop` - Set by `stop`
+fn wait_for_release(infinite: bool, pondering: &AtomicBool, stop: &AtomicBool) {
+    while (infinite || pondering.load(Ordering::Relaxed)) && !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Searches for a mate in at most `moves` moves and reports it like a regular search
+fn go_mate(state: &GameState, moves: u32, stop: Arc<AtomicBool>) {
+    let mut search = MateSearch::with_stop(stop);
+    let start = Instant::now();
+
+    let result = search.search(state, moves, &mut |moves, nodes| {
+        println!(
+            "info depth {} nodes {nodes} time {}",
+            moves * 2 - 1,
+            start.elapsed().as_millis()
+        )
+    });
+
+    match result {
+        MateResult::Mate { moves, line } => {
+            println!(
+                "info depth {} score mate {moves} nodes {} time {} pv {}",
+                moves * 2 - 1,
+                search.nodes(),
+                start.elapsed().as_millis(),
+                line.iter()
+                    .map(|mv| mv.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            );
+            match line.first() {
+                Some(mv) => println!("bestmove {mv}"),
+                None => println!("bestmove 0000"),
+            }
+        }
+        MateResult::NoMate => {
+            println!("info string No forced mate in {moves} moves");
+            println!("bestmove 0000");
+        }
+        MateResult::Aborted => println!("bestmove 0000"),
+    }
+}
+
+/// Formats a search score as `cp <centipawns>` or `mate <moves>`
+pub fn format_score(score: i32) -> String {
+    if score >= MATE_BOUND {
+        format!("mate {}", (MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        format!("mate -{}", (MATE + score) / 2)
+    } else {
+        format!("cp {score}")
+    }
+}
+
+/// Prints an `info` line per line of an intermediate search result
+fn print_info(result: &SearchResult, elapsed: Duration) {
+    let millis = elapsed.as_millis().max(1) as u64;
+
+    for (i, line) in result.lines.iter().enumerate() {
+        println!(
+            "info depth {} multipv {} score {} nodes {} nps {} tbhits {} time {} pv {}",
+            result.depth,
+            i + 1,
+            format_score(line.score),
+            result.nodes,
+            result.nodes * 1000 / millis,
+            result.tb_hits,
+            elapsed.as_millis(),
+            line.pv
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+    }
+}
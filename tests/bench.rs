@@ -0,0 +1,20 @@
+use knightmare::bench;
+
+#[test]
+fn search_signature() {
+    // Changes whenever the behavior of the search changes, update it deliberately
+    let result = bench::search(5, 1, 16, &mut |_, _| {});
+    assert_eq!(result.count, 629002);
+}
+
+#[test]
+fn move_signatures() {
+    // Every move generated is made once, five times over the tree
+    let movegen = bench::movegen(1);
+    let make_move = bench::make_move(1);
+    assert_eq!(movegen.count, 221690);
+    assert_eq!(make_move.count, movegen.count);
+    assert_eq!(bench::movegen(1).count, movegen.count);
+
+    assert_eq!(bench::movegen(0).count, 6975);
+}
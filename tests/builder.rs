@@ -0,0 +1,139 @@
+use knightmare::{
+    board::Board,
+    builder::PositionBuilder,
+    castling::CastlingRights,
+    color::Color,
+    coordinate::{Coordinate, File},
+    fen::{FromFENError, FromFENString, ToFENString, FEN_START},
+    piece::{ColoredPiece, Piece},
+};
+
+mod common;
+use common::state;
+
+fn sq(square: &str) -> Coordinate {
+    Coordinate::from_fen(square).expect("Invalid test square")
+}
+
+fn piece(color: Color, piece: Piece) -> ColoredPiece {
+    ColoredPiece { piece, color }
+}
+
+/// Both kings on their start squares, white to move
+fn kings() -> PositionBuilder {
+    PositionBuilder::new()
+        .piece(sq("e1"), piece(Color::White, Piece::King))
+        .piece(sq("e8"), piece(Color::Black, Piece::King))
+}
+
+#[test]
+fn edits_boards() {
+    let mut board = Board::default();
+    let queen = piece(Color::White, Piece::Queen);
+    let knight = piece(Color::Black, Piece::Knight);
+
+    assert_eq!(board.set(sq("d1"), queen), None);
+    assert_eq!(board.set(sq("d5"), knight), None);
+    assert_eq!(board.get(sq("d1")), Some(queen));
+
+    // Moving onto an occupied square returns the piece that stood there
+    assert_eq!(board.move_piece(sq("d1"), sq("d5")), Ok(Some(knight)));
+    assert_eq!(board.get(sq("d1")), None);
+    assert_eq!(board.get(sq("d5")), Some(queen));
+    assert_eq!(board.move_piece(sq("d5"), sq("h5")), Ok(None));
+
+    // Nothing happens if there is no piece to move
+    let before = board;
+    assert_eq!(board.move_piece(sq("a1"), sq("h5")), Err(sq("a1")));
+    assert_eq!(board, before);
+
+    assert_eq!(board.remove(sq("h5")), Some(queen));
+    assert_eq!(board.remove(sq("h5")), None);
+
+    let mut start = state(FEN_START).board;
+    start.clear();
+    assert_eq!(start, Board::default());
+}
+
+#[test]
+fn builds_positions() {
+    let state = kings()
+        .piece(sq("a1"), piece(Color::White, Piece::Rook))
+        .piece(sq("h1"), piece(Color::White, Piece::Rook))
+        .piece(sq("d5"), piece(Color::Black, Piece::Pawn))
+        .piece(sq("e5"), piece(Color::White, Piece::Pawn))
+        .castling(Color::White, true, File::FileH)
+        .castling(Color::White, false, File::FileA)
+        .en_passant(Some(sq("d6")))
+        .halfmove_clock(0)
+        .fullmove_counter(12)
+        .build()
+        .expect("Valid position");
+
+    assert_eq!(state.to_fen(), "4k3/8/8/3pP3/8/8/8/R3K2R w KQ d6 0 12");
+    assert!(!state.chess960);
+
+    // A builder started from a position builds it again
+    assert_eq!(
+        PositionBuilder::from(&state)
+            .build()
+            .map(|s| s.to_fen())
+            .ok(),
+        Some(state.to_fen())
+    );
+}
+
+#[test]
+fn builds_chess960_castling() {
+    let state = kings()
+        .remove(sq("e1"))
+        .piece(sq("b1"), piece(Color::White, Piece::King))
+        .piece(sq("c1"), piece(Color::White, Piece::Rook))
+        .castling(Color::White, true, File::FileC)
+        .build()
+        .expect("Valid position");
+
+    assert!(state.chess960);
+    assert!(state.castling.kingside(Color::White));
+}
+
+#[test]
+fn rejects_invalid_setups() {
+    let build = |builder: PositionBuilder| builder.build().expect_err("Invalid position");
+
+    assert!(matches!(
+        build(PositionBuilder::new()),
+        FromFENError::KingCount { count: 0, .. }
+    ));
+    assert!(matches!(
+        build(kings().piece(sq("d4"), piece(Color::White, Piece::King))),
+        FromFENError::KingCount {
+            color: Color::White,
+            count: 2
+        }
+    ));
+    assert!(matches!(
+        build(kings().piece(sq("b8"), piece(Color::White, Piece::Pawn))),
+        FromFENError::PawnOnBackRank(_)
+    ));
+    assert!(matches!(
+        build(kings().piece(sq("e4"), piece(Color::White, Piece::Rook))),
+        FromFENError::OpponentInCheck(Color::Black)
+    ));
+    assert!(matches!(
+        build(kings().castling(Color::White, true, File::FileH)),
+        FromFENError::InconsistentCastling('K')
+    ));
+    assert!(matches!(
+        build(kings().castling_rights(CastlingRights::ALL)),
+        FromFENError::InconsistentCastling(_)
+    ));
+    assert!(matches!(
+        build(kings().en_passant(Some(sq("d6")))),
+        FromFENError::InconsistentEnPassant(_)
+    ));
+    assert!(matches!(
+        build(kings().fullmove_counter(0)),
+        FromFENError::ImplausibleCounter { .. }
+    ));
+}
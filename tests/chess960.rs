@@ -0,0 +1,137 @@
+use knightmare::{
+    board::Board,
+    chess960::{CHESS960_POSITIONS, STANDARD_INDEX},
+    fen::{ToFENString, FEN_START},
+    game::GameState,
+    moves::Move,
+};
+
+mod common;
+use common::{perft, state};
+
+/// Chess960 reference positions with their node counts for depths 1 to 3
+const PERFT: [(&str, [u64; 3]); 11] = [
+    (
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        [21, 528, 12189],
+    ),
+    (
+        "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        [21, 807, 18002],
+    ),
+    (
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        [20, 479, 10471],
+    ),
+    (
+        "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        [22, 593, 13440],
+    ),
+    (
+        "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        [28, 1120, 31058],
+    ),
+    (
+        "qnbnr1kr/ppp1b1pp/4p3/3p1p2/8/2NPP3/PPP1BPPP/QNB1R1KR w HEhe - 1 9",
+        [29, 899, 26578],
+    ),
+    (
+        "q1bnrkr1/ppppp2p/2n2p2/4b1p1/2NP4/8/PPP1PPPP/QNB1RRKB w ge - 1 9",
+        [30, 860, 24566],
+    ),
+    (
+        "qbn1brkr/ppp1p1p1/2n4p/3p1p2/P7/6PP/QPPPPP2/1BNNBRKR w HFhf - 0 9",
+        [25, 635, 17054],
+    ),
+    (
+        "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+        [29, 502, 14569],
+    ),
+    (
+        "rbbqn1kr/pp2p1pp/6n1/2pp1p2/2P4P/P7/BP1PPPP1/R1BQNNKR w HAha - 0 9",
+        [27, 916, 25798],
+    ),
+    (
+        "rqbbknr1/1ppp2pp/p5n1/4pp2/P7/1PP5/1Q1PPPPP/R1BBKNRN w GAga - 0 9",
+        [24, 600, 15347],
+    ),
+];
+
+#[test]
+fn perft_reference_positions() {
+    for (fen, counts) in PERFT {
+        let state = state(fen);
+        assert!(state.chess960, "{fen} is not recognized as Chess960");
+
+        for (depth, expected) in counts.iter().enumerate() {
+            assert_eq!(
+                perft(&state, depth as u32 + 1),
+                *expected,
+                "{fen} at depth {}",
+                depth + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn starting_positions() {
+    let standard = state(FEN_START);
+    assert_eq!(
+        Board::from_chess960(STANDARD_INDEX).unwrap().squares,
+        standard.board.squares
+    );
+    assert_eq!(
+        GameState::from_chess960(0).unwrap().to_shredder_fen(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+    );
+    assert_eq!(
+        GameState::from_chess960(959).unwrap().to_fen(),
+        "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+    );
+    assert!(Board::from_chess960(CHESS960_POSITIONS).is_none());
+
+    for index in (0..CHESS960_POSITIONS).step_by(7) {
+        let board = Board::from_chess960(index).unwrap();
+        assert_eq!(board.chess960_index(), Some(index));
+    }
+}
+
+#[test]
+fn castling_fields() {
+    let shredder = state("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+    let xfen = state("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+    assert_eq!(shredder.castling, xfen.castling);
+    assert_eq!(shredder.to_shredder_fen().split(' ').nth(2), Some("HFhf"));
+    assert_eq!(shredder.to_fen().split(' ').nth(2), Some("KQkq"));
+
+    // The inner rook has to be named by its file in X-FEN
+    let inner = state("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1");
+    assert_eq!(inner.to_fen().split(' ').nth(2), Some("B"));
+
+    let standard = state(FEN_START);
+    assert!(!standard.chess960);
+    assert_eq!(standard.to_fen(), FEN_START);
+}
+
+#[test]
+fn castling_moves() {
+    let state = state("4k3/8/8/8/8/8/8/R4KR1 w GA - 0 1");
+
+    // The king captures its own rook
+    let kingside = Move::from_uci("f1g1").unwrap();
+    let queenside = Move::from_uci("f1a1").unwrap();
+    assert!(state.is_legal(kingside) && state.is_legal(queenside));
+    assert!(state.is_castling(kingside) && !state.is_capture(kingside));
+    assert_eq!(kingside.to_san(&state), "O-O");
+    assert_eq!(state.parse_san("O-O-O").unwrap(), queenside);
+
+    assert_eq!(
+        state.make_move(kingside).to_fen(),
+        "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1"
+    );
+    assert_eq!(
+        state.make_move(queenside).to_fen(),
+        "4k3/8/8/8/8/8/8/2KR2R1 b - - 1 1"
+    );
+}
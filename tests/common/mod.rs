@@ -0,0 +1,22 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use knightmare::{fen::FromFENString, game::GameState};
+
+/// Parses a FEN that is known to be valid
+pub fn state(fen: &str) -> GameState {
+    GameState::from_fen(fen).expect("Invalid test FEN")
+}
+
+/// Counts the leaf nodes of the move tree of `state` up to `depth` plies
+pub fn perft(state: &GameState, depth: u32) -> u64 {
+    let moves = state.legal_moves();
+    if depth <= 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .iter()
+        .map(|mv| perft(&state.make_move(*mv), depth - 1))
+        .sum()
+}
@@ -0,0 +1,89 @@
+use knightmare::{
+    datagen::{generate, DataFormat, DataRecord, DatagenConfig, DatagenError, RECORD_SIZE},
+    error::ErrorType,
+    fen::{FromFENString, ToFENString, FEN_START},
+    game::{GameResult, GameState},
+    search::SearchLimits,
+};
+
+fn config(max_opening_score: i32) -> DatagenConfig {
+    DatagenConfig {
+        games: 1,
+        threads: 1,
+        limits: SearchLimits {
+            depth: Some(1),
+            ..Default::default()
+        },
+        random_plies: 0,
+        max_opening_score,
+        hash_mb: 1,
+        seed: 1,
+    }
+}
+
+#[test]
+fn binary_records_round_trip() {
+    let records = [
+        (FEN_START, 12, GameResult::WhiteWins),
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 3 10",
+            -317,
+            GameResult::Draw,
+        ),
+        (
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            45,
+            GameResult::BlackWins,
+        ),
+        (
+            "8/8/8/8/5kp1/P7/8/1K1N4 b - - 99 300",
+            i16::MIN,
+            GameResult::Draw,
+        ),
+    ];
+
+    for (fen, score, result) in records {
+        let record = DataRecord {
+            state: GameState::from_fen(fen).expect("Invalid test FEN"),
+            score,
+            result,
+        };
+
+        let decoded = DataRecord::from_binary(&record.to_binary()).expect("Valid record");
+        assert_eq!(decoded.state.to_fen(), fen);
+        assert_eq!(decoded.state.hash(), record.state.hash(), "{fen}");
+        assert_eq!(decoded.score, score);
+        assert_eq!(decoded.result, result);
+    }
+}
+
+#[test]
+fn text_records() {
+    let record = DataRecord {
+        state: GameState::from_fen(FEN_START).expect("Invalid test FEN"),
+        score: -25,
+        result: GameResult::Draw,
+    };
+    assert_eq!(record.to_text(), format!("{FEN_START} | -25 | 0.5"));
+}
+
+#[test]
+fn unreachable_opening_score_is_an_error() {
+    let mut output = Vec::new();
+    let error = generate(&config(-1), DataFormat::Text, &mut output).expect_err("No opening");
+
+    assert!(matches!(
+        error.error,
+        ErrorType::Datagen(DatagenError::NoOpening { .. })
+    ));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn generates_games() {
+    let mut output = Vec::new();
+    let stats = generate(&config(1000), DataFormat::Binary, &mut output).expect("Datagen");
+
+    assert_eq!(stats.games, 1);
+    assert_eq!(output.len() as u64, stats.positions * RECORD_SIZE as u64);
+}
@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use knightmare::{
+    endgame::{EndgameTable, Endgames, DTM},
+    syzygy::Material,
+};
+
+mod common;
+use common::state;
+
+/// Generates KQvK and KRvK once for all tests, this takes a while without optimizations
+fn endgames() -> &'static Endgames {
+    static ENDGAMES: OnceLock<Endgames> = OnceLock::new();
+    ENDGAMES.get_or_init(|| {
+        let mut endgames = Endgames::new();
+        for name in ["KQvK", "KRvK"] {
+            let material = Material::parse(name).expect("Valid signature");
+            assert!(endgames.generate(&material).is_some(), "{name}");
+        }
+        endgames
+    })
+}
+
+fn table(name: &str) -> &'static EndgameTable {
+    endgames()
+        .get(&Material::parse(name).expect("Valid signature"))
+        .expect("Generated table")
+}
+
+#[test]
+fn longest_mates() {
+    // The well-known maximal distances: mate in 10 with the queen, in 16 with the rook
+    for (name, longest) in [("KQvK", 20), ("KRvK", 32)] {
+        let stats = table(name).stats();
+
+        assert_eq!(stats.longest, longest, "{name}");
+        assert!(
+            stats.wins > 0 && stats.losses > 0 && stats.draws > 0,
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn probes_positions() {
+    let endgames = endgames();
+
+    assert_eq!(
+        endgames.probe(&state("k7/8/1K6/8/8/8/8/6Q1 w - - 0 1")),
+        Some(DTM::Win(1))
+    );
+    assert_eq!(
+        endgames.probe(&state("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1")),
+        Some(DTM::Loss(0))
+    );
+    assert_eq!(
+        endgames.probe(&state("k7/8/1KQ5/8/8/8/8/8 b - - 0 1")),
+        Some(DTM::Loss(2))
+    );
+    // Black to move captures the queen
+    assert_eq!(
+        endgames.probe(&state("kQ6/8/8/8/8/8/8/7K b - - 0 1")),
+        Some(DTM::Draw)
+    );
+    assert_eq!(
+        endgames.probe(&state("k7/8/8/8/8/8/8/7K w - - 0 1")),
+        Some(DTM::Draw)
+    );
+    assert_eq!(endgames.probe(&state("k7/8/8/8/8/8/8/B6K w - - 0 1")), None);
+}
+
+#[test]
+fn probes_color_flipped_positions() {
+    let endgames = endgames();
+
+    assert_eq!(
+        endgames.probe(&state("7r/8/8/8/8/1k6/8/K7 b - - 0 1")),
+        Some(DTM::Win(1))
+    );
+    for fen in [
+        "8/8/8/4k3/8/8/8/R3K3 w - - 0 1",
+        "8/8/3k4/8/8/2K5/8/7R b - - 0 1",
+        "k7/8/K7/8/8/8/8/7R w - - 0 1",
+    ] {
+        let state = state(fen);
+        let dtm = endgames.probe(&state);
+
+        assert!(dtm.is_some(), "{fen}");
+        assert_eq!(endgames.probe(&state.flip_colors()), dtm, "{fen}");
+    }
+}
+
+#[test]
+fn tables_round_trip() {
+    let table = table("KRvK");
+    let bytes = table.to_bytes();
+
+    let read = EndgameTable::from_bytes(&bytes).expect("Valid table");
+    assert_eq!(read.material(), table.material());
+    assert_eq!(read.to_bytes(), bytes);
+
+    // Truncated and mislabeled files are rejected
+    assert!(EndgameTable::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    let mut wrong_magic = bytes.clone();
+    wrong_magic[0] = b'X';
+    assert!(EndgameTable::from_bytes(&wrong_magic).is_none());
+}
@@ -0,0 +1,108 @@
+use knightmare::{epd::EPD, fen::ToFENString, moves::Move};
+
+fn epd(line: &str) -> EPD {
+    EPD::from_epd(line).expect("Invalid test EPD")
+}
+
+fn uci(moves: &[&str]) -> Vec<Move> {
+    moves
+        .iter()
+        .map(|mv| Move::from_uci(mv).expect("Invalid test move"))
+        .collect()
+}
+
+/// The first position of Win at Chess
+const WAC_001: &str =
+    "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+
+#[test]
+fn reads_operations() {
+    let record = epd(WAC_001);
+
+    assert_eq!(record.id(), Some("WAC.001"));
+    assert_eq!(record.best_moves().unwrap(), uci(&["g3g6"]));
+    assert!(record.avoid_moves().unwrap().is_empty());
+    assert_eq!(
+        record.state.to_fen(),
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1"
+    );
+}
+
+#[test]
+fn reads_moves_comments_and_analysis() {
+    let record = epd(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e4 d4; am f3 g4; \
+         c0 \"Sound; and solid\"; c1 classic; acd 12; ce -15; pv e4 e5 Nf3 Nc6;",
+    );
+
+    assert_eq!(record.best_moves().unwrap(), uci(&["e2e4", "d2d4"]));
+    assert_eq!(record.avoid_moves().unwrap(), uci(&["f2f3", "g2g4"]));
+    assert_eq!(record.comment(0), Some("Sound; and solid"));
+    assert_eq!(record.comment(1), Some("classic"));
+    assert_eq!(record.comment(2), None);
+    assert_eq!(record.analysis_depth(), Some(12));
+    assert_eq!(record.centipawn_evaluation(), Some(-15));
+    assert_eq!(
+        record.predicted_variation().unwrap(),
+        uci(&["e2e4", "e7e5", "g1f3", "b8c6"])
+    );
+}
+
+#[test]
+fn illegal_moves_are_errors() {
+    let record = epd("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5; pv Kd2 Kd2;");
+
+    assert!(record.best_moves().is_err());
+    assert!(record.predicted_variation().is_err());
+}
+
+#[test]
+fn writes_operations() {
+    let record = epd(WAC_001);
+    assert_eq!(record.to_epd(), WAC_001);
+    assert_eq!(epd(&record.to_epd()).id(), Some("WAC.001"));
+
+    let mut record = epd("4k3/8/8/8/8/8/8/4K3 w - -");
+    record.set_operation("bm", vec!["Kd2".to_string(), "Ke2".to_string()]);
+    record.set_operation("c0", vec!["5".to_string()]);
+    record.set_operation("c1", vec!["a \"quoted\" word".to_string()]);
+    record.set_operation("pv", vec!["Kd2".to_string(), "Kd7".to_string()]);
+    assert_eq!(
+        record.to_epd(),
+        "4k3/8/8/8/8/8/8/4K3 w - - bm Kd2 Ke2; c0 \"5\"; c1 \"a \\\"quoted\\\" word\"; pv Kd2 Kd7;"
+    );
+}
+
+#[test]
+fn string_operands_round_trip() {
+    let operands = ["say \"hi\"", "C:\\temp\\", "a; b", "\"", ""];
+
+    let mut record = epd("4k3/8/8/8/8/8/8/4K3 w - -");
+    for (i, operand) in operands.iter().enumerate() {
+        record.set_operation(&format!("c{i}"), vec![operand.to_string()]);
+    }
+
+    let read = epd(&record.to_epd());
+    for (i, operand) in operands.iter().enumerate() {
+        assert_eq!(read.comment(i as u8), Some(*operand));
+    }
+    assert_eq!(read.to_epd(), record.to_epd());
+
+    // A backslash cannot end a string
+    assert!(EPD::from_epd("4k3/8/8/8/8/8/8/4K3 w - - c0 \"open\\").is_err());
+}
+
+#[test]
+fn counters_are_only_written_on_request() {
+    let record = epd("4k3/8/8/8/8/8/8/4K3 b - - hmvc 12; fmvn 40; id \"end\";");
+
+    assert_eq!(record.state.halfmove_clock, 12);
+    assert_eq!(record.state.fullmove_counter, 40);
+    assert_eq!(record.operation("hmvc"), None);
+
+    assert_eq!(record.to_epd(), "4k3/8/8/8/8/8/8/4K3 b - - id \"end\";");
+    assert_eq!(
+        record.to_epd_with_counters(),
+        "4k3/8/8/8/8/8/8/4K3 b - - id \"end\"; hmvc 12; fmvn 40;"
+    );
+}
@@ -0,0 +1,47 @@
+use std::io;
+
+use knightmare::{
+    datagen::DatagenError,
+    error::ErrorType,
+    fen::FromFENError,
+    pgn::{PGNError, PGNErrorKind},
+    san::FromSANError,
+    syzygy::{MaterialError, TablebaseError},
+    uci::{OptionError, ProtocolError},
+};
+
+/// One error of every type
+fn errors() -> Vec<ErrorType> {
+    vec![
+        ErrorType::IO(io::Error::other("io")),
+        ErrorType::FEN(FromFENError::InvalidSquare("z9".to_string())),
+        ErrorType::SAN(FromSANError::InvalidFormat("Zz9".to_string())),
+        ErrorType::PGN(PGNError {
+            line: 1,
+            column: 1,
+            kind: PGNErrorKind::UnexpectedEnd,
+        }),
+        ErrorType::Protocol(ProtocolError::ExpectedPosition),
+        ErrorType::EngineOption(OptionError::UnknownOption("Foo".to_string())),
+        ErrorType::Tablebase(TablebaseError::NotFound {
+            tables: 0,
+            max_pieces: 0,
+        }),
+        ErrorType::Material(MaterialError::InvalidSignature("KvX".to_string())),
+        ErrorType::Datagen(DatagenError::NoOpening {
+            attempts: 1,
+            max_opening_score: 0,
+        }),
+    ]
+}
+
+#[test]
+fn exit_codes_are_distinct() {
+    let codes: Vec<i32> = errors().iter().map(ErrorType::exit_code).collect();
+
+    assert_eq!(codes, (2..=10).collect::<Vec<i32>>());
+    for (i, code) in codes.iter().enumerate() {
+        assert!(*code > 1, "{code}");
+        assert!(!codes[..i].contains(code), "{code}");
+    }
+}
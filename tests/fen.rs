@@ -0,0 +1,53 @@
+use knightmare::{
+    epd::EPD,
+    fen::{FENField, FENMode, FromFENError, FromFENString},
+    game::GameState,
+    variant::Variant,
+};
+
+/// Positions that are well-formed but cannot occur in a game
+const IMPOSSIBLE: [&str; 5] = [
+    "4k3/8/8/8/8/8/8/4K2P w - - 0 1",
+    "4k3/8/8/8/8/8/8/4K3 w K - 0 1",
+    "4k3/8/8/8/8/8/8/4K3 w - e3 0 1",
+    "4k3/4Q3/8/8/8/8/8/4K3 w - - 0 1",
+    "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+];
+
+#[test]
+fn strict_mode_rejects_impossible_positions() {
+    for fen in IMPOSSIBLE {
+        assert!(GameState::from_fen(fen).is_ok(), "{fen}");
+        assert!(
+            GameState::from_fen_with(fen, FENMode::Strict).is_err(),
+            "{fen}"
+        );
+        assert!(
+            GameState::from_variant_fen_with(fen, Variant::Standard, FENMode::Strict).is_err(),
+            "{fen}"
+        );
+    }
+
+    assert_eq!(
+        GameState::from_fen_with(IMPOSSIBLE[0], FENMode::Strict)
+            .unwrap_err()
+            .location(),
+        Some((FENField::Placement, 19))
+    );
+}
+
+#[test]
+fn strict_mode_skips_variant_rules() {
+    // The horde has no king, which is fine in Horde
+    let horde = Variant::Horde.start_fen();
+    assert!(GameState::from_variant_fen_with(horde, Variant::Horde, FENMode::Strict).is_ok());
+}
+
+#[test]
+fn epd_positions_are_checked() {
+    assert!(EPD::from_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Kd2;").is_ok());
+    assert!(matches!(
+        EPD::from_epd("4k3/4Q3/8/8/8/8/8/4K3 w - - bm Kd2;"),
+        Err(FromFENError::At { .. })
+    ));
+}
@@ -0,0 +1,82 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use knightmare::{
+    game::{GameState, Outcome},
+    mate::{MateResult, MateSearch},
+};
+
+mod common;
+use common::state;
+
+/// Plays `line` from `state` and checks that it ends in mate by the side to move
+fn assert_mates(state: &GameState, line: &[knightmare::moves::Move]) {
+    let end = line.iter().fold(*state, |state, mv| {
+        assert!(state.is_legal(*mv), "{mv} is illegal");
+        state.make_move(*mv)
+    });
+    assert_eq!(
+        end.outcome(),
+        Some(Outcome::Checkmate {
+            winner: state.side_to_move
+        })
+    );
+}
+
+/// Positions with the number of moves the side to move needs to mate
+const MATES: [(&str, u32); 3] = [
+    ("k7/8/1K6/8/8/8/8/7Q w - - 0 1", 1),
+    ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 1),
+    (
+        "r1b1kb1r/pppp1ppp/5q2/4n3/3KP3/2N3PN/PPP4P/R1BQ1B1R b kq - 0 1",
+        3,
+    ),
+];
+
+#[test]
+fn finds_shortest_mates() {
+    for (fen, expected) in MATES {
+        let state = state(fen);
+        match MateSearch::new().search(&state, 5, &mut |_, _| {}) {
+            MateResult::Mate { moves, line } => {
+                assert_eq!(moves, expected, "{fen}");
+                assert_eq!(line.len() as u32, 2 * moves - 1, "{fen}");
+                assert_mates(&state, &line);
+            }
+            result => panic!("{fen}: {result:?}"),
+        }
+    }
+}
+
+#[test]
+fn no_mate_within_limit() {
+    let state = state("4k3/8/8/8/8/8/8/4K2R w - - 0 1");
+    assert_eq!(
+        MateSearch::new().search(&state, 2, &mut |_, _| {}),
+        MateResult::NoMate
+    );
+}
+
+#[test]
+fn stop_aborts_the_search() {
+    let (fen, _) = MATES[2];
+    let state = state(fen);
+
+    let stop = Arc::new(AtomicBool::new(true));
+    assert_eq!(
+        MateSearch::with_stop(stop).search(&state, 5, &mut |_, _| {}),
+        MateResult::Aborted
+    );
+
+    // Stopped after the mate in 1 was refuted, before the mate in 3 is proven
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = stop.clone();
+    let result = MateSearch::with_stop(stop).search(&state, 5, &mut |moves, _| {
+        if moves == 1 {
+            flag.store(true, Ordering::Relaxed);
+        }
+    });
+    assert_eq!(result, MateResult::Aborted);
+}
@@ -0,0 +1,51 @@
+use knightmare::fen::FEN_START;
+
+mod common;
+use common::{perft, state};
+
+/// The standard perft positions with their node counts for depths 1 to 3
+const PERFT: [(&str, [u64; 3]); 6] = [
+    (FEN_START, [20, 400, 8902]),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        [48, 2039, 97862],
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", [14, 191, 2812]),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        [6, 264, 9467],
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        [44, 1486, 62379],
+    ),
+    (
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        [46, 2079, 89890],
+    ),
+];
+
+#[test]
+fn perft_reference_positions() {
+    for (fen, counts) in PERFT {
+        let state = state(fen);
+
+        for (depth, expected) in counts.iter().enumerate() {
+            assert_eq!(
+                perft(&state, depth as u32 + 1),
+                *expected,
+                "{fen} at depth {}",
+                depth + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn perft_deeper() {
+    assert_eq!(perft(&state(FEN_START), 4), 197281);
+    assert_eq!(
+        perft(&state("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"), 4),
+        43238
+    );
+}
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use knightmare::{
+    color::Color,
+    error::{Error, ErrorType},
+    game::{GameResult, Outcome},
+    pgn::{Game, PGNError, PGNErrorKind, PGNEval, PGNReader, Variation},
+    san::FromSANError,
+};
+
+fn read(pgn: &str) -> Vec<Result<Game, Error>> {
+    PGNReader::new(pgn.as_bytes()).collect()
+}
+
+/// Returns the moves of `variation` played from `game`'s start in SAN
+fn san(game: &Game, variation: &Variation) -> Vec<String> {
+    let mut state = game.start;
+    variation
+        .moves
+        .iter()
+        .map(|node| {
+            let san = node.mv.to_san(&state);
+            state = state.make_move(node.mv);
+            san
+        })
+        .collect()
+}
+
+fn pgn_error(error: &Error) -> &PGNError {
+    match &error.error {
+        ErrorType::PGN(e) => e,
+        e => panic!("Not a PGN error: {e}"),
+    }
+}
+
+#[test]
+fn reads_games_with_results() {
+    let games = read(
+        "[Event \"a\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n\n\
+         [Event \"b\"]\n\n1. d4 d5 1/2-1/2\n",
+    );
+
+    assert_eq!(games.len(), 2);
+    let first = games[0].as_ref().expect("First game");
+    assert_eq!(first.tag("Event"), Some("a"));
+    assert_eq!(first.result, Some(GameResult::WhiteWins));
+    assert_eq!(first.mainline.moves.len(), 7);
+    assert_eq!(
+        first.end_state().outcome(),
+        Some(Outcome::Checkmate {
+            winner: Color::White
+        })
+    );
+
+    let second = games[1].as_ref().expect("Second game");
+    assert_eq!(second.tag("Event"), Some("b"));
+    assert_eq!(second.result, Some(GameResult::Draw));
+    assert_eq!(san(second, &second.mainline), ["d4", "d5"]);
+}
+
+#[test]
+fn reads_nags_and_suffix_annotations() {
+    let games = read("1. e4! e5?! 2. Nf3 $14 Nc6 $2 $32 *");
+    let game = games[0].as_ref().expect("Game");
+
+    let nags: Vec<&[u8]> = game
+        .mainline
+        .moves
+        .iter()
+        .map(|node| node.nags.as_slice())
+        .collect();
+    assert_eq!(nags, [&[1][..], &[6], &[14], &[2, 32]]);
+}
+
+#[test]
+fn reads_clock_and_eval_commands() {
+    let games = read(
+        "1. e4 { [%eval 0.35] [%clk 1:02:03.5] best by test } \
+         e5 { [%eval #-3] } 2. Nf3 { [%unknown x] } *",
+    );
+    let game = games[0].as_ref().expect("Game");
+    let moves = &game.mainline.moves;
+
+    assert_eq!(moves[0].eval, Some(PGNEval::Centipawns(35)));
+    assert_eq!(moves[0].clock, Some(Duration::from_secs_f64(3723.5)));
+    assert_eq!(moves[0].comments, ["best by test"]);
+
+    assert_eq!(moves[1].eval, Some(PGNEval::Mate(-3)));
+    assert_eq!(moves[1].clock, None);
+    assert!(moves[1].comments.is_empty());
+
+    assert_eq!(moves[2].comments, ["[%unknown x]"]);
+}
+
+#[test]
+fn reads_nested_variations() {
+    let games = read("1. e4 e5 (1... c5 2. Nf3 (2. c3 d5) 2... d6) (1... e6) 2. Nf3 *");
+    let game = games[0].as_ref().expect("Game");
+
+    assert_eq!(san(game, &game.mainline), ["e4", "e5", "Nf3"]);
+
+    let reply = &game.mainline.moves[1];
+    assert_eq!(reply.variations.len(), 2);
+    let after_e4 = game.start.make_move(game.mainline.moves[0].mv);
+    let sicilian = &reply.variations[0];
+    let french = &reply.variations[1];
+    assert_eq!(sicilian.moves[0].mv.to_san(&after_e4), "c5");
+    assert_eq!(french.moves[0].mv.to_san(&after_e4), "e6");
+
+    // The inner variation replaces 2. Nf3 of the Sicilian
+    let alapin = &sicilian.moves[1].variations[0];
+    let after_c5 = after_e4.make_move(sicilian.moves[0].mv);
+    assert_eq!(alapin.moves[0].mv.to_san(&after_c5), "c3");
+    assert_eq!(alapin.moves.len(), 2);
+}
+
+#[test]
+fn reports_illegal_moves_and_continues() {
+    let games = read("[Event \"a\"]\n\n1. e4 e5 2. Ke3 *\n\n[Event \"b\"]\n\n1. d4 *\n");
+
+    assert_eq!(games.len(), 2);
+    let error = pgn_error(games[0].as_ref().expect_err("Illegal move"));
+    assert_eq!((error.line, error.column), (3, 13));
+    assert!(matches!(
+        error.kind,
+        PGNErrorKind::IllegalMove(FromSANError::IllegalMove(_))
+    ));
+
+    let game = games[1].as_ref().expect("Second game");
+    assert_eq!(game.tag("Event"), Some("b"));
+}
+
+#[test]
+fn recovers_at_the_next_game_after_an_unclosed_variation() {
+    let games = read("[Event \"a\"]\n\n1. e4 (1. d4 d5\n\n[Event \"b\"]\n\n1. d4 *");
+
+    assert_eq!(games.len(), 2);
+    let error = pgn_error(games[0].as_ref().expect_err("Unclosed variation"));
+    assert_eq!((error.line, error.column), (5, 1));
+
+    let game = games[1].as_ref().expect("Second game");
+    assert_eq!(game.tag("Event"), Some("b"));
+    assert_eq!(san(game, &game.mainline), ["d4"]);
+}
+
+#[test]
+fn locates_invalid_fen_tags() {
+    let games = read(
+        "[Event \"a\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2P w - - 0 1\"]\n[SetUp \"1\"]\n\
+         [Site \"b\"]\n\n1. Kd2 *\n",
+    );
+
+    let error = pgn_error(games[0].as_ref().expect_err("Invalid FEN"));
+    assert_eq!((error.line, error.column), (2, 6));
+    assert!(matches!(error.kind, PGNErrorKind::InvalidFEN(_)));
+}
+
+/// Reads a single game that is known to be valid
+fn game(pgn: &str) -> Game {
+    PGNReader::new(pgn.as_bytes())
+        .next()
+        .expect("No game")
+        .expect("Invalid game")
+}
+
+/// Returns the move text of a game in export format
+fn movetext(pgn: &str) -> &str {
+    pgn.split_once("\n\n").expect("No move text").1.trim_end()
+}
+
+#[test]
+fn writes_move_numbers_after_interruptions() {
+    let pgn = game("1. e4 {best} e5 2. Nf3 (2. Nc3 Nc6) Nc6 $1 3. Bb5 *").to_pgn();
+
+    assert_eq!(
+        movetext(&pgn),
+        "1. e4 {best} 1... e5 2. Nf3 (2. Nc3 Nc6) 2... Nc6 $1 3. Bb5 *"
+    );
+}
+
+#[test]
+fn wraps_closing_parentheses() {
+    // The variation ends exactly at column 80 if `)` is glued on without wrapping
+    let game =
+        game("1. e4 e5 2. Nf3 (2. Nc3 {a} 2... Nc6 3. f4 {hello there} (3. Bc4 Bc5) 3... exf4) *");
+    let pgn = game.to_pgn();
+
+    for line in movetext(&pgn).lines() {
+        assert!(line.len() < 80, "{line}");
+    }
+    assert_eq!(
+        movetext(&pgn),
+        "1. e4 e5 2. Nf3 (2. Nc3 {a} 2... Nc6 3. f4 {hello there} (3. Bc4 Bc5) 3...\nexf4) *"
+    );
+}
+
+#[test]
+fn wraps_long_games() {
+    let moves = "1. Nf3 Nf6 2. Ng1 Ng8 ".repeat(20);
+    let pgn = game(&format!("{moves}*")).to_pgn();
+
+    let lines: Vec<&str> = movetext(&pgn).lines().collect();
+    assert!(lines.len() > 1);
+    for line in &lines {
+        assert!(line.len() < 80, "{line}");
+        assert!(!line.starts_with(' ') && !line.ends_with(' '), "{line}");
+    }
+
+    // Wrapping keeps the moves intact
+    assert_eq!(game(&pgn).mainline.moves.len(), 80);
+}
+
+#[test]
+fn writes_escaped_tags() {
+    let mut game = Game::default();
+    game.set_tag("Event", "The \"Big\" Open");
+    game.set_tag("Annotator", "C:\\Users");
+    let pgn = game.to_pgn();
+
+    assert!(pgn.starts_with(
+        "[Event \"The \\\"Big\\\" Open\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n\
+         [Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n[Annotator \"C:\\\\Users\"]\n\n"
+    ));
+
+    let read = self::game(&pgn);
+    assert_eq!(read.tag("Event"), Some("The \"Big\" Open"));
+    assert_eq!(read.tag("Annotator"), Some("C:\\Users"));
+}
+
+#[test]
+fn writes_setup_and_fen_for_custom_starts() {
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30";
+    let game = game(&format!(
+        "[FEN \"{fen}\"]\n[SetUp \"1\"]\n\n30... Kd7 31. e4 *"
+    ));
+    let pgn = game.to_pgn();
+
+    assert!(pgn.contains(&format!(
+        "[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"{fen}\"]\n\n"
+    )));
+    assert_eq!(pgn.matches("[FEN").count(), 1);
+    assert_eq!(movetext(&pgn), "30... Kd7 31. e4 *");
+
+    assert!(!Game::default().to_pgn().contains("[FEN"));
+}
@@ -0,0 +1,103 @@
+use knightmare::{
+    fen::FEN_START,
+    game::GameState,
+    moves::Move,
+    polyglot::{BookEntry, BookSelection, PolyglotBook},
+    random::Random,
+};
+
+mod common;
+use common::state;
+
+/// Plays `moves` in UCI notation from the starting position
+fn play(moves: &[&str]) -> GameState {
+    moves.iter().fold(state(FEN_START), |state, mv| {
+        let mv = Move::from_uci(mv).filter(|mv| state.is_legal(*mv));
+        state.make_move(mv.expect("Illegal test move"))
+    })
+}
+
+/// The reference keys from the Polyglot book format specification
+const KEYS: [(&[&str], u64); 9] = [
+    (&[], 0x463b96181691fc9c),
+    (&["e2e4"], 0x823c9b50fd114196),
+    (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+    (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+    (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+    (
+        &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"],
+        0x652a607ca3f242c1,
+    ),
+    (
+        &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"],
+        0x00fdd303c946bdd9,
+    ),
+    (
+        &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"],
+        0x3c8123ea7b067637,
+    ),
+    (
+        &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+        0x5c3f9b829b279560,
+    ),
+];
+
+#[test]
+fn reference_keys() {
+    for (moves, key) in KEYS {
+        assert_eq!(play(moves).polyglot_key(), key, "{moves:?}");
+    }
+}
+
+#[test]
+fn entries_round_trip() {
+    let entry = BookEntry {
+        key: 0x463b96181691fc9c,
+        mv: 0x031c,
+        weight: 42,
+        learn: 7,
+    };
+    let bytes = entry.to_bytes();
+
+    assert_eq!(bytes[..8], 0x463b96181691fc9cu64.to_be_bytes());
+    assert_eq!(BookEntry::from_bytes(&bytes), entry);
+}
+
+#[test]
+fn castling_is_encoded_as_capturing_the_rook() {
+    let state = state("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let castle = Move::from_uci("e1g1").expect("Valid move");
+
+    let encoded = BookEntry::encode_move(castle, &state);
+    // e1 to h1
+    assert_eq!(encoded, 4 << 6 | 7);
+
+    let entry = BookEntry {
+        key: state.polyglot_key(),
+        mv: encoded,
+        weight: 1,
+        learn: 0,
+    };
+    assert_eq!(entry.decode_move(&state), Some(castle));
+}
+
+#[test]
+fn picks_the_heaviest_move() {
+    let start = state(FEN_START);
+    let entry = |mv: &str, weight| BookEntry {
+        key: start.polyglot_key(),
+        mv: BookEntry::encode_move(Move::from_uci(mv).expect("Valid move"), &start),
+        weight,
+        learn: 0,
+    };
+    let book = PolyglotBook::from_entries(vec![entry("d2d4", 10), entry("e2e4", 30)]);
+
+    let moves = book.moves(&start);
+    assert_eq!(moves.len(), 2);
+    assert_eq!(moves[0].1, 30);
+    assert_eq!(
+        book.pick(&start, BookSelection::Best, &mut Random::new(1)),
+        Move::from_uci("e2e4")
+    );
+    assert!(book.lookup(&play(&["e2e4"])).is_empty());
+}
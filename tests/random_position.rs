@@ -0,0 +1,74 @@
+use knightmare::{
+    color::Color,
+    fen::{FENMode, ToFENString},
+    game::GameState,
+    piece::Piece,
+    random_position::{Constraints, MaterialRange, PositionGenerator},
+    syzygy::{Material, MaterialError},
+};
+
+fn fens(signature: &str, constraints: Constraints, seed: u64) -> Vec<String> {
+    let material = MaterialRange::parse(signature).expect("Valid signature");
+    PositionGenerator::new(material, constraints, seed)
+        .take(20)
+        .map(|state| state.to_fen())
+        .collect()
+}
+
+#[test]
+fn same_seed_gives_same_positions() {
+    let constraints = Constraints {
+        not_in_check: true,
+        ..Default::default()
+    };
+
+    let first = fens("KRPvKNP", constraints, 42);
+    assert_eq!(first.len(), 20);
+    assert_eq!(first, fens("KRPvKNP", constraints, 42));
+    assert_ne!(first, fens("KRPvKNP", constraints, 43));
+}
+
+#[test]
+fn positions_have_the_material_and_meet_the_constraints() {
+    let constraints = Constraints {
+        not_in_check: true,
+        no_captures: true,
+        back_rank_pawns: false,
+    };
+
+    for fen in fens("KQPPvKRB", constraints, 7) {
+        let state = GameState::from_fen_with(&fen, FENMode::Strict).expect("Legal position");
+        assert_eq!(Material::of(&state).name(), "KQPPvKRB", "{fen}");
+        assert!(!state.is_check(), "{fen}");
+        assert!(
+            state.legal_moves().iter().all(|mv| !state.is_capture(*mv)),
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn ranges_follow_the_signature() {
+    let range = MaterialRange::parse("KQvKRR").expect("Valid signature");
+    assert_eq!(
+        range.counts[Color::White as usize][Piece::Queen as usize],
+        (1, 1)
+    );
+    assert_eq!(
+        range.counts[Color::Black as usize][Piece::Rook as usize],
+        (2, 2)
+    );
+    assert_eq!(
+        range.counts[Color::Black as usize][Piece::Pawn as usize],
+        (0, 0)
+    );
+
+    assert!(matches!(
+        MaterialRange::parse("KRvR"),
+        Err(MaterialError::InvalidSignature(_))
+    ));
+    assert!(matches!(
+        MaterialRange::parse("KPPPPPPPPPvK"),
+        Err(MaterialError::Impossible(_))
+    ));
+}
@@ -0,0 +1,83 @@
+use knightmare::{
+    fen::FEN_START,
+    moves::Move,
+    render::{PieceStyle, RenderOptions},
+};
+
+mod common;
+use common::state;
+
+#[test]
+fn renders_the_start_position() {
+    let expected = [
+        " 8  r  n  b  q  k  b  n  r ",
+        " 7  p  p  p  p  p  p  p  p ",
+        " 6  .  .  .  .  .  .  .  . ",
+        " 5  .  .  .  .  .  .  .  . ",
+        " 4  .  .  .  .  .  .  .  . ",
+        " 3  .  .  .  .  .  .  .  . ",
+        " 2  P  P  P  P  P  P  P  P ",
+        " 1  R  N  B  Q  K  B  N  R ",
+        "    a  b  c  d  e  f  g  h ",
+    ]
+    .join("\n");
+
+    let state = state(FEN_START);
+    assert_eq!(state.render(&RenderOptions::default()), expected);
+    assert_eq!(state.board.to_string(), expected);
+    assert_eq!(state.to_string(), format!("{expected}\nWhite to move"));
+}
+
+#[test]
+fn renders_flipped_boards_with_highlights() {
+    let options = RenderOptions {
+        flipped: true,
+        highlight: Move::from_uci("e2e4"),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        state(FEN_START).board.render(&options),
+        [
+            " 1  R  N  B  K  Q  B  N  R ",
+            " 2  P  P  P [P] P  P  P  P ",
+            " 3  .  .  .  .  .  .  .  . ",
+            " 4  .  .  . [.] .  .  .  . ",
+            " 5  .  .  .  .  .  .  .  . ",
+            " 6  .  .  .  .  .  .  .  . ",
+            " 7  p  p  p  p  p  p  p  p ",
+            " 8  r  n  b  k  q  b  n  r ",
+            "    h  g  f  e  d  c  b  a ",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn marks_the_checked_king() {
+    let state = state("4k3/8/8/8/8/8/8/4K2r w - - 0 1");
+    let options = RenderOptions {
+        labels: false,
+        ..Default::default()
+    };
+
+    let rendered = state.render(&options);
+    assert_eq!(rendered.lines().last(), Some(" .  .  .  . (K) .  .  r "));
+    assert_eq!(rendered.lines().count(), 8);
+
+    // The board alone does not know about checks
+    assert!(!state.board.render(&options).contains('('));
+}
+
+#[test]
+fn renders_unicode_pieces() {
+    let options = RenderOptions {
+        style: PieceStyle::Unicode,
+        labels: false,
+        ..Default::default()
+    };
+
+    let rendered = state(FEN_START).render(&options);
+    assert_eq!(rendered.lines().next(), Some(" ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜ "));
+    assert_eq!(rendered.lines().last(), Some(" ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖ "));
+}
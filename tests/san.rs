@@ -0,0 +1,117 @@
+use knightmare::{fen::FEN_START, game::GameState, moves::Move, san::FromSANError};
+
+mod common;
+use common::state;
+
+fn uci(mv: &str) -> Move {
+    Move::from_uci(mv).expect("Invalid test move")
+}
+
+/// Asserts that `mv` is written as `san` and that `san` parses back to `mv`
+fn assert_san(state: &GameState, mv: &str, san: &str) {
+    assert_eq!(uci(mv).to_san(state), san);
+    assert_eq!(state.parse_san(san).ok(), Some(uci(mv)), "{san}");
+}
+
+#[test]
+fn disambiguates_by_file_rank_and_square() {
+    let knights = state("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+    assert_san(&knights, "b1d2", "Nbd2");
+    assert_san(&knights, "f1d2", "Nfd2");
+    assert_san(&knights, "f1g3", "Ng3");
+
+    let rooks = state("4k3/R7/8/8/8/8/8/R3K3 w - - 0 1");
+    assert_san(&rooks, "a1a4", "R1a4");
+    assert_san(&rooks, "a7a4", "R7a4");
+
+    let queens = state("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+    assert_san(&queens, "a1b2", "Qa1b2");
+    assert_san(&queens, "c1b2", "Qcb2");
+    assert_san(&queens, "a3b2", "Q3b2");
+}
+
+#[test]
+fn writes_captures_promotions_and_checks() {
+    let state = state("r3k3/1P6/8/3p4/4P3/8/8/4K3 w q - 0 1");
+    assert_san(&state, "e4d5", "exd5");
+    assert_san(&state, "b7a8q", "bxa8=Q+");
+    assert_san(&state, "b7b8n", "b8=N");
+
+    let mate = self::state("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    assert_san(&mate, "a1a8", "Ra8#");
+}
+
+#[test]
+fn writes_castling() {
+    let state = state("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_san(&state, "e1g1", "O-O");
+    assert_san(&state, "e1c1", "O-O-O");
+}
+
+#[test]
+fn accepts_sloppy_notation() {
+    let start = state(FEN_START);
+    assert_eq!(start.parse_san("Ng1f3").ok(), Some(uci("g1f3")));
+    assert_eq!(start.parse_san("e2e4").ok(), Some(uci("e2e4")));
+    assert_eq!(start.parse_san("e2-e4").ok(), Some(uci("e2e4")));
+    assert_eq!(start.parse_san("Nf3!?").ok(), Some(uci("g1f3")));
+
+    let castling = state("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(castling.parse_san("0-0").ok(), Some(uci("e1g1")));
+    assert_eq!(castling.parse_san("0-0-0").ok(), Some(uci("e1c1")));
+
+    let promotion = state("8/4P3/8/8/8/8/k7/4K3 w - - 0 1");
+    assert_eq!(promotion.parse_san("e8Q").ok(), Some(uci("e7e8q")));
+    assert_eq!(promotion.parse_san("e8=Q").ok(), Some(uci("e7e8q")));
+
+    // Captures without `x`
+    let capture = state("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    assert_eq!(capture.parse_san("ed5").ok(), Some(uci("e4d5")));
+    assert_eq!(capture.parse_san("e4d5").ok(), Some(uci("e4d5")));
+    let piece_capture = state("4k3/8/8/3p4/8/4N3/8/4K3 w - - 0 1");
+    assert_eq!(piece_capture.parse_san("Nd5").ok(), Some(uci("e3d5")));
+}
+
+#[test]
+fn rejects_ambiguous_illegal_and_invalid_moves() {
+    let knights = state("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+    match knights.parse_san("Nd2") {
+        Err(FromSANError::AmbiguousMove { candidates, .. }) => {
+            assert_eq!(candidates, ["Nbd2", "Nfd2"])
+        }
+        result => panic!("{result:?}"),
+    }
+
+    let start = state(FEN_START);
+    for illegal in ["Qh5", "e5", "O-O", "Nd2", "exd3"] {
+        assert!(
+            matches!(start.parse_san(illegal), Err(FromSANError::IllegalMove(_))),
+            "{illegal}"
+        );
+    }
+    for invalid in ["", "Zz9", "N", "e9", "Nxx3"] {
+        assert!(
+            matches!(
+                start.parse_san(invalid),
+                Err(FromSANError::InvalidFormat(_))
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn all_moves_round_trip() {
+    for fen in [
+        FEN_START,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ] {
+        let state = state(fen);
+        for mv in state.legal_moves() {
+            let san = mv.to_san(&state);
+            assert_eq!(state.parse_san(&san).ok(), Some(mv), "{fen}: {san}");
+        }
+    }
+}
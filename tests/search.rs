@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use knightmare::{
+    fen::FEN_START,
+    moves::Move,
+    search::{Clock, SearchLimits, Searcher, TimeManager},
+};
+
+mod common;
+use common::state;
+
+fn clock(time: u64, increment: u64, moves_to_go: Option<u32>) -> SearchLimits {
+    SearchLimits {
+        clock: Some(Clock {
+            time: Duration::from_millis(time),
+            increment: Duration::from_millis(increment),
+            moves_to_go,
+        }),
+        ..Default::default()
+    }
+}
+
+fn limits(time: &TimeManager) -> (Option<Duration>, Option<Duration>) {
+    (time.soft_limit(), time.hard_limit())
+}
+
+#[test]
+fn movetime_is_a_hard_limit() {
+    let limits = SearchLimits {
+        movetime: Some(Duration::from_millis(1000)),
+        move_overhead: Duration::from_millis(50),
+        ..Default::default()
+    };
+
+    let time = TimeManager::new(&limits);
+    assert_eq!(
+        self::limits(&time),
+        (None, Some(Duration::from_millis(950)))
+    );
+    assert_eq!(
+        self::limits(&TimeManager::new(&SearchLimits::default())),
+        (None, None)
+    );
+}
+
+#[test]
+fn clock_time_is_spread_over_the_moves() {
+    // Sudden death spreads the time over 30 moves, the hard limit is four shares
+    assert_eq!(
+        limits(&TimeManager::new(&clock(60_000, 0, None))),
+        (Some(Duration::from_secs(2)), Some(Duration::from_secs(8)))
+    );
+
+    // Three quarters of the increment are added to the share
+    let mut limits = clock(10_000, 1000, Some(10));
+    limits.move_overhead = Duration::from_millis(100);
+    assert_eq!(
+        self::limits(&TimeManager::new(&limits)),
+        (
+            Some(Duration::from_millis(1740)),
+            Some(Duration::from_millis(6960))
+        )
+    );
+}
+
+#[test]
+fn clock_time_is_never_used_up() {
+    // The last move before the time control keeps a tenth in reserve
+    assert_eq!(
+        limits(&TimeManager::new(&clock(10_000, 0, Some(1)))),
+        (Some(Duration::from_secs(9)), Some(Duration::from_secs(9)))
+    );
+
+    // Otherwise a quarter, even with a large increment
+    assert_eq!(
+        limits(&TimeManager::new(&clock(4000, 5000, Some(20)))),
+        (Some(Duration::from_secs(3)), Some(Duration::from_secs(3)))
+    );
+
+    // The overhead cannot eat up all the time
+    let mut limits = clock(30, 0, Some(1));
+    limits.move_overhead = Duration::from_millis(50);
+    let time = TimeManager::new(&limits);
+    assert!(time.hard_limit().is_some_and(|hard| hard > Duration::ZERO));
+}
+
+#[test]
+fn unstable_best_moves_stretch_the_soft_limit() {
+    let mut time = TimeManager::new(&clock(60_000, 0, None));
+    let e4 = Move::from_uci("e2e4");
+    let d4 = Move::from_uci("d2d4");
+
+    time.update(e4, 20);
+    time.update(e4, 20);
+    assert_eq!(time.soft_limit(), Some(Duration::from_secs(2)));
+
+    time.update(d4, 20);
+    assert_eq!(time.soft_limit(), Some(Duration::from_secs(4)));
+
+    // A falling score stretches it further, up to three times
+    time.update(e4, -80);
+    assert_eq!(time.soft_limit(), Some(Duration::from_secs(6)));
+}
+
+#[test]
+fn multi_pv_returns_distinct_moves_best_first() {
+    let mut searcher = Searcher::new(16);
+    let limits = SearchLimits {
+        depth: Some(4),
+        ..Default::default()
+    };
+
+    let lines = searcher.analyze(&state(FEN_START), &[], &limits, 4);
+    assert_eq!(lines.len(), 4);
+
+    let moves: Vec<Move> = lines.iter().map(|line| line.pv[0]).collect();
+    for (i, mv) in moves.iter().enumerate() {
+        assert!(!moves[..i].contains(mv), "{mv} is searched twice");
+    }
+    assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    // Positions with fewer legal moves get fewer lines
+    let lines = searcher.analyze(&state("k7/8/1K6/8/8/8/8/7R b - - 0 1"), &[], &limits, 4);
+    assert_eq!(lines.len(), 1);
+}
+
+#[test]
+fn single_threaded_search_is_deterministic() {
+    let state = state("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let limits = SearchLimits {
+        depth: Some(5),
+        ..Default::default()
+    };
+
+    let search = || Searcher::new(16).search(&state, &[], &limits);
+    let (first, second) = (search(), search());
+    assert_eq!(first.nodes, second.nodes);
+    assert_eq!(first.best_move, second.best_move);
+    assert_eq!(first.pv, second.pv);
+    assert_eq!(first.score, second.score);
+}
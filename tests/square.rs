@@ -0,0 +1,114 @@
+use knightmare::{
+    coordinate::{Coordinate, File, Rank},
+    square::{Direction, Square, SquareColor},
+};
+
+fn sq(square: &str) -> Square {
+    square.parse().expect("Invalid test square")
+}
+
+/// Returns the mask of `squares`
+fn mask(squares: &[&str]) -> u64 {
+    squares
+        .iter()
+        .fold(0, |mask, square| mask | sq(square).bit())
+}
+
+#[test]
+fn parses_and_writes_squares() {
+    assert_eq!(sq("a1"), Square::A1);
+    assert_eq!(sq("h8"), Square::H8);
+    assert_eq!(sq("e4").index(), 28);
+    assert_eq!(sq("e4").to_string(), "e4");
+    assert_eq!(
+        (sq("c7").file(), sq("c7").rank()),
+        (File::FileC, Rank::Rank7)
+    );
+
+    for square in ["", "e", "i1", "a9", "e44", "E4"] {
+        assert!(square.parse::<Square>().is_err(), "{square}");
+    }
+
+    // Every square survives the trip through a coordinate
+    for square in Square::all() {
+        assert_eq!(Square::from(Coordinate::from(square)), square);
+        assert_eq!(square.to_string().parse::<Square>().ok(), Some(square));
+    }
+    assert_eq!(Square::new(64), None);
+}
+
+#[test]
+fn distances() {
+    assert_eq!(sq("a1").chebyshev_distance(sq("h8")), 7);
+    assert_eq!(sq("a1").manhattan_distance(sq("h8")), 14);
+    assert_eq!(sq("e4").chebyshev_distance(sq("f6")), 2);
+    assert_eq!(sq("e4").manhattan_distance(sq("f6")), 3);
+    assert_eq!(sq("d5").chebyshev_distance(sq("d5")), 0);
+
+    for a in Square::all() {
+        for b in Square::all() {
+            assert_eq!(a.chebyshev_distance(b), b.chebyshev_distance(a));
+            assert!(a.manhattan_distance(b) >= a.chebyshev_distance(b));
+        }
+    }
+}
+
+#[test]
+fn colors_and_directions() {
+    assert_eq!(sq("a1").color(), SquareColor::Dark);
+    assert_eq!(sq("h1").color(), SquareColor::Light);
+    assert_eq!(sq("d1").color(), SquareColor::Light);
+    assert_eq!(sq("e1").color(), SquareColor::Dark);
+
+    assert_eq!(sq("e4").direction_to(sq("e8")), Some(Direction::North));
+    assert_eq!(sq("e4").direction_to(sq("b1")), Some(Direction::SouthWest));
+    assert_eq!(sq("e4").direction_to(sq("a4")), Some(Direction::West));
+    assert_eq!(sq("e4").direction_to(sq("f6")), None);
+    assert_eq!(sq("e4").direction_to(sq("e4")), None);
+
+    let ray: Vec<Square> = sq("f6").ray(Direction::NorthEast).collect();
+    assert_eq!(ray, [sq("g7"), sq("h8")]);
+    assert_eq!(sq("h8").step(Direction::North), None);
+    assert_eq!(sq("e4").offset(-4, 3), Some(sq("a7")));
+    assert_eq!(sq("e4").offset(4, 0), None);
+}
+
+#[test]
+fn squares_between() {
+    assert_eq!(sq("a1").between(sq("a4")), mask(&["a2", "a3"]));
+    assert_eq!(sq("h1").between(sq("d5")), mask(&["g2", "f3", "e4"]));
+    assert_eq!(sq("c3").between(sq("f3")), mask(&["d3", "e3"]));
+    assert_eq!(sq("e4").between(sq("e5")), 0);
+    assert_eq!(sq("e4").between(sq("f6")), 0);
+    assert_eq!(sq("e4").between(sq("e4")), 0);
+
+    for a in Square::all() {
+        for b in Square::all() {
+            assert_eq!(a.between(b), b.between(a), "{a} {b}");
+        }
+    }
+}
+
+#[test]
+fn lines_through_squares() {
+    let diagonal = mask(&["a1", "b2", "c3", "d4", "e5", "f6", "g7", "h8"]);
+    assert_eq!(sq("c3").line(sq("f6")), diagonal);
+    assert_eq!(sq("h8").line(sq("a1")), diagonal);
+    assert_eq!(
+        sq("b5").line(sq("g5")),
+        mask(&["a5", "b5", "c5", "d5", "e5", "f5", "g5", "h5"])
+    );
+    assert_eq!(sq("e4").line(sq("f6")), 0);
+
+    // The squares between always lie on the line, without its ends
+    for a in Square::all() {
+        for b in Square::all() {
+            let line = a.line(b);
+            assert_eq!(a.between(b) & !line, 0, "{a} {b}");
+            if line != 0 {
+                assert_eq!(line & (a.bit() | b.bit()), a.bit() | b.bit(), "{a} {b}");
+                assert_eq!(a.between(b) & (a.bit() | b.bit()), 0, "{a} {b}");
+            }
+        }
+    }
+}
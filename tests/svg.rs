@@ -0,0 +1,87 @@
+use knightmare::{
+    coordinate::Coordinate,
+    fen::{FromFENString, FEN_START},
+    moves::Move,
+    svg::SvgOptions,
+};
+
+mod common;
+use common::state;
+
+fn coordinate(square: &str) -> Coordinate {
+    Coordinate::from_fen(square).expect("Invalid test square")
+}
+
+#[test]
+fn draws_every_piece_once() {
+    let svg = state(FEN_START).to_svg(&SvgOptions::default());
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<use ").count(), 32);
+    assert_eq!(svg.matches("<use xlink:href=\"#white-king\"").count(), 1);
+    assert_eq!(svg.matches("<use xlink:href=\"#black-pawn\"").count(), 8);
+
+    let empty = state("4k3/8/8/8/8/8/8/4K3 w - - 0 1").to_svg(&SvgOptions::default());
+    assert_eq!(empty.matches("<use ").count(), 2);
+}
+
+#[test]
+fn coordinates_add_a_margin_and_labels() {
+    let svg = state(FEN_START).to_svg(&SvgOptions::default());
+    assert!(svg.contains("viewBox=\"0 0 405 405\""));
+    // Every file and rank is labeled on both sides
+    assert_eq!(svg.matches("<text ").count(), 32);
+
+    let options = SvgOptions {
+        square_size: 50,
+        coordinates: false,
+        ..Default::default()
+    };
+    let svg = state(FEN_START).to_svg(&options);
+    assert!(svg.contains("viewBox=\"0 0 400 400\""));
+    assert!(!svg.contains("<text "));
+}
+
+#[test]
+fn flipping_turns_the_board() {
+    let options = SvgOptions {
+        coordinates: false,
+        ..Default::default()
+    };
+    let king = "href=\"#white-king\" transform=\"translate";
+
+    let svg = state(FEN_START).to_svg(&options);
+    assert!(svg.contains(&format!("{king}(180 315)")));
+
+    let flipped = SvgOptions {
+        flipped: true,
+        ..options
+    };
+    let svg = state(FEN_START).to_svg(&flipped);
+    assert!(svg.contains(&format!("{king}(135 0)")));
+}
+
+#[test]
+fn marks_moves_highlights_and_checks() {
+    let mv = |uci| Move::from_uci(uci).expect("Invalid test move");
+    let options = SvgOptions {
+        last_move: Some(mv("e2e4")),
+        arrows: vec![mv("g1f3"), mv("b1c3")],
+        highlights: vec![coordinate("d5")],
+        ..Default::default()
+    };
+
+    let svg = state(FEN_START).to_svg(&options);
+    assert_eq!(svg.matches("fill=\"#cdd26a\"").count(), 2);
+    assert_eq!(svg.matches("fill=\"#3c9bd9\"").count(), 1);
+    assert_eq!(svg.matches("<line ").count(), 2);
+    assert!(!svg.contains("fill=\"#dc3c32\""));
+
+    // The king in check is marked on its own
+    let check = state("4k3/8/8/8/8/8/8/4K2r w - - 0 1").to_svg(&SvgOptions {
+        coordinates: false,
+        ..Default::default()
+    });
+    assert!(check.contains("<rect x=\"180\" y=\"315\" width=\"45\" height=\"45\" fill=\"#dc3c32\""));
+}
@@ -0,0 +1,99 @@
+use knightmare::{
+    eval::evaluate,
+    fen::{FromFENString, ToFENString},
+    game::GameState,
+    variant::Variant,
+};
+
+mod common;
+use common::{perft, state};
+
+/// Positions with castling rights, en passant squares and unbalanced material
+const POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "6k1/5ppp/8/8/8/8/1q3PPP/6K1 b - - 0 30",
+    "4k3/8/8/3n4/8/8/2P5/4K2R b K - 3 41",
+];
+
+#[test]
+fn eval_symmetric_under_color_flip() {
+    for fen in POSITIONS {
+        let state = state(fen);
+        let flipped = state.flip_colors();
+
+        assert_eq!(evaluate(&state), evaluate(&flipped), "{fen}");
+        assert_eq!(flipped.flip_colors().to_fen(), fen);
+    }
+}
+
+#[test]
+fn color_flip_keeps_moves() {
+    let flipped =
+        state("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").flip_colors();
+    assert_eq!(
+        flipped.to_fen(),
+        "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+    );
+    assert_eq!(flipped.hash(), flipped.compute_hash());
+    assert_eq!(perft(&flipped, 3), 97862);
+
+    let en_passant = state(POSITIONS[5]).flip_colors();
+    assert_eq!(
+        en_passant.to_fen(),
+        "rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b KQkq f3 0 3"
+    );
+    assert_eq!(perft(&en_passant, 2), perft(&state(POSITIONS[5]), 2));
+}
+
+#[test]
+fn mirror_and_flip_and_mirror() {
+    let state = state(POSITIONS[2]);
+
+    let mirrored = state.mirror();
+    assert_eq!(
+        mirrored.to_fen(),
+        "8/5p2/4p3/r5PK/k1p3R1/8/1P1P4/8 w - - 0 1"
+    );
+    assert_eq!(evaluate(&mirrored), evaluate(&state));
+    assert_eq!(perft(&mirrored, 3), perft(&state, 3));
+    assert_eq!(mirrored.mirror().to_fen(), state.to_fen());
+
+    let rotated = state.flip_and_mirror();
+    assert_eq!(
+        rotated.to_fen(),
+        "8/1p1p4/8/K1P3r1/R5pk/4P3/5P2/8 b - - 0 1"
+    );
+    assert_eq!(rotated.flip_and_mirror().to_fen(), state.to_fen());
+
+    // Castling does not survive mirroring, the king would castle the other way
+    let castling = GameState::from_fen(POSITIONS[0]).unwrap();
+    assert_eq!(
+        castling.mirror().to_fen(),
+        "rnbkqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR w - - 0 1"
+    );
+}
+
+#[test]
+fn promoted_pieces_follow_the_transforms() {
+    let state =
+        GameState::from_variant_fen("rQ~2k3/8/8/8/8/8/8/4K3[] b - - 0 1", Variant::Crazyhouse)
+            .expect("Valid position");
+
+    assert_eq!(
+        state.flip_colors().to_fen(),
+        "4k3/8/8/8/8/8/8/Rq~2K3[] w - - 0 1"
+    );
+    assert_eq!(
+        state.mirror().to_fen(),
+        "3k2Q~r/8/8/8/8/8/8/3K4[] b - - 0 1"
+    );
+    assert_eq!(
+        state.flip_and_mirror().to_fen(),
+        "3k4/8/8/8/8/8/8/3K2q~R[] w - - 0 1"
+    );
+}
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use knightmare::{
+    color::Color,
+    fen::FromFENString,
+    game::GameState,
+    piece::Piece,
+    syzygy::{Material, Tablebase, WDL},
+};
+
+/// The directory with the 3-4-man Syzygy files, `SYZYGY_PATH` if it is set
+fn tablebase_path() -> String {
+    std::env::var("SYZYGY_PATH").unwrap_or_else(|_| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("syzygy")
+            .display()
+            .to_string()
+    })
+}
+
+fn state(fen: &str) -> GameState {
+    GameState::from_fen(fen).expect("Invalid test FEN")
+}
+
+/// Creates a directory with a KQvK WDL table that stores the same value
+/// for every position: white to move wins, black to move loses
+fn single_value_tablebase(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("knightmare-syzygy-{name}"));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let mut file = vec![0x71, 0xE8, 0x23, 0x5D];
+    // Split flag, the order of the groups and the pieces for both sides
+    file.extend([0x01, 0x00, 0x66, 0x55, 0xEE]);
+    // Word alignment, then a single value part per side
+    file.push(0);
+    file.extend([0x80, 4, 0x80, 0]);
+
+    std::fs::write(directory.join("KQvK.rtbw"), file).unwrap();
+    directory
+}
+
+#[test]
+fn missing_directory_is_an_error() {
+    assert!(Tablebase::open("/nonexistent/knightmare/syzygy").is_err());
+}
+
+#[test]
+fn parses_material_signatures() {
+    let material = Material::parse("KRPPvKQ").expect("Valid signature");
+    assert_eq!(material.count(Color::White, Piece::Pawn), 2);
+    assert_eq!(material.count(Color::Black, Piece::Queen), 1);
+    assert_eq!(material.name(), "KRPPvKQ");
+
+    // Signatures larger than the Syzygy tables are still valid
+    assert_eq!(Material::parse("KQQQQvKRRRR").unwrap().piece_count(), 10);
+
+    for invalid in ["KRK", "KRvQ", "KKvK", "KXvK", "krvk", ""] {
+        assert!(Material::parse(invalid).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn empty_tablebase_does_not_probe() {
+    let tablebase = Tablebase::new();
+
+    assert_eq!(tablebase.max_pieces(), 0);
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k3/8/8/8/8/8/8/4K2Q w - - 0 1")),
+        None
+    );
+}
+
+#[test]
+fn single_value_table() {
+    let directory = single_value_tablebase("single");
+    let tablebase = Tablebase::open(directory.to_str().unwrap()).unwrap();
+
+    assert_eq!(tablebase.max_pieces(), 3);
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k3/8/8/8/8/8/8/4K2Q w - - 0 1")),
+        Some(WDL::Win)
+    );
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k3/8/8/8/8/8/8/4K2Q b - - 0 1")),
+        Some(WDL::Loss)
+    );
+
+    // The colors are swapped if black has the queen
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k2q/8/8/8/8/8/8/4K3 b - - 0 1")),
+        Some(WDL::Win)
+    );
+
+    // Capturing the queen draws, whatever the table stores
+    assert_eq!(
+        tablebase.probe_wdl(&state("4K3/8/8/8/8/8/3kQ3/8 b - - 0 1")),
+        Some(WDL::Draw)
+    );
+
+    // Castling rights keep positions out of the tablebases
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k3/8/8/8/8/8/8/4K2Q w K - 0 1")),
+        None
+    );
+}
+
+#[test]
+fn corrupted_table_is_ignored() {
+    let directory = std::env::temp_dir().join("knightmare-syzygy-corrupted");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("KRvK.rtbw"), [0u8; 64]).unwrap();
+
+    let tablebase = Tablebase::open(directory.to_str().unwrap()).unwrap();
+    assert_eq!(
+        tablebase.probe_wdl(&state("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")),
+        None
+    );
+}
+
+#[test]
+#[ignore = "needs KQvK, KRvK and KPvK .rtbw/.rtbz in tests/syzygy or SYZYGY_PATH"]
+fn wdl_three_men() {
+    let tablebase = Tablebase::open(&tablebase_path()).unwrap();
+    assert!(tablebase.max_pieces() >= 3);
+
+    let cases = [
+        ("4k3/8/8/8/8/8/8/4K2Q w - - 0 1", WDL::Win),
+        ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", WDL::Win),
+        ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", WDL::Win),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", WDL::Loss),
+        ("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1", WDL::Draw),
+        ("4K3/8/8/8/8/8/8/4k2q w - - 0 1", WDL::Loss),
+        ("k6Q/8/1K6/8/8/8/8/8 b - - 0 1", WDL::Loss),
+    ];
+
+    for (fen, expected) in cases {
+        assert_eq!(tablebase.probe_wdl(&state(fen)), Some(expected), "{fen}");
+    }
+}
+
+#[test]
+#[ignore = "needs the 3-4-man Syzygy files in tests/syzygy or SYZYGY_PATH"]
+fn wdl_minor_and_four_men() {
+    let tablebase = Tablebase::open(&tablebase_path()).unwrap();
+    assert!(tablebase.max_pieces() >= 4);
+
+    let cases = [
+        ("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", WDL::Draw),
+        ("4k3/8/8/8/8/8/8/1N2K3 b - - 0 1", WDL::Draw),
+        ("3qk3/8/8/8/8/8/8/3QK3 w - - 0 1", WDL::Draw),
+        ("3qk3/8/8/8/8/8/8/3QK3 b - - 0 1", WDL::Draw),
+    ];
+
+    for (fen, expected) in cases {
+        assert_eq!(tablebase.probe_wdl(&state(fen)), Some(expected), "{fen}");
+    }
+}
+
+#[test]
+#[ignore = "needs KQvK, KRvK and KPvK .rtbw/.rtbz in tests/syzygy or SYZYGY_PATH"]
+fn dtz_three_men() {
+    let tablebase = Tablebase::open(&tablebase_path()).unwrap();
+
+    // Mate in one, only by Qg8
+    let mate_in_one = state("k7/8/1K6/8/8/8/8/6Q1 w - - 0 1");
+    assert_eq!(tablebase.probe_dtz(&mate_in_one), Some(1));
+
+    let moves = tablebase.rank_root_moves(&mate_in_one, false).unwrap();
+    assert!(moves[0].dtz > 0);
+    assert!(moves
+        .iter()
+        .any(|m| m.mv.to_string() == "g1g8" && m.dtz == 1));
+
+    // Mated
+    assert_eq!(
+        tablebase.probe_dtz(&state("k6Q/8/1K6/8/8/8/8/8 b - - 0 1")),
+        Some(-1)
+    );
+
+    // Winning and losing DTZ agree in sign with WDL
+    for fen in [
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 b - - 0 1",
+        "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1",
+        "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1",
+    ] {
+        let state = state(fen);
+        let wdl = tablebase.probe_wdl(&state).unwrap();
+        let dtz = tablebase.probe_dtz(&state).unwrap();
+        assert_eq!(dtz.signum(), (wdl as i32).signum(), "{fen}");
+    }
+}
@@ -0,0 +1,253 @@
+use knightmare::{
+    color::Color,
+    fen::ToFENString,
+    game::{GameState, Outcome},
+    moves::Move,
+    piece::Piece,
+    variant::Variant,
+};
+
+mod common;
+use common::perft;
+
+fn state(fen: &str, variant: Variant) -> GameState {
+    GameState::from_variant_fen(fen, variant).expect("Invalid test FEN")
+}
+
+/// Reference positions of the variants with their node counts for depths 1 to 3
+const PERFT: [(Variant, &str, [u64; 3]); 12] = [
+    (
+        Variant::Crazyhouse,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+        [20, 400, 8902],
+    ),
+    (
+        Variant::Atomic,
+        "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1",
+        [40, 1238, 45237],
+    ),
+    (
+        Variant::Atomic,
+        "rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1",
+        [28, 833, 23353],
+    ),
+    (
+        Variant::ThreeCheck,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 1+1 0 1",
+        [48, 2039, 97848],
+    ),
+    (
+        Variant::Antichess,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+        [20, 400, 8067],
+    ),
+    (
+        Variant::Antichess,
+        "8/1p6/8/8/8/8/P7/8 w - - 0 1",
+        [2, 4, 4],
+    ),
+    (
+        Variant::Horde,
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1",
+        [8, 128, 1274],
+    ),
+    (
+        Variant::Horde,
+        "4k3/pp4q1/3P2p1/8/P3PP2/PPP2r2/PPP5/PPPP4 b - - 0 1",
+        [30, 241, 6633],
+    ),
+    (
+        Variant::Horde,
+        "k7/5p2/4p2P/3p2P1/2p2P2/1p2P2P/p2P2P1/2P2P2 w - - 0 1",
+        [13, 172, 2205],
+    ),
+    (
+        Variant::RacingKings,
+        "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+        [21, 421, 11264],
+    ),
+    (
+        Variant::RacingKings,
+        "4brn1/2K2k2/8/8/8/8/8/8 w - - 0 1",
+        [6, 33, 178],
+    ),
+    (
+        Variant::KingOfTheHill,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        [20, 400, 8902],
+    ),
+];
+
+#[test]
+fn perft_reference_positions() {
+    for (variant, fen, counts) in PERFT {
+        let state = state(fen, variant);
+
+        for (depth, expected) in counts.iter().enumerate() {
+            assert_eq!(
+                perft(&state, depth as u32 + 1),
+                *expected,
+                "{variant} {fen} at depth {}",
+                depth + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn start_positions() {
+    for variant in Variant::ALL {
+        let state = state(variant.start_fen(), variant);
+        assert_eq!(state.to_fen(), variant.start_fen(), "{variant}");
+        assert_eq!(Variant::from_uci_name(variant.uci_name()), Some(variant));
+    }
+}
+
+#[test]
+fn antichess_rules() {
+    // The capture on b5 is forced
+    let forced = state(
+        "rnbqkbnr/p1pppppp/8/1p6/8/4P3/PPPP1PPP/RNBQKBNR w - - 0 2",
+        Variant::Antichess,
+    );
+    assert_eq!(forced.legal_moves(), vec![Move::from_uci("f1b5").unwrap()]);
+    assert!(!forced.is_legal(Move::from_uci("e3e4").unwrap()));
+
+    // Pawns may promote to a king, which can be captured like any piece
+    let promotion = state("8/P7/8/8/8/8/8/k7 w - - 0 1", Variant::Antichess);
+    assert!(promotion.is_legal(Move::from_uci("a7a8k").unwrap()));
+    assert!(!promotion.is_check());
+
+    // Losing all pieces wins
+    let empty = state("8/8/8/8/8/8/8/k7 w - - 0 1", Variant::Antichess);
+    assert_eq!(
+        empty.outcome(),
+        Some(Outcome::VariantWin {
+            winner: Color::White
+        })
+    );
+}
+
+#[test]
+fn horde_rules() {
+    // Pawns on the first rank can push twice, but not be captured en passant
+    let horde = state("4k3/8/8/8/8/1p6/8/P7 w - - 0 1", Variant::Horde);
+    let double = Move::from_uci("a1a3").unwrap();
+    assert!(horde.is_legal(double));
+    assert_eq!(horde.make_move(double).en_passant, None);
+
+    // Black wins by capturing the whole horde
+    let captured = state("4k3/8/8/8/8/8/8/8 w - - 0 1", Variant::Horde);
+    assert_eq!(
+        captured.outcome(),
+        Some(Outcome::VariantWin {
+            winner: Color::Black
+        })
+    );
+}
+
+#[test]
+fn racing_kings_rules() {
+    // Giving check is not allowed
+    let check = state("8/8/8/8/8/8/k7/6RK w - - 0 1", Variant::RacingKings);
+    assert!(!check.is_legal(Move::from_uci("g1a1").unwrap()));
+    assert!(check.is_legal(Move::from_uci("g1b1").unwrap()));
+
+    // Black gets one more move to draw after white arrives
+    let catch_up = state("2K5/5k2/8/8/8/8/8/8 b - - 0 1", Variant::RacingKings);
+    assert_eq!(catch_up.outcome(), None);
+    assert_eq!(
+        catch_up
+            .make_move(Move::from_uci("f7f8").unwrap())
+            .outcome(),
+        Some(Outcome::VariantDraw)
+    );
+
+    let too_late = state("2K5/8/5k2/8/8/8/8/8 b - - 0 1", Variant::RacingKings);
+    assert_eq!(
+        too_late.outcome(),
+        Some(Outcome::VariantWin {
+            winner: Color::White
+        })
+    );
+}
+
+#[test]
+fn crazyhouse_drops() {
+    // Every piece in the pocket can be dropped on every empty square
+    let drops = state(
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+        Variant::Crazyhouse,
+    );
+    assert_eq!(perft(&drops, 1), 301);
+    assert_eq!(perft(&drops, 2), 75353);
+
+    // Pawns cannot be dropped on the first and last rank
+    let pawn = state("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1", Variant::Crazyhouse);
+    assert!(pawn.is_legal(Move::from_uci("P@a2").unwrap()));
+    assert!(!pawn.is_legal(Move::from_uci("P@a1").unwrap()));
+    assert!(!pawn.is_legal(Move::from_uci("P@a8").unwrap()));
+}
+
+#[test]
+fn crazyhouse_holdings() {
+    // Captured pieces go to the pocket of the capturing side and can be dropped
+    let capture = state("4k3/8/8/3n4/4P3/8/8/4K3[] w - - 0 1", Variant::Crazyhouse);
+    let captured = capture.make_move(Move::from_uci("e4d5").unwrap());
+    assert_eq!(captured.pockets.count(Color::White, Piece::Knight), 1);
+    assert_eq!(captured.to_fen(), "4k3/8/8/3P4/8/8/8/4K3[N] b - - 0 1");
+
+    let replied = captured.make_move(Move::from_uci("e8d7").unwrap());
+    let drop = Move::from_uci("N@f6").unwrap();
+    assert!(replied.is_legal(drop));
+    assert!(replied.make_move(drop).pockets.is_empty());
+
+    // A promoted piece goes back to the pocket as a pawn
+    let promotion = state("r3k3/1P6/8/8/8/8/8/4K3[] w - - 0 1", Variant::Crazyhouse);
+    let promoted = promotion.make_move(Move::from_uci("b7b8q").unwrap());
+    assert_eq!(promoted.to_fen(), "rQ~2k3/8/8/8/8/8/8/4K3[] b - - 0 1");
+
+    let recaptured = promoted.make_move(Move::from_uci("a8b8").unwrap());
+    assert_eq!(recaptured.pockets.count(Color::Black, Piece::Pawn), 1);
+    assert_eq!(recaptured.pockets.count(Color::Black, Piece::Queen), 0);
+    assert_eq!(recaptured.promoted, 0);
+}
+
+#[test]
+fn king_of_the_hill_rules() {
+    let state = state("4k3/8/8/8/8/3K4/8/8 w - - 0 1", Variant::KingOfTheHill);
+    assert_eq!(state.outcome(), None);
+
+    // Reaching one of the four center squares wins
+    assert_eq!(
+        state.make_move(Move::from_uci("d3d4").unwrap()).outcome(),
+        Some(Outcome::VariantWin {
+            winner: Color::White
+        })
+    );
+    assert_eq!(
+        state.make_move(Move::from_uci("d3c4").unwrap()).outcome(),
+        None
+    );
+}
+
+#[test]
+fn three_check_rules() {
+    let state = state("4k3/8/8/8/8/8/8/R3K3 w - - 1+3 0 1", Variant::ThreeCheck);
+    assert_eq!(state.checks, [2, 0]);
+    assert_eq!(state.outcome(), None);
+
+    // The third check wins even though it is not mate
+    let third = state.make_move(Move::from_uci("a1a8").unwrap());
+    assert_eq!(third.checks, [3, 0]);
+    assert_eq!(
+        third.outcome(),
+        Some(Outcome::VariantWin {
+            winner: Color::White
+        })
+    );
+
+    let quiet = state.make_move(Move::from_uci("a1a7").unwrap());
+    assert_eq!(quiet.checks, [2, 0]);
+    assert_eq!(quiet.outcome(), None);
+}